@@ -0,0 +1,754 @@
+use crate::glob;
+use bytes::{Bytes, BytesMut};
+use redis_client::resp::Resp;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::time::{Duration, Instant};
+
+const WRONGTYPE: &str = "WRONGTYPE Operation against a key holding the wrong kind of value";
+const NOT_INTEGER: &str = "ERR value is not an integer or out of range";
+const SYNTAX: &str = "ERR syntax error";
+
+// An error reply
+type Result<T> = std::result::Result<T, Resp>;
+
+#[derive(Debug)]
+enum Value {
+    String(Bytes),
+    Hash(HashMap<Bytes, Bytes>),
+    List(VecDeque<Bytes>),
+    Set(HashSet<Bytes>),
+}
+
+impl Value {
+    fn type_name(&self) -> &'static str {
+        match self {
+            Value::String(_) => "string",
+            Value::Hash(_) => "hash",
+            Value::List(_) => "list",
+            Value::Set(_) => "set",
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        match self {
+            Value::String(_) => false,
+            Value::Hash(h) => h.is_empty(),
+            Value::List(l) => l.is_empty(),
+            Value::Set(s) => s.is_empty(),
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Entry {
+    value: Value,
+    expires_at: Option<Instant>,
+}
+
+impl Entry {
+    fn new(value: Value) -> Entry {
+        Entry {
+            value,
+            expires_at: None,
+        }
+    }
+
+    fn is_expired(&self, now: Instant) -> bool {
+        self.expires_at.is_some_and(|at| at <= now)
+    }
+}
+
+/// Returns the arity of a command run by `Db::execute`, including the
+/// command name. A negative arity -N means at least N arguments.
+pub fn arity(name: &str) -> Option<i64> {
+    let arity = match name {
+        "get" | "strlen" | "getdel" | "incr" | "decr" | "ttl" | "pttl" | "persist" | "type"
+        | "keys" | "hgetall" | "hkeys" | "hvals" | "hlen" | "llen" | "smembers" | "scard" => 2,
+        "append" | "incrby" | "decrby" | "rename" | "hget" | "hexists" | "lindex" | "sismember" => {
+            3
+        }
+        "hincrby" | "lrange" => 4,
+        "dbsize" | "flushdb" => -1,
+        "set" | "expire" | "pexpire" | "hset" | "lpush" | "rpush" => -3,
+        "del" | "exists" | "mget" | "lpop" | "rpop" => -2,
+        "mset" | "hmget" | "hdel" | "sadd" | "srem" => -3,
+        _ => return None,
+    };
+    Some(arity)
+}
+
+/// One of the numbered databases selected by SELECT. Expired keys are removed
+/// when accessed.
+#[derive(Debug, Default)]
+pub struct Db {
+    entries: HashMap<Bytes, Entry>,
+}
+
+impl Db {
+    pub fn len(&mut self) -> usize {
+        self.purge();
+        self.entries.len()
+    }
+
+    pub fn expires(&mut self) -> usize {
+        self.purge();
+        self.entries
+            .values()
+            .filter(|e| e.expires_at.is_some())
+            .count()
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+
+    fn purge(&mut self) {
+        let now = Instant::now();
+        self.entries.retain(|_, e| !e.is_expired(now));
+    }
+
+    fn entry(&mut self, key: &Bytes) -> Option<&mut Entry> {
+        if self
+            .entries
+            .get(key)
+            .is_some_and(|e| e.is_expired(Instant::now()))
+        {
+            self.entries.remove(key);
+        }
+        self.entries.get_mut(key)
+    }
+
+    fn value(&mut self, key: &Bytes) -> Option<&mut Value> {
+        self.entry(key).map(|e| &mut e.value)
+    }
+
+    // Removes a collection once its last element was removed
+    fn remove_if_empty(&mut self, key: &Bytes) {
+        if self.entries.get(key).is_some_and(|e| e.value.is_empty()) {
+            self.entries.remove(key);
+        }
+    }
+
+    fn string(&mut self, key: &Bytes) -> Result<Option<&Bytes>> {
+        match self.value(key) {
+            None => Ok(None),
+            Some(Value::String(s)) => Ok(Some(s)),
+            Some(_) => Err(error(WRONGTYPE)),
+        }
+    }
+
+    fn hash(&mut self, key: &Bytes) -> Result<Option<&mut HashMap<Bytes, Bytes>>> {
+        match self.value(key) {
+            None => Ok(None),
+            Some(Value::Hash(h)) => Ok(Some(h)),
+            Some(_) => Err(error(WRONGTYPE)),
+        }
+    }
+
+    fn list(&mut self, key: &Bytes) -> Result<Option<&mut VecDeque<Bytes>>> {
+        match self.value(key) {
+            None => Ok(None),
+            Some(Value::List(l)) => Ok(Some(l)),
+            Some(_) => Err(error(WRONGTYPE)),
+        }
+    }
+
+    fn set(&mut self, key: &Bytes) -> Result<Option<&mut HashSet<Bytes>>> {
+        match self.value(key) {
+            None => Ok(None),
+            Some(Value::Set(s)) => Ok(Some(s)),
+            Some(_) => Err(error(WRONGTYPE)),
+        }
+    }
+
+    // Returns the value at key, inserting `empty` if the key does not exist
+    fn value_or_insert(&mut self, key: &Bytes, empty: fn() -> Value) -> &mut Value {
+        if self.entry(key).is_none() {
+            self.entries.insert(key.clone(), Entry::new(empty()));
+        }
+        &mut self.entries.get_mut(key).unwrap().value
+    }
+
+    /// Runs a command whose name is lowercase and arity was checked with `arity`.
+    pub fn execute(&mut self, name: &str, args: &[Bytes]) -> Resp {
+        match self.run(name, args) {
+            Ok(resp) => resp,
+            Err(resp) => resp,
+        }
+    }
+
+    fn run(&mut self, name: &str, args: &[Bytes]) -> Result<Resp> {
+        match name {
+            "dbsize" => Ok(Resp::Integer(self.len() as i64)),
+            "flushdb" => {
+                self.clear();
+                Ok(ok())
+            }
+            "del" => Ok(Resp::Integer(
+                args.iter()
+                    .filter(|key| self.entry(key).is_some() && self.entries.remove(*key).is_some())
+                    .count() as i64,
+            )),
+            "exists" => Ok(Resp::Integer(
+                args.iter().filter(|key| self.entry(key).is_some()).count() as i64,
+            )),
+            "expire" => self.expire(name, args, Duration::from_secs),
+            "pexpire" => self.expire(name, args, Duration::from_millis),
+            "ttl" => Ok(self.ttl(&args[0], |d| (d.as_millis() as i64 + 500) / 1000)),
+            "pttl" => Ok(self.ttl(&args[0], |d| d.as_millis() as i64)),
+            "persist" => Ok(Resp::Integer(match self.entry(&args[0]) {
+                Some(e) => e.expires_at.take().is_some() as i64,
+                None => 0,
+            })),
+            "type" => Ok(Resp::SimpleString(
+                self.value(&args[0])
+                    .map_or("none", |v| v.type_name())
+                    .to_string(),
+            )),
+            "keys" => {
+                self.purge();
+                Ok(Resp::Array(
+                    self.entries
+                        .keys()
+                        .filter(|key| glob::matches(&args[0], key))
+                        .map(|key| Resp::BulkString(key.clone()))
+                        .collect(),
+                ))
+            }
+            "rename" => match self.entry(&args[0]).is_some() {
+                true => {
+                    let entry = self.entries.remove(&args[0]).unwrap();
+                    self.entries.insert(args[1].clone(), entry);
+                    Ok(ok())
+                }
+                false => Err(error("ERR no such key")),
+            },
+
+            "get" => Ok(bulk(self.string(&args[0])?.cloned())),
+            "getdel" => {
+                let value = self.string(&args[0])?.cloned();
+                self.entries.remove(&args[0]);
+                Ok(bulk(value))
+            }
+            "set" => self.set_string(args),
+            "mget" => Ok(Resp::Array(
+                args.iter()
+                    .map(|key| bulk(self.string(key).ok().flatten().cloned()))
+                    .collect(),
+            )),
+            "mset" => {
+                if !args.len().is_multiple_of(2) {
+                    return Err(wrong_arity(name));
+                }
+                for pair in args.chunks(2) {
+                    let value = Value::String(pair[1].clone());
+                    self.entries.insert(pair[0].clone(), Entry::new(value));
+                }
+                Ok(ok())
+            }
+            "append" => {
+                let mut value =
+                    BytesMut::from(&self.string(&args[0])?.cloned().unwrap_or_default()[..]);
+                value.extend_from_slice(&args[1]);
+                let len = value.len();
+                self.replace_string(&args[0], value.freeze());
+                Ok(Resp::Integer(len as i64))
+            }
+            "strlen" => Ok(Resp::Integer(
+                self.string(&args[0])?.map_or(0, |s| s.len()) as i64
+            )),
+            "incr" => self.incr_by(&args[0], 1),
+            "decr" => self.incr_by(&args[0], -1),
+            "incrby" => self.incr_by(&args[0], parse_int(&args[1])?),
+            "decrby" => self.incr_by(
+                &args[0],
+                parse_int(&args[1])?
+                    .checked_neg()
+                    .ok_or_else(|| error(NOT_INTEGER))?,
+            ),
+
+            "hset" => {
+                if args.len().is_multiple_of(2) {
+                    return Err(wrong_arity(name));
+                }
+                self.hash(&args[0])?;
+                let Value::Hash(hash) =
+                    self.value_or_insert(&args[0], || Value::Hash(HashMap::new()))
+                else {
+                    unreachable!()
+                };
+                let added = args[1..]
+                    .chunks(2)
+                    .filter(|pair| hash.insert(pair[0].clone(), pair[1].clone()).is_none())
+                    .count();
+                Ok(Resp::Integer(added as i64))
+            }
+            "hget" => Ok(bulk(
+                self.hash(&args[0])?.and_then(|h| h.get(&args[1]).cloned()),
+            )),
+            "hmget" => {
+                let hash = self.hash(&args[0])?;
+                Ok(Resp::Array(
+                    args[1..]
+                        .iter()
+                        .map(|field| bulk(hash.as_ref().and_then(|h| h.get(field).cloned())))
+                        .collect(),
+                ))
+            }
+            "hgetall" => Ok(Resp::Array(
+                self.hash(&args[0])?
+                    .map(|h| {
+                        h.iter()
+                            .flat_map(|(k, v)| {
+                                [Resp::BulkString(k.clone()), Resp::BulkString(v.clone())]
+                            })
+                            .collect()
+                    })
+                    .unwrap_or_default(),
+            )),
+            "hkeys" => Ok(array(
+                self.hash(&args[0])?.map(|h| h.keys().cloned().collect()),
+            )),
+            "hvals" => Ok(array(
+                self.hash(&args[0])?.map(|h| h.values().cloned().collect()),
+            )),
+            "hlen" => Ok(Resp::Integer(
+                self.hash(&args[0])?.map_or(0, |h| h.len()) as i64
+            )),
+            "hexists" => Ok(Resp::Integer(
+                self.hash(&args[0])?
+                    .is_some_and(|h| h.contains_key(&args[1])) as i64,
+            )),
+            "hdel" => {
+                let removed = match self.hash(&args[0])? {
+                    Some(h) => args[1..].iter().filter(|f| h.remove(*f).is_some()).count(),
+                    None => 0,
+                };
+                self.remove_if_empty(&args[0]);
+                Ok(Resp::Integer(removed as i64))
+            }
+            "hincrby" => {
+                let increment = parse_int(&args[2])?;
+                self.hash(&args[0])?;
+                let Value::Hash(hash) =
+                    self.value_or_insert(&args[0], || Value::Hash(HashMap::new()))
+                else {
+                    unreachable!()
+                };
+                let current = match hash.get(&args[1]) {
+                    Some(v) => {
+                        parse_int(v).map_err(|_| error("ERR hash value is not an integer"))?
+                    }
+                    None => 0,
+                };
+                let value = current
+                    .checked_add(increment)
+                    .ok_or_else(|| error("ERR increment or decrement would overflow"))?;
+                hash.insert(args[1].clone(), Bytes::from(value.to_string()));
+                Ok(Resp::Integer(value))
+            }
+
+            "lpush" | "rpush" => {
+                self.list(&args[0])?;
+                let Value::List(list) =
+                    self.value_or_insert(&args[0], || Value::List(VecDeque::new()))
+                else {
+                    unreachable!()
+                };
+                for value in &args[1..] {
+                    match name {
+                        "lpush" => list.push_front(value.clone()),
+                        _ => list.push_back(value.clone()),
+                    }
+                }
+                Ok(Resp::Integer(list.len() as i64))
+            }
+            "lpop" | "rpop" => {
+                let count = match args.get(1) {
+                    Some(count) if args.len() == 2 => Some(
+                        usize::try_from(parse_int(count)?)
+                            .map_err(|_| error("ERR value is out of range, must be positive"))?,
+                    ),
+                    Some(_) => return Err(wrong_arity(name)),
+                    None => None,
+                };
+                let list = match self.list(&args[0])? {
+                    Some(list) => list,
+                    None => return Ok(Resp::Null),
+                };
+                let mut pop = || match name {
+                    "lpop" => list.pop_front(),
+                    _ => list.pop_back(),
+                };
+                let resp = match count {
+                    Some(count) => Resp::Array(
+                        (0..count)
+                            .map_while(|_| pop())
+                            .map(Resp::BulkString)
+                            .collect(),
+                    ),
+                    None => bulk(pop()),
+                };
+                self.remove_if_empty(&args[0]);
+                Ok(resp)
+            }
+            "lrange" => {
+                let (start, stop) = (parse_int(&args[1])?, parse_int(&args[2])?);
+                Ok(Resp::Array(match self.list(&args[0])? {
+                    Some(list) => match range(start, stop, list.len()) {
+                        Some((start, stop)) => list
+                            .range(start..=stop)
+                            .cloned()
+                            .map(Resp::BulkString)
+                            .collect(),
+                        None => vec![],
+                    },
+                    None => vec![],
+                }))
+            }
+            "llen" => Ok(Resp::Integer(
+                self.list(&args[0])?.map_or(0, |l| l.len()) as i64
+            )),
+            "lindex" => {
+                let index = parse_int(&args[1])?;
+                Ok(bulk(self.list(&args[0])?.and_then(|list| {
+                    let index = if index < 0 {
+                        list.len() as i64 + index
+                    } else {
+                        index
+                    };
+                    usize::try_from(index)
+                        .ok()
+                        .and_then(|i| list.get(i).cloned())
+                })))
+            }
+
+            "sadd" => {
+                self.set(&args[0])?;
+                let Value::Set(set) = self.value_or_insert(&args[0], || Value::Set(HashSet::new()))
+                else {
+                    unreachable!()
+                };
+                let added = args[1..]
+                    .iter()
+                    .filter(|m| set.insert((*m).clone()))
+                    .count();
+                Ok(Resp::Integer(added as i64))
+            }
+            "srem" => {
+                let removed = match self.set(&args[0])? {
+                    Some(set) => args[1..].iter().filter(|m| set.remove(*m)).count(),
+                    None => 0,
+                };
+                self.remove_if_empty(&args[0]);
+                Ok(Resp::Integer(removed as i64))
+            }
+            "smembers" => Ok(array(
+                self.set(&args[0])?.map(|s| s.iter().cloned().collect()),
+            )),
+            "sismember" => Ok(Resp::Integer(
+                self.set(&args[0])?.is_some_and(|s| s.contains(&args[1])) as i64,
+            )),
+            "scard" => Ok(Resp::Integer(
+                self.set(&args[0])?.map_or(0, |s| s.len()) as i64
+            )),
+
+            _ => Err(error(&format!("ERR unknown command '{}'", name))),
+        }
+    }
+
+    // SET key value [NX | XX] [GET] [EX seconds | PX milliseconds | KEEPTTL]
+    fn set_string(&mut self, args: &[Bytes]) -> Result<Resp> {
+        let (key, value) = (&args[0], &args[1]);
+        let (mut nx, mut xx, mut get, mut keepttl) = (false, false, false, false);
+        let mut expire = None;
+
+        let mut options = args[2..].iter();
+        while let Some(option) = options.next() {
+            match option.to_ascii_uppercase().as_slice() {
+                b"NX" if !xx => nx = true,
+                b"XX" if !nx => xx = true,
+                b"GET" => get = true,
+                b"KEEPTTL" if expire.is_none() => keepttl = true,
+                unit @ (b"EX" | b"PX") if expire.is_none() && !keepttl => {
+                    let n = parse_int(options.next().ok_or_else(|| error(SYNTAX))?)?;
+                    if n <= 0 {
+                        return Err(error("ERR invalid expire time in 'set' command"));
+                    }
+                    expire = Some(match unit {
+                        b"EX" => Duration::from_secs(n as u64),
+                        _ => Duration::from_millis(n as u64),
+                    });
+                }
+                _ => return Err(error(SYNTAX)),
+            }
+        }
+
+        let old = match get {
+            true => self.string(key)?.cloned(),
+            false => None,
+        };
+        let exists = self.entry(key).is_some();
+        if (nx && exists) || (xx && !exists) {
+            return Ok(if get { bulk(old) } else { Resp::Null });
+        }
+
+        let expires_at = match (keepttl, expire) {
+            (true, _) => self.entry(key).and_then(|e| e.expires_at),
+            (false, None) => None,
+            (false, Some(d)) => Some(expires_at(d, "set")?),
+        };
+        self.entries.insert(
+            key.clone(),
+            Entry {
+                value: Value::String(value.clone()),
+                expires_at,
+            },
+        );
+
+        Ok(if get { bulk(old) } else { ok() })
+    }
+
+    // Sets a string keeping the time to live of the key
+    fn replace_string(&mut self, key: &Bytes, value: Bytes) {
+        match self.entry(key) {
+            Some(entry) => entry.value = Value::String(value),
+            None => {
+                self.entries
+                    .insert(key.clone(), Entry::new(Value::String(value)));
+            }
+        }
+    }
+
+    fn incr_by(&mut self, key: &Bytes, increment: i64) -> Result<Resp> {
+        let current = match self.string(key)? {
+            Some(v) => parse_int(v)?,
+            None => 0,
+        };
+        let value = current
+            .checked_add(increment)
+            .ok_or_else(|| error("ERR increment or decrement would overflow"))?;
+        self.replace_string(key, Bytes::from(value.to_string()));
+        Ok(Resp::Integer(value))
+    }
+
+    // EXPIRE key timeout [NX | XX | GT | LT]
+    fn expire(&mut self, name: &str, args: &[Bytes], unit: fn(u64) -> Duration) -> Result<Resp> {
+        let timeout = parse_int(&args[1])?;
+        let condition = match args.get(2) {
+            Some(c) if args.len() == 3 => Some(c.to_ascii_uppercase()),
+            Some(_) => return Err(error(SYNTAX)),
+            None => None,
+        };
+
+        let entry = match self.entry(&args[0]) {
+            Some(entry) => entry,
+            None => return Ok(Resp::Integer(0)),
+        };
+        let expires_at = expires_at(unit(timeout.max(0) as u64), name)?;
+        let allowed = match condition.as_deref() {
+            None => true,
+            Some(b"NX") => entry.expires_at.is_none(),
+            Some(b"XX") => entry.expires_at.is_some(),
+            // no expiry counts as an infinite time to live
+            Some(b"GT") => entry.expires_at.is_some_and(|at| expires_at > at),
+            Some(b"LT") => entry.expires_at.is_none_or(|at| expires_at < at),
+            Some(_) => return Err(error(SYNTAX)),
+        };
+        if !allowed {
+            return Ok(Resp::Integer(0));
+        }
+
+        if timeout <= 0 {
+            self.entries.remove(&args[0]);
+        } else {
+            entry.expires_at = Some(expires_at);
+        }
+        Ok(Resp::Integer(1))
+    }
+
+    fn ttl(&mut self, key: &Bytes, convert: fn(Duration) -> i64) -> Resp {
+        Resp::Integer(match self.entry(key) {
+            None => -2,
+            Some(Entry {
+                expires_at: None, ..
+            }) => -1,
+            Some(Entry {
+                expires_at: Some(at),
+                ..
+            }) => convert(at.saturating_duration_since(Instant::now())),
+        })
+    }
+}
+
+// Resolves LRANGE style inclusive indexes, which may be negative, against a length
+fn range(start: i64, stop: i64, len: usize) -> Option<(usize, usize)> {
+    let len = len as i64;
+    let start = if start < 0 {
+        (len + start).max(0)
+    } else {
+        start
+    };
+    let stop = if stop < 0 {
+        len + stop
+    } else {
+        stop.min(len - 1)
+    };
+    if start > stop || start >= len {
+        None
+    } else {
+        Some((start as usize, stop as usize))
+    }
+}
+
+pub fn parse_int(arg: &[u8]) -> Result<i64> {
+    std::str::from_utf8(arg)
+        .ok()
+        .and_then(|s| s.parse().ok())
+        .ok_or_else(|| error(NOT_INTEGER))
+}
+
+pub fn error(msg: &str) -> Resp {
+    Resp::Error(msg.to_string())
+}
+
+pub fn wrong_arity(name: &str) -> Resp {
+    error(&format!(
+        "ERR wrong number of arguments for '{}' command",
+        name
+    ))
+}
+
+pub fn ok() -> Resp {
+    Resp::SimpleString("OK".to_string())
+}
+
+fn bulk(value: Option<Bytes>) -> Resp {
+    value.map_or(Resp::Null, Resp::BulkString)
+}
+
+fn array(values: Option<Vec<Bytes>>) -> Resp {
+    Resp::Array(
+        values
+            .unwrap_or_default()
+            .into_iter()
+            .map(Resp::BulkString)
+            .collect(),
+    )
+}
+
+// The instant a time to live ends, which may not be representable
+fn expires_at(ttl: Duration, command: &str) -> Result<Instant> {
+    Instant::now()
+        .checked_add(ttl)
+        .ok_or_else(|| error(&format!("ERR invalid expire time in '{}' command", command)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(db: &mut Db, args: &[&str]) -> Resp {
+        let name = args[0].to_ascii_lowercase();
+        let args: Vec<Bytes> = args[1..]
+            .iter()
+            .map(|a| Bytes::from(a.to_string()))
+            .collect();
+        db.execute(&name, &args)
+    }
+
+    fn bulk_str(s: &str) -> Resp {
+        Resp::BulkString(Bytes::from(s.to_string()))
+    }
+
+    #[test]
+    fn strings() {
+        let mut db = Db::default();
+        assert_eq!(ok(), run(&mut db, &["SET", "k", "v"]));
+        assert_eq!(bulk_str("v"), run(&mut db, &["GET", "k"]));
+        assert_eq!(Resp::Null, run(&mut db, &["SET", "k", "w", "NX"]));
+        assert_eq!(bulk_str("v"), run(&mut db, &["SET", "k", "w", "GET"]));
+        assert_eq!(Resp::Integer(3), run(&mut db, &["APPEND", "k", "xy"]));
+        assert_eq!(Resp::Integer(-1), run(&mut db, &["TTL", "k"]));
+
+        assert_eq!(Resp::Integer(5), run(&mut db, &["INCRBY", "n", "5"]));
+        assert_eq!(Resp::Integer(4), run(&mut db, &["DECR", "n"]));
+        assert_eq!(error(NOT_INTEGER), run(&mut db, &["INCR", "k"]));
+        assert_eq!(error(SYNTAX), run(&mut db, &["SET", "k", "v", "EX"]));
+    }
+
+    #[test]
+    fn huge_expire_times() {
+        let mut db = Db::default();
+        let max = i64::MAX.to_string();
+        assert_eq!(
+            error("ERR invalid expire time in 'set' command"),
+            run(&mut db, &["SET", "k", "v", "EX", &max])
+        );
+        assert_eq!(ok(), run(&mut db, &["SET", "k", "v"]));
+        assert_eq!(
+            error("ERR invalid expire time in 'expire' command"),
+            run(&mut db, &["EXPIRE", "k", &max])
+        );
+        assert_eq!(Resp::Integer(-1), run(&mut db, &["TTL", "k"]));
+    }
+
+    #[test]
+    fn expiry() {
+        let mut db = Db::default();
+        run(&mut db, &["SET", "k", "v", "PX", "20"]);
+        assert!(matches!(
+            run(&mut db, &["PTTL", "k"]),
+            Resp::Integer(1..=20)
+        ));
+        assert_eq!(
+            Resp::Integer(0),
+            run(&mut db, &["EXPIRE", "k", "100", "NX"])
+        );
+
+        std::thread::sleep(Duration::from_millis(30));
+        assert_eq!(Resp::Null, run(&mut db, &["GET", "k"]));
+        assert_eq!(Resp::Integer(-2), run(&mut db, &["TTL", "k"]));
+        assert_eq!(0, db.len());
+    }
+
+    #[test]
+    fn collections() {
+        let mut db = Db::default();
+        assert_eq!(
+            Resp::Integer(2),
+            run(&mut db, &["HSET", "h", "a", "1", "b", "2"])
+        );
+        assert_eq!(Resp::Integer(3), run(&mut db, &["HINCRBY", "h", "a", "2"]));
+        assert_eq!(Resp::Integer(1), run(&mut db, &["HDEL", "h", "a", "c"]));
+        assert_eq!(error(WRONGTYPE), run(&mut db, &["LPUSH", "h", "x"]));
+
+        assert_eq!(
+            Resp::Integer(3),
+            run(&mut db, &["RPUSH", "l", "a", "b", "c"])
+        );
+        assert_eq!(
+            Resp::Array(vec![bulk_str("b"), bulk_str("c")]),
+            run(&mut db, &["LRANGE", "l", "1", "-1"])
+        );
+        assert_eq!(
+            Resp::Array(vec![bulk_str("a"), bulk_str("b"), bulk_str("c")]),
+            run(&mut db, &["LPOP", "l", "5"])
+        );
+        assert_eq!(Resp::Integer(0), run(&mut db, &["EXISTS", "l"]));
+
+        assert_eq!(
+            Resp::Integer(2),
+            run(&mut db, &["SADD", "s", "a", "b", "a"])
+        );
+        assert_eq!(Resp::Integer(1), run(&mut db, &["SISMEMBER", "s", "b"]));
+        assert_eq!(
+            Resp::SimpleString("set".into()),
+            run(&mut db, &["TYPE", "s"])
+        );
+        assert_eq!(
+            Resp::Array(vec![bulk_str("s")]),
+            run(&mut db, &["KEYS", "s*"])
+        );
+    }
+}
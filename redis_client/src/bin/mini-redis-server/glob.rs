@@ -0,0 +1,72 @@
+// Glob-style matching as used by KEYS and PSUBSCRIBE: `*`, `?`, `[abc]`,
+// `[^a-z]` and `\` to escape a special character.
+pub fn matches(pattern: &[u8], s: &[u8]) -> bool {
+    match pattern.split_first() {
+        None => s.is_empty(),
+        Some((b'*', rest)) => (0..=s.len()).any(|i| matches(rest, &s[i..])),
+        Some((b'?', rest)) => !s.is_empty() && matches(rest, &s[1..]),
+        Some((b'[', rest)) => match (class(rest), s.split_first()) {
+            (Some((matched, rest)), Some((c, s))) => matched(*c) && matches(rest, s),
+            (Some(_), None) => false,
+            // no closing bracket: `[` is a literal
+            (None, _) => s.first() == Some(&b'[') && matches(rest, &s[1..]),
+        },
+        Some((b'\\', rest)) if !rest.is_empty() => {
+            s.first() == Some(&rest[0]) && matches(&rest[1..], &s[1..])
+        }
+        Some((c, rest)) => s.first() == Some(c) && matches(rest, &s[1..]),
+    }
+}
+
+// Parses a character class after `[`, returning its predicate and the rest of the pattern.
+fn class(pattern: &[u8]) -> Option<(impl Fn(u8) -> bool + '_, &[u8])> {
+    let (negated, body) = match pattern.first() {
+        Some(b'^') => (true, &pattern[1..]),
+        _ => (false, pattern),
+    };
+    let end = body.iter().position(|c| *c == b']')?;
+    let (set, rest) = (&body[..end], &body[end + 1..]);
+
+    let matched = move |c: u8| {
+        let mut i = 0;
+        let mut found = false;
+        while i < set.len() {
+            if set[i] == b'\\' && i + 1 < set.len() {
+                found |= set[i + 1] == c;
+                i += 2;
+            } else if i + 2 < set.len() && set[i + 1] == b'-' {
+                let (lo, hi) = (set[i].min(set[i + 2]), set[i].max(set[i + 2]));
+                found |= (lo..=hi).contains(&c);
+                i += 3;
+            } else {
+                found |= set[i] == c;
+                i += 1;
+            }
+        }
+        found != negated
+    };
+
+    Some((matched, rest))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::matches;
+
+    #[test]
+    fn glob() {
+        assert!(matches(b"*", b""));
+        assert!(matches(b"h?llo", b"hello"));
+        assert!(matches(b"h*llo", b"heeeello"));
+        assert!(matches(b"h[ae]llo", b"hallo"));
+        assert!(!matches(b"h[ae]llo", b"hillo"));
+        assert!(matches(b"h[^e]llo", b"hallo"));
+        assert!(!matches(b"h[^e]llo", b"hello"));
+        assert!(matches(b"h[a-b]llo", b"hbllo"));
+        assert!(matches(b"news.*", b"news.art"));
+        assert!(!matches(b"news.*", b"new"));
+        assert!(matches(b"a\\*", b"a*"));
+        assert!(!matches(b"a\\*", b"ab"));
+        assert!(matches(b"[abc", b"[abc"));
+    }
+}
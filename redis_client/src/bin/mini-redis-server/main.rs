@@ -0,0 +1,622 @@
+//! An in-memory server implementing a subset of Redis on top of the `resp`
+//! module, for local development and integration tests without redis-server.
+//!
+//! Supports strings with expiry, hashes, lists, sets, pub/sub, PING, ECHO,
+//! INFO, SELECT, MULTI/EXEC and both RESP and inline commands.
+//!
+//! Usage: mini-redis-server [--bind ADDR] [--port PORT]
+
+mod db;
+mod glob;
+
+use bytes::{Bytes, BytesMut};
+use db::{error, ok, parse_int, wrong_arity, Db};
+use redis_client::resp::{Resp, RespDecoder};
+use std::collections::{HashMap, HashSet};
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+use std::time::Instant;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::mpsc;
+
+const DATABASES: usize = 16;
+
+type Sender = mpsc::UnboundedSender<Resp>;
+
+#[derive(Debug)]
+struct Shared {
+    dbs: Mutex<Vec<Db>>,
+    channels: Mutex<Channels>,
+    started: Instant,
+    clients: AtomicUsize,
+    commands: AtomicU64,
+    next_id: AtomicU64,
+}
+
+// Subscribers of each channel and pattern, by client id
+#[derive(Debug, Default)]
+struct Channels {
+    channels: HashMap<Bytes, HashMap<u64, Sender>>,
+    patterns: HashMap<Bytes, HashMap<u64, Sender>>,
+}
+
+impl Channels {
+    fn publish(&self, channel: &Bytes, message: &Bytes) -> i64 {
+        let mut receivers = 0;
+        for tx in self
+            .channels
+            .get(channel)
+            .into_iter()
+            .flat_map(|s| s.values())
+        {
+            let push = bulks(&[b"message", channel, message]);
+            receivers += tx.send(push).is_ok() as i64;
+        }
+        for (pattern, subscribers) in &self.patterns {
+            if glob::matches(pattern, channel) {
+                for tx in subscribers.values() {
+                    let push = bulks(&[b"pmessage", pattern, channel, message]);
+                    receivers += tx.send(push).is_ok() as i64;
+                }
+            }
+        }
+        receivers
+    }
+}
+
+#[tokio::main]
+async fn main() -> std::io::Result<()> {
+    let mut bind = "127.0.0.1".to_string();
+    let mut port = 6379;
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match (arg.as_str(), args.next()) {
+            ("--bind", Some(addr)) => bind = addr,
+            ("--port", Some(p)) => port = p.parse().expect("invalid port"),
+            _ => {
+                eprintln!("usage: mini-redis-server [--bind ADDR] [--port PORT]");
+                std::process::exit(2);
+            }
+        }
+    }
+
+    let listener = TcpListener::bind((bind.as_str(), port)).await?;
+    eprintln!("ready to accept connections on {}", listener.local_addr()?);
+    serve(listener).await
+}
+
+async fn serve(listener: TcpListener) -> std::io::Result<()> {
+    let shared = Arc::new(Shared {
+        dbs: Mutex::new((0..DATABASES).map(|_| Db::default()).collect()),
+        channels: Mutex::new(Channels::default()),
+        started: Instant::now(),
+        clients: AtomicUsize::new(0),
+        commands: AtomicU64::new(0),
+        next_id: AtomicU64::new(1),
+    });
+
+    loop {
+        let (socket, _) = listener.accept().await?;
+        let shared = shared.clone();
+        tokio::spawn(async move {
+            shared.clients.fetch_add(1, Ordering::Relaxed);
+            let mut client = Client::new(socket, shared.clone());
+            if let Err(e) = client.run().await {
+                eprintln!("connection error: {}", e);
+            }
+            client.unsubscribe_all();
+            shared.clients.fetch_sub(1, Ordering::Relaxed);
+        });
+    }
+}
+
+struct Client {
+    id: u64,
+    socket: TcpStream,
+    shared: Arc<Shared>,
+    buffer: BytesMut,
+    decoder: RespDecoder,
+    out: BytesMut,
+
+    db: usize,
+
+    // commands queued by MULTI, and whether one of them was rejected
+    multi: Option<Vec<Vec<Bytes>>>,
+    multi_failed: bool,
+
+    channels: HashSet<Bytes>,
+    patterns: HashSet<Bytes>,
+    tx: Sender,
+    rx: mpsc::UnboundedReceiver<Resp>,
+}
+
+impl Client {
+    fn new(socket: TcpStream, shared: Arc<Shared>) -> Client {
+        let (tx, rx) = mpsc::unbounded_channel();
+        Client {
+            id: shared.next_id.fetch_add(1, Ordering::Relaxed),
+            socket,
+            shared,
+            buffer: BytesMut::with_capacity(4 * 1024),
+            decoder: RespDecoder::new(),
+            out: BytesMut::new(),
+            db: 0,
+            multi: None,
+            multi_failed: false,
+            channels: HashSet::new(),
+            patterns: HashSet::new(),
+            tx,
+            rx,
+        }
+    }
+
+    async fn run(&mut self) -> std::io::Result<()> {
+        loop {
+            let mut quit = false;
+            loop {
+                match self.next_command() {
+                    Ok(Some(args)) => {
+                        if !self.handle(args) {
+                            quit = true;
+                            break;
+                        }
+                    }
+                    Ok(None) => break,
+                    Err(e) => {
+                        error(&format!("ERR Protocol error: {}", e)).encode(&mut self.out);
+                        quit = true;
+                        break;
+                    }
+                }
+            }
+
+            if !self.out.is_empty() {
+                self.socket.write_all(&self.out).await?;
+                self.out.clear();
+            }
+            if quit {
+                return Ok(());
+            }
+
+            tokio::select! {
+                n = self.socket.read_buf(&mut self.buffer) => {
+                    if n? == 0 {
+                        return Ok(());
+                    }
+                }
+                Some(push) = self.rx.recv() => push.encode(&mut self.out),
+            }
+        }
+    }
+
+    // Returns the next command in the buffer, either an array of bulk strings
+    // or an inline command (space separated arguments on a line)
+    fn next_command(&mut self) -> Result<Option<Vec<Bytes>>, String> {
+        loop {
+            match self.buffer.first() {
+                None => return Ok(None),
+                Some(b'*') => {
+                    return match self.decoder.decode(&mut self.buffer) {
+                        Ok(Some(Resp::Array(items))) => items
+                            .into_iter()
+                            .map(|item| match item {
+                                Resp::BulkString(arg) => Ok(arg),
+                                _ => Err("expected bulk strings".to_string()),
+                            })
+                            .collect::<Result<Vec<_>, _>>()
+                            .map(|args| (!args.is_empty()).then_some(args))
+                            .and_then(|args| match args {
+                                Some(args) => Ok(Some(args)),
+                                None => Err("empty command".to_string()),
+                            }),
+                        Ok(Some(_)) => Err("expected an array".to_string()),
+                        Ok(None) => Ok(None),
+                        Err(e) => Err(format!("{:?}", e)),
+                    };
+                }
+                Some(_) => {
+                    let end = match self.buffer.iter().position(|b| *b == b'\n') {
+                        Some(end) => end,
+                        None => return Ok(None),
+                    };
+                    let line = self.buffer.split_to(end + 1).freeze();
+                    let args: Vec<Bytes> = line
+                        .split(|b| b.is_ascii_whitespace())
+                        .filter(|arg| !arg.is_empty())
+                        .map(|arg| line.slice_ref(arg))
+                        .collect();
+                    if !args.is_empty() {
+                        return Ok(Some(args));
+                    }
+                }
+            }
+        }
+    }
+
+    // Handles a command, writing its replies to `out`. Returns false to close the connection.
+    fn handle(&mut self, args: Vec<Bytes>) -> bool {
+        self.shared.commands.fetch_add(1, Ordering::Relaxed);
+        let name = String::from_utf8_lossy(&args[0]).to_ascii_lowercase();
+
+        let queuing = !matches!(name.as_str(), "exec" | "discard" | "multi" | "quit");
+        if let Some(queued) = self.multi.as_mut().filter(|_| queuing) {
+            let reply = match check_arity(&name, args.len()) {
+                Ok(()) if is_connection_command(&name) => error(&format!(
+                    "ERR Command not allowed inside a transaction: '{}'",
+                    name
+                )),
+                Ok(()) => {
+                    queued.push(args);
+                    Resp::SimpleString("QUEUED".to_string())
+                }
+                Err(e) => e,
+            };
+            if matches!(reply, Resp::Error(_)) {
+                self.multi_failed = true;
+            }
+            reply.encode(&mut self.out);
+            return true;
+        }
+
+        let subscribed = !self.channels.is_empty() || !self.patterns.is_empty();
+        if subscribed
+            && !matches!(
+                name.as_str(),
+                "subscribe" | "unsubscribe" | "psubscribe" | "punsubscribe" | "ping" | "quit"
+            )
+        {
+            error(&format!(
+                "ERR Can't execute '{}': only (P)SUBSCRIBE / (P)UNSUBSCRIBE / PING / QUIT are allowed in this context",
+                name
+            ))
+            .encode(&mut self.out);
+            return true;
+        }
+
+        if let Err(e) = check_arity(&name, args.len()) {
+            e.encode(&mut self.out);
+            return true;
+        }
+
+        let reply = match name.as_str() {
+            "quit" => {
+                ok().encode(&mut self.out);
+                return false;
+            }
+            "ping" if subscribed => {
+                let message = args.get(1).cloned().unwrap_or_default();
+                Resp::Array(vec![bulk("pong"), Resp::BulkString(message)])
+            }
+            "subscribe" | "psubscribe" => {
+                for channel in &args[1..] {
+                    self.subscribe(&name, channel.clone());
+                }
+                return true;
+            }
+            "unsubscribe" | "punsubscribe" => {
+                let current = match name.as_str() {
+                    "unsubscribe" => &self.channels,
+                    _ => &self.patterns,
+                };
+                let channels = match args.len() {
+                    1 => current.iter().cloned().collect(),
+                    _ => args[1..].to_vec(),
+                };
+                if channels.is_empty() {
+                    Resp::Array(vec![bulk(&name), Resp::Null, Resp::Integer(0)])
+                        .encode(&mut self.out);
+                }
+                for channel in channels {
+                    self.unsubscribe(&name, channel);
+                }
+                return true;
+            }
+            "multi" if self.multi.is_some() => error("ERR MULTI calls can not be nested"),
+            "multi" => {
+                self.multi = Some(vec![]);
+                ok()
+            }
+            "discard" => match self.multi.take() {
+                Some(_) => {
+                    self.multi_failed = false;
+                    ok()
+                }
+                None => error("ERR DISCARD without MULTI"),
+            },
+            "exec" => match self.multi.take() {
+                None => error("ERR EXEC without MULTI"),
+                Some(_) if std::mem::take(&mut self.multi_failed) => {
+                    error("EXECABORT Transaction discarded because of previous errors.")
+                }
+                Some(queued) => {
+                    // holding the lock for the whole transaction makes it atomic
+                    let shared = self.shared.clone();
+                    let mut dbs = lock(&shared.dbs);
+                    let mut replies = Vec::with_capacity(queued.len());
+                    for args in &queued {
+                        let name = String::from_utf8_lossy(&args[0]).to_ascii_lowercase();
+                        replies.push(self.execute(&mut dbs, &name, &args[1..]));
+                    }
+                    Resp::Array(replies)
+                }
+            },
+            _ => {
+                let shared = self.shared.clone();
+                let mut dbs = lock(&shared.dbs);
+                self.execute(&mut dbs, &name, &args[1..])
+            }
+        };
+        reply.encode(&mut self.out);
+        true
+    }
+
+    // Runs a command that can be queued by MULTI
+    fn execute(&mut self, dbs: &mut [Db], name: &str, args: &[Bytes]) -> Resp {
+        match name {
+            "ping" => match args.first() {
+                Some(message) => Resp::BulkString(message.clone()),
+                None => Resp::SimpleString("PONG".to_string()),
+            },
+            "echo" => Resp::BulkString(args[0].clone()),
+            "select" => match parse_int(&args[0]) {
+                Ok(index) if (0..DATABASES as i64).contains(&index) => {
+                    self.db = index as usize;
+                    ok()
+                }
+                Ok(_) => error("ERR DB index is out of range"),
+                Err(e) => e,
+            },
+            "flushall" => {
+                dbs.iter_mut().for_each(Db::clear);
+                ok()
+            }
+            "publish" => {
+                let channels = lock(&self.shared.channels);
+                Resp::Integer(channels.publish(&args[0], &args[1]))
+            }
+            "info" => Resp::BulkString(Bytes::from(self.info(dbs))),
+            // redis-cli asks for the documentation of commands on startup
+            "command" => Resp::Array(vec![]),
+            name => dbs[self.db].execute(name, args),
+        }
+    }
+
+    fn info(&self, dbs: &mut [Db]) -> String {
+        let mut info = format!(
+            "# Server\r\nredis_version:7.0.0\r\nredis_mode:standalone\r\nuptime_in_seconds:{}\r\n\r\n\
+             # Clients\r\nconnected_clients:{}\r\n\r\n\
+             # Stats\r\ntotal_commands_processed:{}\r\n\r\n\
+             # Keyspace\r\n",
+            self.shared.started.elapsed().as_secs(),
+            self.shared.clients.load(Ordering::Relaxed),
+            self.shared.commands.load(Ordering::Relaxed),
+        );
+        for (i, db) in dbs.iter_mut().enumerate() {
+            let keys = db.len();
+            if keys > 0 {
+                info.push_str(&format!(
+                    "db{}:keys={},expires={},avg_ttl=0\r\n",
+                    i,
+                    keys,
+                    db.expires()
+                ));
+            }
+        }
+        info
+    }
+
+    fn subscribe(&mut self, kind: &str, channel: Bytes) {
+        let mut shared = lock(&self.shared.channels);
+        let (subscribed, registry) = match kind {
+            "subscribe" => (&mut self.channels, &mut shared.channels),
+            _ => (&mut self.patterns, &mut shared.patterns),
+        };
+        subscribed.insert(channel.clone());
+        registry
+            .entry(channel.clone())
+            .or_default()
+            .insert(self.id, self.tx.clone());
+
+        let count = self.channels.len() + self.patterns.len();
+        Resp::Array(vec![
+            bulk(kind),
+            Resp::BulkString(channel),
+            Resp::Integer(count as i64),
+        ])
+        .encode(&mut self.out);
+    }
+
+    fn unsubscribe(&mut self, kind: &str, channel: Bytes) {
+        let mut shared = lock(&self.shared.channels);
+        let (subscribed, registry) = match kind {
+            "unsubscribe" => (&mut self.channels, &mut shared.channels),
+            _ => (&mut self.patterns, &mut shared.patterns),
+        };
+        subscribed.remove(&channel);
+        if let Some(subscribers) = registry.get_mut(&channel) {
+            subscribers.remove(&self.id);
+            if subscribers.is_empty() {
+                registry.remove(&channel);
+            }
+        }
+
+        let count = self.channels.len() + self.patterns.len();
+        Resp::Array(vec![
+            bulk(kind),
+            Resp::BulkString(channel),
+            Resp::Integer(count as i64),
+        ])
+        .encode(&mut self.out);
+    }
+
+    fn unsubscribe_all(&mut self) {
+        let mut shared = lock(&self.shared.channels);
+        let Channels { channels, patterns } = &mut *shared;
+        let id = self.id;
+        for (subscribed, registry) in [(&self.channels, channels), (&self.patterns, patterns)] {
+            for channel in subscribed {
+                if let Some(subscribers) = registry.get_mut(channel) {
+                    subscribers.remove(&id);
+                    if subscribers.is_empty() {
+                        registry.remove(channel);
+                    }
+                }
+            }
+        }
+    }
+}
+
+// A panic while holding a lock must not take down every other connection,
+// so a poisoned lock is used as is
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+// Commands changing the state of the connection, which MULTI cannot queue
+fn is_connection_command(name: &str) -> bool {
+    matches!(
+        name,
+        "subscribe" | "unsubscribe" | "psubscribe" | "punsubscribe"
+    )
+}
+
+fn check_arity(name: &str, len: usize) -> Result<(), Resp> {
+    let arity = match name {
+        "quit" | "multi" | "exec" | "discard" | "flushall" | "command" => -1,
+        "ping" | "info" | "unsubscribe" | "punsubscribe" => -1,
+        "echo" | "select" => 2,
+        "publish" => 3,
+        "subscribe" | "psubscribe" => -2,
+        name => match db::arity(name) {
+            Some(arity) => arity,
+            None => return Err(error(&format!("ERR unknown command '{}'", name))),
+        },
+    };
+
+    let len = len as i64;
+    if (arity >= 0 && len != arity) || len < arity.abs() {
+        Err(wrong_arity(name))
+    } else {
+        Ok(())
+    }
+}
+
+fn bulk(s: &str) -> Resp {
+    Resp::BulkString(Bytes::from(s.to_string()))
+}
+
+fn bulks(values: &[&[u8]]) -> Resp {
+    Resp::Array(
+        values
+            .iter()
+            .map(|v| Resp::BulkString(Bytes::copy_from_slice(v)))
+            .collect(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use redis_client::client::{self, Client as RedisClient};
+    use redis_client::subscriber::Message;
+    use std::net::SocketAddr;
+
+    async fn start() -> SocketAddr {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(serve(listener));
+        addr
+    }
+
+    async fn connect(addr: SocketAddr) -> RedisClient {
+        client::connect(addr).await.unwrap()
+    }
+
+    #[tokio::test]
+    async fn inline_commands() {
+        let mut socket = TcpStream::connect(start().await).await.unwrap();
+        socket
+            .write_all(b"PING\r\nSET k  v\nGET k\r\n\r\nQUIT\r\n")
+            .await
+            .unwrap();
+        let mut replies = vec![];
+        socket.read_to_end(&mut replies).await.unwrap();
+        assert_eq!(b"+PONG\r\n+OK\r\n$1\r\nv\r\n+OK\r\n".to_vec(), replies);
+    }
+
+    #[tokio::test]
+    async fn multi_exec() {
+        let mut client = connect(start().await).await;
+        let queued = Resp::SimpleString("QUEUED".to_string());
+        assert_eq!(ok(), client.command(&["MULTI"]).await.unwrap());
+        assert_eq!(queued, client.command(&["SET", "k", "1"]).await.unwrap());
+        assert_eq!(queued, client.command(&["INCR", "k"]).await.unwrap());
+        assert_eq!(
+            Resp::Array(vec![ok(), Resp::Integer(2)]),
+            client.command(&["EXEC"]).await.unwrap()
+        );
+
+        // a rejected command discards the whole transaction
+        client.command(&["MULTI"]).await.unwrap();
+        assert!(client.command(&["GET"]).await.is_err());
+        client.command(&["INCR", "k"]).await.unwrap();
+        assert!(matches!(
+            client.command(&["EXEC"]).await,
+            Err(redis_client::error::Error::ExecAbort(_))
+        ));
+        assert_eq!(Some(Bytes::from("2")), client.get("k").await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn pubsub() {
+        let addr = start().await;
+        let mut subscriber = connect(addr).await;
+        let mut psubscriber = connect(addr).await;
+        let mut publisher = connect(addr).await;
+
+        let mut subscriber = subscriber
+            .subscribe(vec!["news".to_string()])
+            .await
+            .unwrap();
+        let mut psubscriber = psubscriber
+            .psubscribe(vec!["n*".to_string()])
+            .await
+            .unwrap();
+        assert_eq!(
+            2,
+            publisher
+                .publish("news", Bytes::from("hello"))
+                .await
+                .unwrap()
+        );
+
+        match subscriber.next_message().await.unwrap() {
+            Message::Message(channel, message) => {
+                assert_eq!(
+                    (Bytes::from("news"), Bytes::from("hello")),
+                    (channel, message)
+                )
+            }
+            others => panic!("unexpected message {:?}", others),
+        }
+        match psubscriber.next_message().await.unwrap() {
+            Message::PMessage(pattern, channel, _) => {
+                assert_eq!((Bytes::from("n*"), Bytes::from("news")), (pattern, channel))
+            }
+            others => panic!("unexpected message {:?}", others),
+        }
+    }
+
+    #[tokio::test]
+    async fn huge_expire_does_not_break_the_server() {
+        let mut client = connect(start().await).await;
+        client.set("k", Bytes::from("v")).await.unwrap();
+        assert!(client
+            .command(&["EXPIRE", "k", &i64::MAX.to_string()])
+            .await
+            .is_err());
+        assert_eq!(Some(Bytes::from("v")), client.get("k").await.unwrap());
+        client.ping().await.unwrap();
+    }
+}
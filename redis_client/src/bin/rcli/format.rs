@@ -0,0 +1,173 @@
+use redis_client::resp::Resp;
+
+/// Formats a reply as redis-cli does on a terminal.
+pub fn pretty(resp: &Resp) -> String {
+    let mut out = String::new();
+    pretty_into(&mut out, resp, 0);
+    out
+}
+
+fn pretty_into(out: &mut String, resp: &Resp, indent: usize) {
+    match resp {
+        Resp::SimpleString(s) => out.push_str(s),
+        Resp::Error(e) => {
+            out.push_str("(error) ");
+            out.push_str(e);
+        }
+        Resp::Integer(n) => out.push_str(&format!("(integer) {}", n)),
+        Resp::BulkString(b) => out.push_str(&quote(b)),
+        Resp::Null => out.push_str("(nil)"),
+        Resp::Array(items) if items.is_empty() => out.push_str("(empty array)"),
+        Resp::Array(items) => {
+            let width = items.len().to_string().len();
+            for (i, item) in items.iter().enumerate() {
+                if i > 0 {
+                    out.push('\n');
+                    out.push_str(&" ".repeat(indent));
+                }
+                let prefix = format!("{:>width$}) ", i + 1, width = width);
+                out.push_str(&prefix);
+                pretty_into(out, item, indent + prefix.len());
+            }
+        }
+    }
+}
+
+/// Formats a reply as redis-cli does with --raw or when not on a terminal.
+pub fn raw(resp: &Resp) -> Vec<u8> {
+    match resp {
+        Resp::SimpleString(s) | Resp::Error(s) => s.as_bytes().to_vec(),
+        Resp::Integer(n) => n.to_string().into_bytes(),
+        Resp::BulkString(b) => b.to_vec(),
+        Resp::Null => vec![],
+        Resp::Array(items) => items.iter().map(raw).collect::<Vec<_>>().join(&b'\n'),
+    }
+}
+
+/// Quotes a binary string, escaping non printable characters.
+pub fn quote(s: &[u8]) -> String {
+    let mut out = String::with_capacity(s.len() + 2);
+    out.push('"');
+    for &c in s {
+        match c {
+            b'\\' => out.push_str("\\\\"),
+            b'"' => out.push_str("\\\""),
+            b'\n' => out.push_str("\\n"),
+            b'\r' => out.push_str("\\r"),
+            b'\t' => out.push_str("\\t"),
+            0x07 => out.push_str("\\a"),
+            0x08 => out.push_str("\\b"),
+            c if c.is_ascii_graphic() || c == b' ' => out.push(c as char),
+            c => out.push_str(&format!("\\x{:02x}", c)),
+        }
+    }
+    out.push('"');
+    out
+}
+
+/// Splits a command line into arguments like redis-cli: double quoted
+/// arguments support escapes (`\n`, `\xff`, ...), single quoted ones only `\'`.
+/// Returns None if quotes are unbalanced.
+pub fn split_args(line: &str) -> Option<Vec<Vec<u8>>> {
+    let mut args = vec![];
+    let mut chars = line.bytes().peekable();
+
+    loop {
+        while chars.next_if(|c| c.is_ascii_whitespace()).is_some() {}
+        let first = match chars.next() {
+            Some(c) => c,
+            None => return Some(args),
+        };
+
+        let mut arg = vec![];
+        match first {
+            b'"' => loop {
+                match chars.next()? {
+                    b'"' => break,
+                    b'\\' => match chars.next()? {
+                        b'n' => arg.push(b'\n'),
+                        b'r' => arg.push(b'\r'),
+                        b't' => arg.push(b'\t'),
+                        b'a' => arg.push(0x07),
+                        b'b' => arg.push(0x08),
+                        b'x' => {
+                            let hex = [chars.next()?, chars.next()?];
+                            let hex = std::str::from_utf8(&hex).ok()?;
+                            arg.push(u8::from_str_radix(hex, 16).ok()?);
+                        }
+                        c => arg.push(c),
+                    },
+                    c => arg.push(c),
+                }
+            },
+            b'\'' => loop {
+                match chars.next()? {
+                    b'\'' => break,
+                    b'\\' if chars.peek() == Some(&b'\'') => arg.push(chars.next()?),
+                    c => arg.push(c),
+                }
+            },
+            c => {
+                arg.push(c);
+                while let Some(c) = chars.next_if(|c| !c.is_ascii_whitespace()) {
+                    arg.push(c);
+                }
+                args.push(arg);
+                continue;
+            }
+        }
+
+        // a closing quote must be followed by a space or the end of line
+        if chars.peek().is_some_and(|c| !c.is_ascii_whitespace()) {
+            return None;
+        }
+        args.push(arg);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bytes::Bytes;
+
+    #[test]
+    fn split() {
+        assert_eq!(
+            Some(vec![b"SET".to_vec(), b"k".to_vec(), b"a b\n\xff".to_vec()]),
+            split_args(r#"SET k "a b\n\xff""#)
+        );
+        assert_eq!(
+            Some(vec![b"GET".to_vec(), b"it's".to_vec()]),
+            split_args(r"  GET 'it\'s'  ")
+        );
+        assert_eq!(Some(vec![]), split_args("   "));
+        assert_eq!(None, split_args(r#"GET "k"#));
+        assert_eq!(None, split_args(r#"GET "k"x"#));
+    }
+
+    #[test]
+    fn pretty_nested() {
+        let resp = Resp::Array(vec![
+            Resp::BulkString(Bytes::from("a\"b")),
+            Resp::Array(vec![Resp::Integer(1), Resp::Null]),
+            Resp::Array(vec![]),
+        ]);
+        assert_eq!(
+            "1) \"a\\\"b\"\n2) 1) (integer) 1\n   2) (nil)\n3) (empty array)",
+            pretty(&resp)
+        );
+
+        let items = (0..10).map(Resp::Integer).collect();
+        assert!(pretty(&Resp::Array(items)).starts_with(" 1) (integer) 0\n 2)"));
+    }
+
+    #[test]
+    fn raw_output() {
+        let resp = Resp::Array(vec![
+            Resp::BulkString(Bytes::from("a")),
+            Resp::Integer(2),
+            Resp::Null,
+        ]);
+        assert_eq!(b"a\n2\n".to_vec(), raw(&resp));
+    }
+}
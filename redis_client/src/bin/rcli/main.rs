@@ -0,0 +1,471 @@
+//! A redis-cli style command-line tool built on `redis_client`.
+//!
+//! Starts an interactive prompt when no command is given, otherwise runs the
+//! command and prints its reply. SUBSCRIBE, PSUBSCRIBE and MONITOR stream
+//! messages until Ctrl-C.
+
+mod format;
+
+use bytes::{Bytes, BytesMut};
+use format::{pretty, split_args};
+use futures_util::{SinkExt, StreamExt};
+use redis_client::{
+    client::{self, Client},
+    codec::RespCodec,
+    error::Error,
+    resp::{Resp, RespDecoder},
+    subscriber::Message,
+};
+use rustyline::{error::ReadlineError, DefaultEditor};
+use std::io::{ErrorKind, IsTerminal, Write};
+use std::path::PathBuf;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+use tokio_util::codec::{Framed, FramedRead};
+
+const USAGE: &str = "\
+Usage: rcli [OPTIONS] [cmd [arg [arg ...]]]
+  -h <hostname>      Server hostname (default: 127.0.0.1).
+  -p <port>          Server port (default: 6379).
+  -a <password>      Password to use when connecting to the server.
+  --user <username>  Username to send to AUTH with the password.
+  -n <db>            Database number.
+  -u <uri>           Server URI: redis://[[username]:password@]host[:port][/db]
+  --raw              Use raw formatting for replies (default when stdout is not a tty).
+  --no-raw           Force formatted output even when stdout is not a tty.
+  --pipe             Transfer raw Redis protocol from stdin to the server.
+  --help             Output this help and exit.";
+
+#[derive(Debug, Clone)]
+struct Options {
+    host: String,
+    port: u16,
+    user: Option<String>,
+    password: Option<String>,
+    db: u64,
+    raw: Option<bool>,
+    pipe: bool,
+    command: Vec<Vec<u8>>,
+}
+
+impl Options {
+    fn parse(mut args: impl Iterator<Item = String>) -> Result<Options, String> {
+        let mut options = Options {
+            host: "127.0.0.1".to_string(),
+            port: 6379,
+            user: None,
+            password: None,
+            db: 0,
+            raw: None,
+            pipe: false,
+            command: vec![],
+        };
+
+        while let Some(arg) = args.next() {
+            let mut value = || args.next().ok_or(format!("missing value for {}", arg));
+            match arg.as_str() {
+                "-h" => options.host = value()?,
+                "-p" => options.port = value()?.parse().map_err(|_| "invalid port")?,
+                "-a" => options.password = Some(value()?),
+                "--user" => options.user = Some(value()?),
+                "-n" => options.db = value()?.parse().map_err(|_| "invalid database number")?,
+                "-u" => options.apply_uri(&value()?)?,
+                "--raw" => options.raw = Some(true),
+                "--no-raw" => options.raw = Some(false),
+                "--pipe" => options.pipe = true,
+                "--help" => {
+                    println!("{}", USAGE);
+                    std::process::exit(0);
+                }
+                opt if opt.starts_with('-') && options.command.is_empty() => {
+                    return Err(format!("unrecognized option '{}'", opt));
+                }
+                _ => {
+                    options.command.push(arg.into_bytes());
+                    options.command.extend(args.map(String::into_bytes));
+                    break;
+                }
+            }
+        }
+        Ok(options)
+    }
+
+    // redis://[[username]:password@]host[:port][/db]
+    fn apply_uri(&mut self, uri: &str) -> Result<(), String> {
+        let rest = uri
+            .strip_prefix("redis://")
+            .ok_or_else(|| format!("invalid URI scheme: {}", uri))?;
+        let (credentials, rest) = match rest.rsplit_once('@') {
+            Some((credentials, rest)) => (Some(credentials), rest),
+            None => (None, rest),
+        };
+        let (address, db) = match rest.split_once('/') {
+            Some((address, db)) if !db.is_empty() => (address, Some(db)),
+            Some((address, _)) => (address, None),
+            None => (rest, None),
+        };
+
+        if let Some(credentials) = credentials {
+            match credentials.split_once(':') {
+                Some((user, password)) => {
+                    self.user = Some(user.to_string()).filter(|u| !u.is_empty());
+                    self.password = Some(password.to_string());
+                }
+                None => self.password = Some(credentials.to_string()),
+            }
+        }
+        match address.rsplit_once(':') {
+            Some((host, port)) => {
+                self.host = host.to_string();
+                self.port = port
+                    .parse()
+                    .map_err(|_| format!("invalid port in {}", uri))?;
+            }
+            None if !address.is_empty() => self.host = address.to_string(),
+            None => {}
+        }
+        if let Some(db) = db {
+            self.db = db
+                .parse()
+                .map_err(|_| format!("invalid database in {}", uri))?;
+        }
+        Ok(())
+    }
+
+    // Commands to send on a new connection
+    fn handshake(&self) -> Vec<Vec<Vec<u8>>> {
+        let mut commands = vec![];
+        if let Some(password) = &self.password {
+            let mut auth = vec![b"AUTH".to_vec()];
+            auth.extend(self.user.as_ref().map(|u| u.as_bytes().to_vec()));
+            auth.push(password.as_bytes().to_vec());
+            commands.push(auth);
+        }
+        if self.db != 0 {
+            commands.push(vec![b"SELECT".to_vec(), self.db.to_string().into_bytes()]);
+        }
+        commands
+    }
+
+    fn address(&self) -> (&str, u16) {
+        (self.host.as_str(), self.port)
+    }
+}
+
+#[tokio::main]
+async fn main() {
+    let options = match Options::parse(std::env::args().skip(1)) {
+        Ok(options) => options,
+        Err(msg) => {
+            eprintln!("{}\n\n{}", msg, USAGE);
+            std::process::exit(1);
+        }
+    };
+
+    let result = if options.pipe {
+        pipe(&options).await
+    } else if options.command.is_empty() {
+        repl(options).await
+    } else {
+        let raw = options
+            .raw
+            .unwrap_or_else(|| !std::io::stdout().is_terminal());
+        match connect(&options).await {
+            Ok(mut client) => run(&mut client, &options.command, raw).await.map(|_| ()),
+            Err(e) => Err(e),
+        }
+    };
+
+    if let Err(e) = result {
+        eprintln!("Error: {}", e);
+        std::process::exit(1);
+    }
+}
+
+async fn connect(options: &Options) -> Result<Client, Error> {
+    let mut client = client::connect(options.address()).await?;
+    for command in options.handshake() {
+        client.command(&command).await?;
+    }
+    Ok(client)
+}
+
+async fn connect_framed(options: &Options) -> Result<Framed<TcpStream, RespCodec>, Error> {
+    let stream = TcpStream::connect(options.address()).await?;
+    let mut framed = Framed::new(stream, RespCodec::new());
+    for command in options.handshake() {
+        request(&mut framed, &command).await?;
+    }
+    Ok(framed)
+}
+
+async fn request(
+    framed: &mut Framed<TcpStream, RespCodec>,
+    args: &[Vec<u8>],
+) -> Result<Resp, Error> {
+    framed.send(to_command(args)).await?;
+    match framed.next().await {
+        Some(Ok(Resp::Error(msg))) => Err(Error::from_server_message(&msg)),
+        Some(reply) => reply,
+        None => Err(std::io::Error::from(ErrorKind::UnexpectedEof).into()),
+    }
+}
+
+fn to_command(args: &[Vec<u8>]) -> Resp {
+    Resp::Array(
+        args.iter()
+            .map(|arg| Resp::BulkString(Bytes::copy_from_slice(arg)))
+            .collect(),
+    )
+}
+
+fn print_reply(resp: &Resp, raw: bool) {
+    let mut stdout = std::io::stdout().lock();
+    let _ = if raw {
+        stdout.write_all(&format::raw(resp))
+    } else {
+        stdout.write_all(pretty(resp).as_bytes())
+    };
+    let _ = stdout.write_all(b"\n");
+    let _ = stdout.flush();
+}
+
+// Runs a command, printing error replies. Only connection errors are returned,
+// otherwise whether the server replied with an error.
+async fn run(client: &mut Client, args: &[Vec<u8>], raw: bool) -> Result<bool, Error> {
+    let name = String::from_utf8_lossy(&args[0]).to_ascii_uppercase();
+    match name.as_str() {
+        "SUBSCRIBE" | "PSUBSCRIBE" => subscribe(client, &name, &args[1..], raw)
+            .await
+            .map(|_| true),
+        "MONITOR" => monitor(client, raw).await.map(|_| true),
+        _ => match client.command(args).await {
+            Ok(resp) => {
+                print_reply(&resp, raw);
+                Ok(true)
+            }
+            Err(e) if e.is_server_error() => {
+                print_reply(&Resp::Error(e.to_string()), raw);
+                Ok(false)
+            }
+            Err(e) => Err(e),
+        },
+    }
+}
+
+async fn subscribe(
+    client: &mut Client,
+    kind: &str,
+    channels: &[Vec<u8>],
+    raw: bool,
+) -> Result<(), Error> {
+    let channels: Vec<String> = channels
+        .iter()
+        .map(|c| String::from_utf8_lossy(c).into_owned())
+        .collect();
+    let mut subscriber = match kind {
+        "SUBSCRIBE" => client.subscribe(channels.clone()).await?,
+        _ => client.psubscribe(channels.clone()).await?,
+    };
+
+    eprintln!("Reading messages... (press Ctrl-C to quit)");
+    for (channel, n) in channels.iter().zip(subscriber.subscription_counts()) {
+        let confirmation = Resp::Array(vec![
+            Resp::BulkString(Bytes::from(kind.to_ascii_lowercase())),
+            Resp::BulkString(Bytes::from(channel.clone())),
+            Resp::Integer(*n as i64),
+        ]);
+        print_reply(&confirmation, raw);
+    }
+
+    loop {
+        tokio::select! {
+            _ = tokio::signal::ctrl_c() => return Ok(()),
+            message = subscriber.next_message() => print_reply(&message_to_resp(message?), raw),
+        }
+    }
+}
+
+fn message_to_resp(message: Message) -> Resp {
+    let bulk = |s: &str| Resp::BulkString(Bytes::from(s.to_string()));
+    Resp::Array(match message {
+        Message::Subscribe(channel, n) => {
+            vec![
+                bulk("subscribe"),
+                Resp::BulkString(channel),
+                Resp::Integer(n as i64),
+            ]
+        }
+        Message::PSubscribe(pattern, n) => {
+            vec![
+                bulk("psubscribe"),
+                Resp::BulkString(pattern),
+                Resp::Integer(n as i64),
+            ]
+        }
+        Message::Unsubscribe(channel) => vec![bulk("unsubscribe"), Resp::BulkString(channel)],
+        Message::Message(channel, payload) => vec![
+            bulk("message"),
+            Resp::BulkString(channel),
+            Resp::BulkString(payload),
+        ],
+        Message::PMessage(pattern, channel, payload) => vec![
+            bulk("pmessage"),
+            Resp::BulkString(pattern),
+            Resp::BulkString(channel),
+            Resp::BulkString(payload),
+        ],
+    })
+}
+
+async fn monitor(client: &mut Client, raw: bool) -> Result<(), Error> {
+    let entries = client.monitor().await?;
+    futures_util::pin_mut!(entries);
+    print_reply(&Resp::SimpleString("OK".to_string()), raw);
+
+    loop {
+        tokio::select! {
+            _ = tokio::signal::ctrl_c() => return Ok(()),
+            entry = entries.next() => match entry {
+                Some(entry) => print_reply(&Resp::SimpleString(entry?.to_string()), raw),
+                None => return Ok(()),
+            },
+        }
+    }
+}
+
+async fn repl(mut options: Options) -> Result<(), Error> {
+    let raw = options.raw.unwrap_or(false);
+    let mut editor = DefaultEditor::new().map_err(|e| Error::Other(e.to_string()))?;
+    let history = std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".rcli_history"));
+    if let Some(history) = &history {
+        let _ = editor.load_history(history);
+    }
+
+    // connected lazily, and again after a connection error
+    let mut client: Option<Client> = None;
+
+    loop {
+        let prompt = match options.db {
+            0 => format!("{}:{}> ", options.host, options.port),
+            db => format!("{}:{}[{}]> ", options.host, options.port, db),
+        };
+        let line = match tokio::task::block_in_place(|| editor.readline(&prompt)) {
+            Ok(line) => line,
+            Err(ReadlineError::Interrupted | ReadlineError::Eof) => break,
+            Err(e) => return Err(Error::Other(e.to_string())),
+        };
+        let args = match split_args(&line) {
+            Some(args) if args.is_empty() => continue,
+            Some(args) => args,
+            None => {
+                println!("Invalid argument(s)");
+                continue;
+            }
+        };
+        let _ = editor.add_history_entry(line.as_str());
+
+        let name = String::from_utf8_lossy(&args[0]).to_ascii_uppercase();
+        if name == "QUIT" || name == "EXIT" {
+            break;
+        }
+
+        let connected = match client.as_mut() {
+            Some(connected) => connected,
+            None => match connect(&options).await {
+                Ok(connected) => client.insert(connected),
+                Err(e) => {
+                    println!(
+                        "Could not connect to Redis at {}:{}: {}",
+                        options.host, options.port, e
+                    );
+                    continue;
+                }
+            },
+        };
+        let streaming = matches!(name.as_str(), "SUBSCRIBE" | "PSUBSCRIBE" | "MONITOR");
+
+        match run(connected, &args, raw).await {
+            Ok(_) if streaming => break,
+            Ok(succeeded) => {
+                // the reply of a valid SELECT is OK
+                if name == "SELECT" && succeeded {
+                    if let Some(db) = args
+                        .get(1)
+                        .and_then(|db| std::str::from_utf8(db).ok()?.parse().ok())
+                    {
+                        options.db = db;
+                    }
+                }
+            }
+            Err(e) => {
+                println!("Error: {}", e);
+                client = None;
+            }
+        }
+    }
+
+    if let Some(history) = &history {
+        let _ = editor.save_history(history);
+    }
+    Ok(())
+}
+
+// Sends the protocol read from stdin as is, then waits for all the replies
+async fn pipe(options: &Options) -> Result<(), Error> {
+    let (read, mut write) = connect_framed(options).await?.into_inner().into_split();
+
+    let mut writer = tokio::spawn(async move {
+        let mut stdin = tokio::io::stdin();
+        let mut buf = BytesMut::with_capacity(16 * 1024);
+        let mut pending = BytesMut::new();
+        let mut decoder = RespDecoder::new();
+        let mut sent = 0u64;
+
+        while stdin.read_buf(&mut buf).await? > 0 {
+            write.write_all(&buf).await?;
+            pending.extend_from_slice(&buf);
+            buf.clear();
+            while decoder
+                .decode(&mut pending)
+                .map_err(|e| Error::Serialization(format!("{:?}", e)))?
+                .is_some()
+            {
+                sent += 1;
+            }
+        }
+        write.flush().await?;
+        // dropping the write half would close the connection before the last replies
+        Ok::<_, Error>((sent, write))
+    });
+
+    let mut replies = FramedRead::new(read, RespCodec::new());
+    let (mut received, mut errors) = (0u64, 0u64);
+    let mut sent = None;
+    let mut _write = None;
+    while sent != Some(received) {
+        tokio::select! {
+            result = &mut writer, if sent.is_none() => {
+                let (n, write) = result.map_err(|e| Error::Other(e.to_string()))??;
+                sent = Some(n);
+                _write = Some(write);
+                eprintln!("All data transferred. Waiting for the last reply...");
+            }
+            reply = replies.next() => {
+                match reply {
+                    Some(Ok(Resp::Error(msg))) => {
+                        errors += 1;
+                        eprintln!("{}", msg);
+                    }
+                    Some(Ok(_)) => {}
+                    Some(Err(e)) => return Err(e),
+                    None => break,
+                }
+                received += 1;
+            }
+        }
+    }
+
+    println!("errors: {}, replies: {}", errors, received);
+    Ok(())
+}
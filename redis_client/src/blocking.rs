@@ -0,0 +1,449 @@
+//! A synchronous client over `std::net::TcpStream`, for programs not running
+//! a tokio runtime. It shares the RESP encoding and decoding of the async one.
+//!
+//! ```no_run
+//! use bytes::Bytes;
+//! use redis_client::blocking::{self, Pipeline};
+//!
+//! # fn main() -> Result<(), redis_client::error::Error> {
+//! let mut client = blocking::connect("127.0.0.1:6379")?;
+//! client.set("k", Bytes::from("v"))?;
+//! assert_eq!(Some(Bytes::from("v")), client.get("k")?);
+//!
+//! let mut pipeline = Pipeline::new();
+//! pipeline.cmd(&["INCR", "n"]).cmd(&["GET", "n"]);
+//! let replies = client.pipeline(&pipeline)?;
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::resp::{ParseErr, Resp, RespDecoder};
+use crate::{command, connection::DEFAULT_READ_BUFFER_CAPACITY, error::Error, pubsub};
+use bytes::{Bytes, BytesMut};
+use std::io::{self, Read, Write};
+use std::net::{TcpStream, ToSocketAddrs};
+use std::time::Duration;
+
+pub use crate::pubsub::Message;
+
+#[derive(Debug)]
+pub struct Client {
+    stream: TcpStream,
+    buffer: BytesMut,
+    decoder: RespDecoder,
+    // set once a read or a write timed out, or an invalid frame was read, as
+    // in the async client
+    poisoned: bool,
+}
+
+pub fn connect<A: ToSocketAddrs>(addr: A) -> Result<Client, Error> {
+    let stream = TcpStream::connect(addr)?;
+    stream.set_nodelay(true)?;
+    Ok(Client {
+        stream,
+        buffer: BytesMut::with_capacity(DEFAULT_READ_BUFFER_CAPACITY),
+        decoder: RespDecoder::new(),
+        poisoned: false,
+    })
+}
+
+impl Client {
+    /// Maximum time to wait for a reply. None, the default, waits forever.
+    pub fn set_read_timeout(&mut self, timeout: Option<Duration>) -> Result<(), Error> {
+        Ok(self.stream.set_read_timeout(timeout)?)
+    }
+
+    /// Maximum time to write a command. None, the default, waits forever.
+    pub fn set_write_timeout(&mut self, timeout: Option<Duration>) -> Result<(), Error> {
+        Ok(self.stream.set_write_timeout(timeout)?)
+    }
+
+    /// Returns true if a command timed out or got an invalid reply on this
+    /// connection. A poisoned client fails every command and must be replaced
+    /// by a new connection.
+    pub fn is_poisoned(&self) -> bool {
+        self.poisoned
+    }
+
+    pub fn ping(&mut self) -> Result<(), Error> {
+        let ping = Resp::from(command::Ping::new());
+        match self.request(ping)? {
+            Resp::SimpleString(resp) if resp == "PONG" => Ok(()),
+            others => Err(others.to_error()),
+        }
+    }
+
+    pub fn set(&mut self, key: &str, value: Bytes) -> Result<Option<()>, Error> {
+        let set = Resp::from(command::Set::new(key, value));
+        self.request(set)?.into_ok().map(|_| None)
+    }
+
+    /// SET with options. Returns false if the key was not set because of NX or XX.
+    pub fn set_with_options(
+        &mut self,
+        key: &str,
+        value: Bytes,
+        options: command::SetOptions,
+    ) -> Result<bool, Error> {
+        let set = Resp::from(command::Set::new_with_options(key, value, options));
+        match self.request(set)? {
+            Resp::Null => Ok(false),
+            resp => resp.into_ok().map(|_| true),
+        }
+    }
+
+    pub fn get(&mut self, key: &str) -> Result<Option<Bytes>, Error> {
+        let get = Resp::from(command::Get::new(key));
+        self.request(get)?.into_bytes()
+    }
+
+    pub fn incr(&mut self, key: &str) -> Result<i64, Error> {
+        let incr = Resp::from(command::Incr::new(key));
+        self.request(incr)?.into_integer()
+    }
+
+    pub fn decr(&mut self, key: &str) -> Result<i64, Error> {
+        let decr = Resp::from(command::Decr::new(key));
+        self.request(decr)?.into_integer()
+    }
+
+    /// Sends an arbitrary command, such as `["CLIENT", "INFO"]`, and returns its reply.
+    pub fn command<T: AsRef<[u8]>>(&mut self, args: &[T]) -> Result<Resp, Error> {
+        self.request(args_to_resp(args))
+    }
+
+    pub fn publish(&mut self, channel: &str, message: Bytes) -> Result<u64, Error> {
+        let publish = Resp::from(command::Publish::new(channel, message));
+        self.request(publish)?.into_integer().map(|v| v as u64)
+    }
+
+    /// Sends the commands of the pipeline at once and returns their replies in
+    /// order. Error replies are returned as `Resp::Error` without failing the
+    /// other commands.
+    pub fn pipeline(&mut self, pipeline: &Pipeline) -> Result<Vec<Resp>, Error> {
+        self.write(&pipeline.commands)?;
+        (0..pipeline.commands.len()).map(|_| self.read()).collect()
+    }
+
+    pub fn subscribe(&mut self, channels: Vec<String>) -> Result<Subscriber<'_>, Error> {
+        let subscribe = Resp::from(command::Subscribe::new(&channels));
+        self.write(&[subscribe])?;
+
+        let mut counts = Vec::with_capacity(channels.len());
+        for channel in &channels {
+            counts.push(pubsub::confirm(&self.read()?, channel, false)?);
+        }
+
+        Ok(Subscriber {
+            client: self,
+            channels,
+            patterns: vec![],
+            counts,
+        })
+    }
+
+    pub fn psubscribe(&mut self, patterns: Vec<String>) -> Result<Subscriber<'_>, Error> {
+        let psubscribe = Resp::from(command::Subscribe::new_patterns(&patterns));
+        self.write(&[psubscribe])?;
+
+        let mut counts = Vec::with_capacity(patterns.len());
+        for pattern in &patterns {
+            counts.push(pubsub::confirm(&self.read()?, pattern, true)?);
+        }
+
+        Ok(Subscriber {
+            client: self,
+            channels: vec![],
+            patterns,
+            counts,
+        })
+    }
+
+    // Sends a command and reads its reply, turning an error reply into Error
+    fn request(&mut self, command: Resp) -> Result<Resp, Error> {
+        self.write(&[command])?;
+        match self.read()? {
+            Resp::Error(msg) => Err(Error::from_server_message(&msg)),
+            resp => Ok(resp),
+        }
+    }
+
+    fn write(&mut self, commands: &[Resp]) -> Result<(), Error> {
+        self.check_poisoned()?;
+
+        let mut buf = BytesMut::new();
+        for command in commands {
+            command.encode(&mut buf);
+        }
+        self.stream
+            .write_all(&buf)
+            .map_err(|e| self.poison_on_timeout(e))
+    }
+
+    fn read(&mut self) -> Result<Resp, Error> {
+        self.read_data(false)
+    }
+
+    // Reads a pushed message, see `Subscriber::next_message`
+    fn read_message(&mut self) -> Result<Resp, Error> {
+        self.read_data(true)
+    }
+
+    // A timeout poisons the client, unless it expired while waiting for a
+    // pushed message with nothing received yet: no reply is in flight then.
+    fn read_data(&mut self, pushed: bool) -> Result<Resp, Error> {
+        self.check_poisoned()?;
+
+        let mut chunk = [0; 4096];
+        loop {
+            match self.decoder.decode(&mut self.buffer) {
+                Ok(Some(resp)) => return Ok(resp),
+                Ok(None) | Err(ParseErr::Imcomplete) => {}
+                Err(ParseErr::Invalid(e)) => {
+                    self.poisoned = true;
+                    return Err(Error::protocol(e));
+                }
+            }
+
+            let n = match self.stream.read(&mut chunk) {
+                Ok(n) => n,
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                Err(e) if pushed && self.buffer.is_empty() && is_timeout(&e) => {
+                    return Err(Error::IO(io::Error::new(io::ErrorKind::TimedOut, e)))
+                }
+                Err(e) => return Err(self.poison_on_timeout(e)),
+            };
+            if n == 0 {
+                if self.buffer.is_empty() {
+                    return Err(Error::closed());
+                }
+                return Err(Error::IO(io::Error::new(
+                    io::ErrorKind::ConnectionReset,
+                    "connection reset by peer",
+                )));
+            }
+            self.buffer.extend_from_slice(&chunk[..n]);
+        }
+    }
+
+    fn poison_on_timeout(&mut self, e: io::Error) -> Error {
+        if is_timeout(&e) {
+            self.poisoned = true;
+            Error::IO(io::Error::new(io::ErrorKind::TimedOut, e))
+        } else {
+            Error::IO(e)
+        }
+    }
+
+    fn check_poisoned(&self) -> Result<(), Error> {
+        if self.poisoned {
+            Err(Error::IO(io::Error::new(
+                io::ErrorKind::NotConnected,
+                "connection poisoned by an earlier timeout or protocol error",
+            )))
+        } else {
+            Ok(())
+        }
+    }
+}
+
+/// Commands sent together by `Client::pipeline`.
+#[derive(Debug, Clone, Default)]
+pub struct Pipeline {
+    commands: Vec<Resp>,
+}
+
+impl Pipeline {
+    pub fn new() -> Pipeline {
+        Pipeline::default()
+    }
+
+    /// Appends a command such as `["SET", "k", "v"]`.
+    pub fn cmd<T: AsRef<[u8]>>(&mut self, args: &[T]) -> &mut Self {
+        self.commands.push(args_to_resp(args));
+        self
+    }
+
+    pub fn len(&self) -> usize {
+        self.commands.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.commands.is_empty()
+    }
+}
+
+// std reports an expired timeout as WouldBlock on Unix and TimedOut on Windows
+fn is_timeout(e: &io::Error) -> bool {
+    matches!(
+        e.kind(),
+        io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut
+    )
+}
+
+fn args_to_resp<T: AsRef<[u8]>>(args: &[T]) -> Resp {
+    Resp::Array(
+        args.iter()
+            .map(|arg| Resp::BulkString(Bytes::copy_from_slice(arg.as_ref())))
+            .collect(),
+    )
+}
+
+pub struct Subscriber<'a> {
+    client: &'a mut Client,
+    channels: Vec<String>,
+    patterns: Vec<String>,
+    counts: Vec<u64>,
+}
+
+impl Subscriber<'_> {
+    pub fn channels(&self) -> &[String] {
+        &self.channels
+    }
+
+    pub fn patterns(&self) -> &[String] {
+        &self.patterns
+    }
+
+    /// The number of subscriptions of the connection confirmed by the server
+    /// after each channel or pattern, in order.
+    pub fn subscription_counts(&self) -> &[u64] {
+        &self.counts
+    }
+
+    /// Waits for the next message, up to the read timeout of the client. If
+    /// none arrived, an `ErrorKind::TimedOut` error is returned and the
+    /// subscriber can still be used.
+    pub fn next_message(&mut self) -> Result<Message, Error> {
+        let resp = self.client.read_message()?;
+        pubsub::parse(&resp)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{cmd, testing::MockServer};
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn commands_and_pipeline() {
+        let server = MockServer::start().await;
+        server
+            .expect(cmd!["SET", "k", "v"])
+            .reply(Resp::SimpleString("OK".into()));
+        server
+            .expect(cmd!["INCR", "k"])
+            .reply_error("ERR not an integer");
+        server.expect(cmd!["INCR", "n"]).reply(Resp::Integer(1));
+        server
+            .expect(cmd!["GET", "n"])
+            .reply(Resp::BulkString(Bytes::from("1")));
+        let addr = server.addr();
+
+        tokio::task::spawn_blocking(move || {
+            let mut client = connect(addr).unwrap();
+            client.set("k", Bytes::from("v")).unwrap();
+            assert!(matches!(
+                client.incr("k"),
+                Err(Error::Generic { code, .. }) if code == "ERR"
+            ));
+
+            let mut pipeline = Pipeline::new();
+            pipeline.cmd(&["INCR", "n"]).cmd(&["GET", "n"]);
+            assert_eq!(
+                vec![Resp::Integer(1), Resp::BulkString(Bytes::from("1"))],
+                client.pipeline(&pipeline).unwrap()
+            );
+        })
+        .await
+        .unwrap();
+        server.assert_done();
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn read_timeout_poisons_client() {
+        let server = MockServer::start().await;
+        server
+            .expect(cmd!["PING"])
+            .delay(Duration::from_millis(500))
+            .reply(Resp::SimpleString("PONG".into()));
+        let addr = server.addr();
+
+        tokio::task::spawn_blocking(move || {
+            let mut client = connect(addr).unwrap();
+            client
+                .set_read_timeout(Some(Duration::from_millis(50)))
+                .unwrap();
+            match client.ping() {
+                Err(Error::IO(e)) => assert_eq!(io::ErrorKind::TimedOut, e.kind()),
+                others => panic!("unexpected result {:?}", others),
+            }
+            assert!(client.is_poisoned());
+        })
+        .await
+        .unwrap();
+    }
+
+    #[test]
+    fn subscriber_idle_timeout() {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let server = std::thread::spawn(move || {
+            let (mut socket, _) = listener.accept().unwrap();
+            let mut buf = [0; 1024];
+            let n = socket.read(&mut buf).unwrap();
+            assert_eq!(b"*2\r\n$9\r\nSubscribe\r\n$2\r\nch\r\n", &buf[..n]);
+            socket
+                .write_all(b"*3\r\n$9\r\nsubscribe\r\n$2\r\nch\r\n:1\r\n")
+                .unwrap();
+            std::thread::sleep(Duration::from_millis(200));
+            socket
+                .write_all(b"*3\r\n$7\r\nmessage\r\n$2\r\nch\r\n$2\r\nhi\r\n")
+                .unwrap();
+        });
+
+        let mut client = connect(addr).unwrap();
+        client
+            .set_read_timeout(Some(Duration::from_millis(50)))
+            .unwrap();
+        let mut subscriber = client.subscribe(vec!["ch".to_string()]).unwrap();
+        match subscriber.next_message() {
+            Err(Error::IO(e)) => assert_eq!(io::ErrorKind::TimedOut, e.kind()),
+            others => panic!("unexpected result {:?}", others),
+        }
+        let message = loop {
+            match subscriber.next_message() {
+                Err(Error::IO(e)) if e.kind() == io::ErrorKind::TimedOut => {}
+                result => break result.unwrap(),
+            }
+        };
+        match message {
+            Message::Message(channel, message) => {
+                assert_eq!("ch", channel);
+                assert_eq!("hi", message);
+            }
+            others => panic!("unexpected message {:?}", others),
+        }
+        assert!(!client.is_poisoned());
+        server.join().unwrap();
+    }
+
+    #[test]
+    fn invalid_frame_poisons_client() {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let server = std::thread::spawn(move || {
+            let (mut socket, _) = listener.accept().unwrap();
+            let mut buf = [0; 64];
+            let _ = socket.read(&mut buf).unwrap();
+            socket.write_all(b"?PONG\r\n+PONG\r\n").unwrap();
+            socket
+        });
+
+        let mut client = connect(addr).unwrap();
+        assert!(client.ping().unwrap_err().is_connection_error());
+        assert!(client.is_poisoned());
+        assert!(client.ping().is_err());
+        drop(server.join().unwrap());
+    }
+}
@@ -0,0 +1,449 @@
+use crate::{command, connection::Connection, error::Error, pubsub, resp::Resp};
+use bytes::Bytes;
+use lru::LruCache;
+use std::collections::HashMap;
+use std::num::NonZeroUsize;
+use std::sync::{Arc, Mutex};
+use tokio::task::JoinHandle;
+
+const INVALIDATE_CHANNEL: &str = "__redis__:invalidate";
+
+// Commands switching or emptying whole databases without naming their keys
+const DATABASE_COMMANDS: &[&[u8]] = &[b"SELECT", b"FLUSHDB", b"FLUSHALL", b"SWAPDB"];
+
+#[derive(Debug)]
+enum Slot {
+    // the request was sent but the reply has not been stored yet
+    Pending,
+    Ready(Resp),
+}
+
+#[derive(Debug)]
+struct Entries {
+    // replies of commands reading a key, keyed by the whole command
+    // (e.g. [GET, k] and [HGET, k, f] are cached separately under k)
+    entries: LruCache<Bytes, HashMap<Vec<Bytes>, Slot>>,
+    // set once invalidation messages can no longer be received
+    broken: bool,
+}
+
+/// Local cache of replies invalidated by the server with CLIENT TRACKING.
+///
+/// Invalidation messages are received on a dedicated connection subscribing
+/// to `__redis__:invalidate` (the RESP2 redirect mode), so that they are
+/// applied even while the main connection is idle.
+#[derive(Debug)]
+pub(crate) struct Cache {
+    shared: Arc<Mutex<Entries>>,
+    task: JoinHandle<()>,
+}
+
+impl Cache {
+    /// Subscribes the invalidation connection, already authenticated, and
+    /// returns the cache with the client ID to redirect invalidation messages to.
+    pub(crate) async fn start(
+        mut connection: Connection,
+        capacity: usize,
+    ) -> Result<(Cache, u64), Error> {
+        let capacity = NonZeroUsize::new(capacity)
+            .ok_or_else(|| Error::Invalid("cache capacity must be positive".to_string()))?;
+
+        connection
+            .write_data(&Resp::from(command::ClientId::new()))
+            .await?;
+        let id = read(&mut connection).await?.into_integer()? as u64;
+
+        let channels = vec![INVALIDATE_CHANNEL.to_string()];
+        connection
+            .write_data(&Resp::from(command::Subscribe::new(&channels)))
+            .await?;
+        pubsub::confirm(&read(&mut connection).await?, INVALIDATE_CHANNEL, false)?;
+
+        let shared = Arc::new(Mutex::new(Entries {
+            entries: LruCache::new(capacity),
+            broken: false,
+        }));
+        let task = tokio::spawn(invalidate(connection, shared.clone()));
+        Ok((Cache { shared, task }, id))
+    }
+
+    /// Returns the cached reply of `command`, whose first argument must be the
+    /// only key it reads. On a miss, the command is marked pending so that
+    /// `fill` can tell whether it was invalidated meanwhile.
+    pub(crate) fn lookup(&self, command: &Resp) -> Option<Resp> {
+        let mut shared = self.shared.lock().unwrap();
+        if shared.broken {
+            return None;
+        }
+
+        let key = read_key(command)?.clone();
+        let entries = shared.entries.get_or_insert_mut(key, HashMap::new);
+        match entries.get(&signature(command)) {
+            Some(Slot::Ready(resp)) => Some(resp.clone()),
+            Some(Slot::Pending) => None,
+            None => {
+                entries.insert(signature(command), Slot::Pending);
+                None
+            }
+        }
+    }
+
+    /// Stores the reply unless the key was invalidated since `lookup`.
+    pub(crate) fn fill(&self, command: &Resp, resp: &Resp) {
+        let Some(key) = read_key(command) else {
+            return;
+        };
+        let mut shared = self.shared.lock().unwrap();
+        if let Some(entries) = shared.entries.get_mut(key) {
+            if let Some(slot @ Slot::Pending) = entries.get_mut(&signature(command)) {
+                *slot = Slot::Ready(resp.clone());
+            }
+        }
+    }
+
+    /// Drops the cached replies of every argument of `command` which may be a
+    /// key, so that a write is visible to the next read of the same client
+    /// even before its invalidation message arrives. Commands switching or
+    /// flushing databases drop every cached reply.
+    pub(crate) fn evict(&self, command: &Resp) {
+        let mut shared = self.shared.lock().unwrap();
+        if let Resp::Array(args) = command {
+            if let Some(Resp::BulkString(name)) = args.first() {
+                if DATABASE_COMMANDS
+                    .iter()
+                    .any(|c| name.eq_ignore_ascii_case(c))
+                {
+                    shared.entries.clear();
+                    return;
+                }
+            }
+            for arg in args.iter().skip(1) {
+                if let Resp::BulkString(arg) = arg {
+                    shared.entries.pop(arg);
+                }
+            }
+        }
+    }
+
+    pub(crate) fn is_broken(&self) -> bool {
+        self.shared.lock().unwrap().broken
+    }
+}
+
+impl Drop for Cache {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+fn read_key(command: &Resp) -> Option<&Bytes> {
+    match command {
+        Resp::Array(args) => match args.get(1) {
+            Some(Resp::BulkString(key)) => Some(key),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+fn signature(command: &Resp) -> Vec<Bytes> {
+    match command {
+        Resp::Array(ary) => ary
+            .iter()
+            .filter_map(|v| match v {
+                Resp::BulkString(v) => Some(v.clone()),
+                _ => None,
+            })
+            .collect(),
+        _ => vec![],
+    }
+}
+
+async fn read(connection: &mut Connection) -> Result<Resp, Error> {
+    match connection.read_data().await? {
+        Some(Resp::Error(msg)) => Err(Error::from_server_message(&msg)),
+        Some(resp) => Ok(resp),
+        None => Err(Error::closed()),
+    }
+}
+
+async fn invalidate(mut connection: Connection, shared: Arc<Mutex<Entries>>) {
+    loop {
+        let resp = match connection.read_data().await {
+            Ok(Some(resp)) => resp,
+            // without invalidation messages cached values could be stale forever
+            _ => {
+                let mut shared = shared.lock().unwrap();
+                shared.broken = true;
+                shared.entries.clear();
+                return;
+            }
+        };
+
+        // ["message", "__redis__:invalidate", [key...]], or nil instead of the
+        // keys when the whole keyspace is flushed
+        let mut shared = shared.lock().unwrap();
+        match resp {
+            Resp::Array(mut ary) if ary.len() == 3 => match ary.pop() {
+                Some(Resp::Array(keys)) => {
+                    for key in keys {
+                        if let Resp::BulkString(key) = key {
+                            shared.entries.pop(&key);
+                        }
+                    }
+                }
+                _ => shared.entries.clear(),
+            },
+            _ => shared.entries.clear(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::middleware::{Middleware, Next};
+    use crate::{client, cmd, error::Error, resp::Resp, testing::MockServer};
+    use bytes::Bytes;
+    use futures_util::future::BoxFuture;
+    use std::sync::Arc;
+    use std::time::Duration;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::{TcpListener, TcpStream};
+
+    async fn expect(socket: &mut TcpStream, request: &[u8], reply: &[u8]) {
+        let mut buf = [0; 1024];
+        let n = socket.read(&mut buf).await.unwrap();
+        assert_eq!(
+            String::from_utf8_lossy(request),
+            String::from_utf8_lossy(&buf[..n])
+        );
+        socket.write_all(reply).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn invalidate_cached_reply() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let client = tokio::spawn(client::connect(addr));
+        let (mut main, _) = listener.accept().await.unwrap();
+        let mut client = client.await.unwrap().unwrap();
+
+        let server = tokio::spawn(async move {
+            let (mut invalidation, _) = listener.accept().await.unwrap();
+            expect(
+                &mut invalidation,
+                b"*2\r\n$6\r\nCLIENT\r\n$2\r\nID\r\n",
+                b":7\r\n",
+            )
+            .await;
+            expect(
+                &mut invalidation,
+                b"*2\r\n$9\r\nSubscribe\r\n$20\r\n__redis__:invalidate\r\n",
+                b"*3\r\n$9\r\nsubscribe\r\n$20\r\n__redis__:invalidate\r\n:1\r\n",
+            )
+            .await;
+            expect(
+                &mut main,
+                b"*5\r\n$6\r\nCLIENT\r\n$8\r\nTRACKING\r\n$2\r\nON\r\n$8\r\nREDIRECT\r\n$1\r\n7\r\n",
+                b"+OK\r\n",
+            )
+            .await;
+            expect(
+                &mut main,
+                b"*2\r\n$3\r\nGET\r\n$1\r\nk\r\n",
+                b"$2\r\nv1\r\n",
+            )
+            .await;
+            (main, invalidation)
+        });
+
+        client.enable_cache(16).await.unwrap();
+        assert_eq!(Some("v1".into()), client.get("k").await.unwrap());
+        let (mut main, mut invalidation) = server.await.unwrap();
+
+        // served from the cache without any request
+        assert_eq!(Some("v1".into()), client.get("k").await.unwrap());
+
+        invalidation
+            .write_all(b"*3\r\n$7\r\nmessage\r\n$20\r\n__redis__:invalidate\r\n*1\r\n$1\r\nk\r\n")
+            .await
+            .unwrap();
+        tokio::time::sleep(Duration::from_millis(50)).await;
+
+        let server = tokio::spawn(async move {
+            expect(
+                &mut main,
+                b"*2\r\n$3\r\nGET\r\n$1\r\nk\r\n",
+                b"$2\r\nv2\r\n",
+            )
+            .await;
+        });
+        assert_eq!(Some("v2".into()), client.get("k").await.unwrap());
+        server.await.unwrap();
+        assert!(client.is_cache_active());
+    }
+
+    // Prefixes the key of the commands other than CLIENT
+    #[derive(Debug)]
+    struct Prefix;
+
+    impl Middleware for Prefix {
+        fn handle<'a>(
+            &'a self,
+            command: Resp,
+            mut next: Next<'a>,
+        ) -> BoxFuture<'a, Result<Resp, Error>> {
+            let command = match command {
+                Resp::Array(mut args) if args[0] != Resp::BulkString("CLIENT".into()) => {
+                    if let Some(Resp::BulkString(key)) = args.get_mut(1) {
+                        *key = Bytes::from([b"app:", &key[..]].concat());
+                    }
+                    Resp::Array(args)
+                }
+                others => others,
+            };
+            Box::pin(async move { next.run(command).await })
+        }
+    }
+
+    #[tokio::test]
+    async fn prefix_middleware() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let client = tokio::spawn(async move {
+            let builder = client::Client::builder().middleware(Arc::new(Prefix));
+            builder.connect(addr).await
+        });
+        let (mut main, _) = listener.accept().await.unwrap();
+        let mut client = client.await.unwrap().unwrap();
+
+        let server = tokio::spawn(async move {
+            let (mut invalidation, _) = listener.accept().await.unwrap();
+            expect(
+                &mut invalidation,
+                b"*2\r\n$6\r\nCLIENT\r\n$2\r\nID\r\n",
+                b":7\r\n",
+            )
+            .await;
+            expect(
+                &mut invalidation,
+                b"*2\r\n$9\r\nSubscribe\r\n$20\r\n__redis__:invalidate\r\n",
+                b"*3\r\n$9\r\nsubscribe\r\n$20\r\n__redis__:invalidate\r\n:1\r\n",
+            )
+            .await;
+            expect(
+                &mut main,
+                b"*5\r\n$6\r\nCLIENT\r\n$8\r\nTRACKING\r\n$2\r\nON\r\n$8\r\nREDIRECT\r\n$1\r\n7\r\n",
+                b"+OK\r\n",
+            )
+            .await;
+            expect(
+                &mut main,
+                b"*2\r\n$3\r\nGET\r\n$5\r\napp:k\r\n",
+                b"$2\r\nv1\r\n",
+            )
+            .await;
+            (main, invalidation)
+        });
+
+        client.enable_cache(16).await.unwrap();
+        assert_eq!(Some("v1".into()), client.get("k").await.unwrap());
+        let (mut main, mut invalidation) = server.await.unwrap();
+        assert_eq!(Some("v1".into()), client.get("k").await.unwrap());
+
+        // the server tracks the key it received
+        invalidation
+            .write_all(
+                b"*3\r\n$7\r\nmessage\r\n$20\r\n__redis__:invalidate\r\n*1\r\n$5\r\napp:k\r\n",
+            )
+            .await
+            .unwrap();
+        tokio::time::sleep(Duration::from_millis(50)).await;
+
+        let server = tokio::spawn(async move {
+            expect(
+                &mut main,
+                b"*2\r\n$3\r\nGET\r\n$5\r\napp:k\r\n",
+                b"$2\r\nv2\r\n",
+            )
+            .await;
+            expect(
+                &mut main,
+                b"*3\r\n$3\r\nSET\r\n$5\r\napp:k\r\n$2\r\nv3\r\n",
+                b"+OK\r\n",
+            )
+            .await;
+            expect(
+                &mut main,
+                b"*2\r\n$3\r\nGET\r\n$5\r\napp:k\r\n",
+                b"$2\r\nv3\r\n",
+            )
+            .await;
+        });
+        assert_eq!(Some("v2".into()), client.get("k").await.unwrap());
+        client.set("k", Bytes::from("v3")).await.unwrap();
+        assert_eq!(Some("v3".into()), client.get("k").await.unwrap());
+        server.await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn read_own_writes() {
+        let ok = || Resp::SimpleString("OK".into());
+        let server = MockServer::start().await;
+        // main connection, then the invalidation one with the same handshake
+        server.expect(cmd!["AUTH", "app", "secret"]).reply(ok());
+        server.expect(cmd!["SELECT", "2"]).reply(ok());
+        server.expect(cmd!["AUTH", "app", "secret"]).reply(ok());
+        server.expect(cmd!["SELECT", "2"]).reply(ok());
+        server.expect(cmd!["CLIENT", "ID"]).reply(Resp::Integer(7));
+        server
+            .expect(cmd!["Subscribe", "__redis__:invalidate"])
+            .reply(Resp::Array(vec![
+                Resp::BulkString("subscribe".into()),
+                Resp::BulkString("__redis__:invalidate".into()),
+                Resp::Integer(1),
+            ]));
+        server
+            .expect(cmd!["CLIENT", "TRACKING", "ON", "REDIRECT", "7"])
+            .reply(ok());
+        server
+            .expect(cmd!["GET", "k"])
+            .reply(Resp::BulkString("v1".into()));
+        server.expect(cmd!["SET", "k", "v2"]).reply(ok());
+        server
+            .expect(cmd!["GET", "k"])
+            .reply(Resp::BulkString("v2".into()));
+        server.expect(cmd!["DEL", "k"]).reply(Resp::Integer(1));
+        server.expect(cmd!["GET", "k"]).reply(Resp::Null);
+        server.expect(cmd!["select", "3"]).reply(ok());
+        server
+            .expect(cmd!["GET", "k"])
+            .reply(Resp::BulkString("v3".into()));
+        server.expect(cmd!["FLUSHDB"]).reply(ok());
+        server.expect(cmd!["GET", "k"]).reply(Resp::Null);
+
+        let mut client = client::Client::builder()
+            .username("app")
+            .password("secret")
+            .database(2)
+            .connect(server.addr())
+            .await
+            .unwrap();
+        client.enable_cache(16).await.unwrap();
+
+        assert_eq!(Some("v1".into()), client.get("k").await.unwrap());
+        assert_eq!(Some("v1".into()), client.get("k").await.unwrap());
+        client.set("k", Bytes::from("v2")).await.unwrap();
+        assert_eq!(Some("v2".into()), client.get("k").await.unwrap());
+        client.del(&["k"]).await.unwrap();
+        assert_eq!(None, client.get("k").await.unwrap());
+
+        // the other database may hold another value
+        client.command(&["select", "3"]).await.unwrap();
+        assert_eq!(Some("v3".into()), client.get("k").await.unwrap());
+        assert_eq!(Some("v3".into()), client.get("k").await.unwrap());
+        client.command(&["FLUSHDB"]).await.unwrap();
+        assert_eq!(None, client.get("k").await.unwrap());
+        server.assert_done();
+    }
+}
@@ -1,26 +1,84 @@
+use crate::cache::Cache;
+use crate::metrics::{self, CommandEvent, Metrics, Outcome};
+use crate::middleware::{Middleware, Next};
 use crate::pubsub;
 use crate::{command, connection::Connection, error::Error, resp::Resp, subscriber::Subscriber};
 use bytes::Bytes;
-use tokio::net::{TcpStream, ToSocketAddrs};
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::net::ToSocketAddrs;
+use tracing::{field, Instrument};
+
+mod builder;
+mod caching;
+#[cfg(any(feature = "json", feature = "bincode", feature = "msgpack"))]
+mod encoded;
+mod functions;
+mod hashes;
+mod keys;
+mod lists;
+mod scripting;
+mod server;
+mod sets;
+mod sorted_sets;
+mod streams;
+#[cfg(feature = "serde")]
+mod typed;
+
+pub use crate::command::{
+    Aggregate, Direction, ExpireCondition, FlushMode, InsertPosition, SetOperation, SetOptions,
+    ZAddOptions, ZRange,
+};
+pub use builder::ClientBuilder;
 
 #[derive(Debug)]
 pub struct Client {
     connection: Connection,
+    cache: Option<Cache>,
+    command_timeout: Option<Duration>,
+    metrics: Option<Arc<dyn Metrics>>,
+    redact_args: bool,
+    middlewares: Arc<[Arc<dyn Middleware>]>,
+    // to open other connections to the same server, such as the one of the cache
+    builder: ClientBuilder,
+    addr: SocketAddr,
 }
 
 pub async fn connect<T: ToSocketAddrs>(addr: T) -> Result<Client, Error> {
-    let stream = TcpStream::connect(addr).await.map_err(|e| e.into())?;
-    let connection = Connection::new(stream);
-
-    Ok(Client { connection })
+    ClientBuilder::new().connect(addr).await
 }
 
 impl Client {
+    pub fn builder() -> ClientBuilder {
+        ClientBuilder::new()
+    }
+
+    /// Returns true if a command timed out or got an invalid reply on this
+    /// connection. A poisoned client fails every command and must be replaced
+    /// by a new connection.
+    pub fn is_poisoned(&self) -> bool {
+        self.connection.is_poisoned()
+    }
+
+    // Marks the connection as unusable after a request was abandoned while its
+    // reply may still arrive
+    pub(crate) fn poison(&mut self, msg: &str) -> Error {
+        self.connection.poison(msg)
+    }
+
+    // Replaces the connection, e.g. once poisoned, by a new one opened with the
+    // same settings. The cache is dropped since tracking was enabled on the old
+    // connection only.
+    pub(crate) async fn reconnect(&mut self) -> Result<(), Error> {
+        self.connection = self.builder.open(self.addr).await?;
+        self.cache = None;
+        Ok(())
+    }
+
     pub async fn ping(&mut self) -> Result<(), Error> {
         let ping = Resp::from(command::Ping::new());
-        self.connection.write_data(&ping).await?;
-
-        match self.read_response().await? {
+        match self.request(ping).await? {
             Resp::SimpleString(resp) if resp == "PONG" => Ok(()),
             others => Err(others.to_error()),
         }
@@ -28,49 +86,54 @@ impl Client {
 
     pub async fn set(&mut self, key: &str, value: Bytes) -> Result<Option<()>, Error> {
         let set = Resp::from(command::Set::new(key, value));
-        self.connection.write_data(&set).await?;
+        self.request(set).await?.into_ok().map(|_| None)
+    }
 
-        let r = self.read_response().await?;
-        dbg!(&r);
-        match r {
-            Resp::SimpleString(resp) if resp == "OK" => Ok(None),
-            others => Err(others.to_error()),
+    /// SET with options. Returns false if the key was not set because of NX or XX.
+    pub async fn set_with_options(
+        &mut self,
+        key: &str,
+        value: Bytes,
+        options: command::SetOptions,
+    ) -> Result<bool, Error> {
+        let set = Resp::from(command::Set::new_with_options(key, value, options));
+        match self.request(set).await? {
+            Resp::Null => Ok(false),
+            resp => resp.into_ok().map(|_| true),
         }
     }
 
     pub async fn get(&mut self, key: &str) -> Result<Option<Bytes>, Error> {
         let get = Resp::from(command::Get::new(key));
-        self.connection.write_data(&get).await?;
-        match self.read_response().await? {
-            Resp::SimpleString(resp) => Ok(Some(resp.into())),
-            Resp::BulkString(resp) => Ok(Some(resp)),
-            Resp::Null => Ok(None),
-            others => Err(others.to_error()),
-        }
+        self.cached_request(get).await?.into_bytes()
     }
 
     pub async fn incr(&mut self, key: &str) -> Result<i64, Error> {
         let incr = Resp::from(command::Incr::new(key));
-        self.connection.write_data(&incr).await?;
-        self.handle_incr_decr_resp().await
+        self.request(incr).await?.into_integer()
     }
 
     pub async fn decr(&mut self, key: &str) -> Result<i64, Error> {
-        let incr = Resp::from(command::Decr::new(key));
-        self.connection.write_data(&incr).await?;
-        self.handle_incr_decr_resp().await
+        let decr = Resp::from(command::Decr::new(key));
+        self.request(decr).await?.into_integer()
     }
 
-    pub async fn publish(&mut self, channel: &str, message: Bytes) -> Result<u64, Error> {
-        let incr = Resp::from(command::Publish::new(channel, message));
-        self.connection.write_data(&incr).await?;
+    /// Sends an arbitrary command, such as `["CLIENT", "INFO"]`, and returns its reply.
+    pub async fn command<T: AsRef<[u8]>>(&mut self, args: &[T]) -> Result<Resp, Error> {
+        let command = Resp::Array(
+            args.iter()
+                .map(|arg| Resp::BulkString(Bytes::copy_from_slice(arg.as_ref())))
+                .collect(),
+        );
+        self.request(command).await
+    }
 
-        match self.read_response().await? {
-            Resp::Integer(v) => Ok(v as u64),
-            rest => Err(Error::Invalid(
-                format!("invalid response {:?}", rest).into(),
-            )),
-        }
+    pub async fn publish(&mut self, channel: &str, message: Bytes) -> Result<u64, Error> {
+        let publish = Resp::from(command::Publish::new(channel, message));
+        self.request(publish)
+            .await?
+            .into_integer()
+            .map(|v| v as u64)
     }
 
     pub async fn subscribe<'a>(
@@ -80,42 +143,155 @@ impl Client {
         let incr = Resp::from(command::Subscribe::new(&channels));
         self.connection.write_data(&incr).await?;
 
+        let mut counts = Vec::with_capacity(channels.len());
         for channel in &channels {
-            use pubsub::Message;
-
-            let resp = self.read_response().await?;
-            match pubsub::parse(&resp)? {
-                Message::Subscribe(ch, _) if ch == channel => {}
-                others => {
-                    return Err(Error::Other(
-                        format!(
-                            "message whose channel is {:?} is required, but {:?}",
-                            channel, others
-                        )
-                        .into(),
-                    ))
-                }
-            }
+            counts.push(pubsub::confirm(
+                &self.read_response().await?,
+                channel,
+                false,
+            )?);
         }
 
-        Ok(Subscriber::new(self, channels))
+        Ok(Subscriber::new(self, channels).with_counts(counts))
     }
 
-    async fn handle_incr_decr_resp(&mut self) -> Result<i64, Error> {
-        match self.read_response().await? {
-            Resp::Integer(resp) => Ok(resp),
-            Resp::Error(msg) => Err(Error::RedisError(msg)),
-            rest => Err(Error::Invalid(
-                format!("invalid response {:?}", rest).into(),
-            )),
+    pub async fn psubscribe<'a>(
+        &'a mut self,
+        patterns: Vec<String>,
+    ) -> Result<Subscriber<'a>, Error> {
+        let psubscribe = Resp::from(command::Subscribe::new_patterns(&patterns));
+        self.connection.write_data(&psubscribe).await?;
+
+        let mut counts = Vec::with_capacity(patterns.len());
+        for pattern in &patterns {
+            counts.push(pubsub::confirm(
+                &self.read_response().await?,
+                pattern,
+                true,
+            )?);
         }
+
+        Ok(Subscriber::new_patterns(self, patterns).with_counts(counts))
+    }
+
+    // Sends a command and reads its reply, turning an error reply into Error
+    pub(crate) async fn request(&mut self, command: Resp) -> Result<Resp, Error> {
+        self.dispatch(command, self.command_timeout, false).await
+    }
+
+    // Same as `request` for commands blocking on the server for up to `block`
+    // (zero blocks forever): the command timeout is added as a margin.
+    pub(crate) async fn request_blocking(
+        &mut self,
+        command: Resp,
+        block: Duration,
+    ) -> Result<Resp, Error> {
+        let timeout = match self.command_timeout {
+            Some(_) if block.is_zero() => None,
+            timeout => timeout.map(|t| t + block),
+        };
+        self.dispatch(command, timeout, false).await
+    }
+
+    // Passes a command through the middlewares. `cached` tells if its reply may
+    // be served from the cache, see `execute`.
+    pub(crate) async fn dispatch(
+        &mut self,
+        command: Resp,
+        timeout: Option<Duration>,
+        cached: bool,
+    ) -> Result<Resp, Error> {
+        if self.middlewares.is_empty() {
+            return self.execute(command, timeout, cached).await;
+        }
+        let stack = self.middlewares.clone();
+        Next::new(&stack, self, timeout, cached).run(command).await
+    }
+
+    // Runs a request/reply command in a tracing span and reports it to the
+    // metrics. With the cache enabled, a `cached` read is looked up and stored
+    // there, while other commands evict the keys they name, even if they
+    // failed since they may have been applied, e.g. on a timeout.
+    pub(crate) async fn execute(
+        &mut self,
+        command: Resp,
+        timeout: Option<Duration>,
+        cached: bool,
+    ) -> Result<Resp, Error> {
+        if cached {
+            if let Some(resp) = self.cache.as_ref().and_then(|c| c.lookup(&command)) {
+                return Ok(resp);
+            }
+        }
+
+        let name = metrics::command_name(&command);
+        let span = tracing::debug_span!(
+            "redis_command",
+            db.system = "redis",
+            db.operation = %name,
+            db.statement = field::Empty,
+        );
+        if !span.is_disabled() {
+            let statement = metrics::statement(&command, &name, self.redact_args);
+            span.record("db.statement", &field::display(statement));
+        }
+
+        let start = Instant::now();
+        let (written, read) = (
+            self.connection.bytes_written(),
+            self.connection.bytes_read(),
+        );
+
+        let result = async {
+            self.connection.write_data(&command).await?;
+            match self.connection.read_data_timeout(timeout).await? {
+                Some(Resp::Error(msg)) => Err(Error::from_server_message(&msg)),
+                Some(resp) => Ok(resp),
+                None => Err(Error::closed()),
+            }
+        }
+        .instrument(span.clone())
+        .await;
+
+        if let Err(e) = &result {
+            span.in_scope(|| tracing::debug!(error = %e, "command failed"));
+        }
+        if let Some(metrics) = &self.metrics {
+            metrics.record(&CommandEvent {
+                command: &name,
+                latency: start.elapsed(),
+                bytes_out: self.connection.bytes_written() - written,
+                bytes_in: self.connection.bytes_read() - read,
+                outcome: Outcome::of(&result),
+            });
+        }
+        if let Some(cache) = &self.cache {
+            if !cached {
+                cache.evict(&command);
+            } else if let Ok(resp) = &result {
+                cache.fill(&command, resp);
+            }
+        }
+        result
     }
 
     pub(crate) async fn read_response(&mut self) -> Result<Resp, Error> {
-        let resp = self.connection.read_data().await?;
+        let resp = self
+            .connection
+            .read_data_timeout(self.command_timeout)
+            .await?;
+        match resp {
+            Some(r) => Ok(r),
+            None => Err(Error::closed()),
+        }
+    }
+
+    // Reads a pushed message, which may take any time to arrive
+    pub(crate) async fn read_message(&mut self) -> Result<Resp, Error> {
+        let resp = self.connection.read_data_timeout(None).await?;
         match resp {
             Some(r) => Ok(r),
-            None => Err(Error::Invalid("invalid state".into())),
+            None => Err(Error::closed()),
         }
     }
 }
@@ -0,0 +1,205 @@
+use super::Client;
+use crate::{
+    command,
+    connection::{Connection, DEFAULT_READ_BUFFER_CAPACITY},
+    error::Error,
+    metrics::Metrics,
+    middleware::Middleware,
+    resp::Resp,
+};
+use std::io::{Error as IError, ErrorKind};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::net::{TcpStream, ToSocketAddrs};
+
+/// Configures and opens a `Client`. No timeout is set by default.
+///
+/// When a command or a write times out, the reply stream of the connection is
+/// out of sync with the commands sent, so the client is poisoned and fails
+/// every subsequent command (see `Client::is_poisoned`).
+#[derive(Debug, Clone)]
+pub struct ClientBuilder {
+    connect_timeout: Option<Duration>,
+    command_timeout: Option<Duration>,
+    write_timeout: Option<Duration>,
+    read_buffer_capacity: usize,
+    metrics: Option<Arc<dyn Metrics>>,
+    redact_args: bool,
+    middlewares: Vec<Arc<dyn Middleware>>,
+    username: Option<String>,
+    password: Option<String>,
+    database: u64,
+}
+
+impl Default for ClientBuilder {
+    fn default() -> Self {
+        ClientBuilder::new()
+    }
+}
+
+impl ClientBuilder {
+    pub fn new() -> ClientBuilder {
+        ClientBuilder {
+            connect_timeout: None,
+            command_timeout: None,
+            write_timeout: None,
+            read_buffer_capacity: DEFAULT_READ_BUFFER_CAPACITY,
+            metrics: None,
+            redact_args: false,
+            middlewares: vec![],
+            username: None,
+            password: None,
+            database: 0,
+        }
+    }
+
+    /// Maximum time to establish the TCP connection.
+    pub fn connect_timeout(mut self, timeout: Duration) -> Self {
+        self.connect_timeout = Some(timeout);
+        self
+    }
+
+    /// Maximum time to wait for the reply of a command. Blocking commands
+    /// (BLPOP, XREAD with BLOCK, ...) wait for their own timeout plus this one.
+    pub fn command_timeout(mut self, timeout: Duration) -> Self {
+        self.command_timeout = Some(timeout);
+        self
+    }
+
+    /// Maximum time to write and flush a command.
+    pub fn write_timeout(mut self, timeout: Duration) -> Self {
+        self.write_timeout = Some(timeout);
+        self
+    }
+
+    /// Initial capacity of the buffer receiving replies, 4 KiB by default.
+    pub fn read_buffer_capacity(mut self, capacity: usize) -> Self {
+        self.read_buffer_capacity = capacity;
+        self
+    }
+
+    /// Receives the name, latency, size and outcome of every command.
+    pub fn metrics(mut self, metrics: Arc<dyn Metrics>) -> Self {
+        self.metrics = Some(metrics);
+        self
+    }
+
+    /// Replaces the arguments of commands by `?` in tracing spans, to keep
+    /// keys and values out of logs.
+    pub fn redact_args(mut self, redact: bool) -> Self {
+        self.redact_args = redact;
+        self
+    }
+
+    /// Adds a middleware below the ones already added, see `redis_client::middleware`.
+    pub fn middleware(mut self, middleware: Arc<dyn Middleware>) -> Self {
+        self.middlewares.push(middleware);
+        self
+    }
+
+    /// Sends AUTH with this password once connected.
+    pub fn password(mut self, password: &str) -> Self {
+        self.password = Some(password.to_string());
+        self
+    }
+
+    /// Username sent to AUTH with the password, for ACL users.
+    pub fn username(mut self, username: &str) -> Self {
+        self.username = Some(username.to_string());
+        self
+    }
+
+    /// Sends SELECT with this database once connected, unless it is 0.
+    pub fn database(mut self, db: u64) -> Self {
+        self.database = db;
+        self
+    }
+
+    pub async fn connect<T: ToSocketAddrs>(&self, addr: T) -> Result<Client, Error> {
+        let connection = self.open(addr).await?;
+
+        Ok(Client {
+            addr: connection.peer_addr()?,
+            connection,
+            cache: None,
+            command_timeout: self.command_timeout,
+            metrics: self.metrics.clone(),
+            redact_args: self.redact_args,
+            middlewares: self.middlewares.clone().into(),
+            builder: self.clone(),
+        })
+    }
+
+    // Connects and authenticates a connection, also used for the connections
+    // a client opens by itself
+    pub(crate) async fn open<T: ToSocketAddrs>(&self, addr: T) -> Result<Connection, Error> {
+        let stream = match self.connect_timeout {
+            Some(timeout) => tokio::time::timeout(timeout, TcpStream::connect(addr))
+                .await
+                .map_err(|_| IError::new(ErrorKind::TimedOut, "connect timed out"))??,
+            None => TcpStream::connect(addr).await?,
+        };
+        let mut connection = Connection::with_capacity(stream, self.read_buffer_capacity);
+        connection.set_write_timeout(self.write_timeout);
+
+        if let Some(password) = &self.password {
+            let auth = command::Auth::new(self.username.as_deref(), password);
+            self.handshake(&mut connection, auth.into()).await?;
+        }
+        if self.database != 0 {
+            let select = command::Select::new(self.database);
+            self.handshake(&mut connection, select.into()).await?;
+        }
+        Ok(connection)
+    }
+
+    async fn handshake(&self, connection: &mut Connection, command: Resp) -> Result<(), Error> {
+        connection.write_data(&command).await?;
+        match connection.read_data_timeout(self.command_timeout).await? {
+            Some(Resp::Error(msg)) => Err(Error::from_server_message(&msg)),
+            Some(resp) => resp.into_ok(),
+            None => Err(Error::closed()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::ErrorKind;
+    use tokio::io::AsyncReadExt;
+    use tokio::net::TcpListener;
+
+    #[tokio::test]
+    async fn command_timeout_poisons_client() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let client = tokio::spawn(async move {
+            ClientBuilder::new()
+                .command_timeout(Duration::from_millis(50))
+                .connect(addr)
+                .await
+        });
+        let (mut socket, _) = listener.accept().await.unwrap();
+        let mut client = client.await.unwrap().unwrap();
+
+        // the server reads the command but never replies
+        let server = tokio::spawn(async move {
+            let mut buf = [0; 1024];
+            while socket.read(&mut buf).await.unwrap() > 0 {}
+        });
+
+        match client.ping().await {
+            Err(Error::IO(e)) => assert_eq!(e.kind(), ErrorKind::TimedOut),
+            others => panic!("unexpected result {:?}", others),
+        }
+        assert!(client.is_poisoned());
+        match client.ping().await {
+            Err(Error::IO(e)) => assert_eq!(e.kind(), ErrorKind::NotConnected),
+            others => panic!("unexpected result {:?}", others),
+        }
+
+        drop(client);
+        server.await.unwrap();
+    }
+}
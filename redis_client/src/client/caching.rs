@@ -0,0 +1,49 @@
+use super::Client;
+use crate::{cache::Cache, command, error::Error, resp::Resp};
+
+impl Client {
+    /// Enables the client-side cache holding up to `capacity` keys.
+    ///
+    /// Replies of read commands such as `get`, `hget`, `hgetall`, `lrange`,
+    /// `smembers` and `zscore` are served locally until the server reports
+    /// the key was modified. This opens another connection to the same server
+    /// to receive invalidation messages (CLIENT TRACKING with REDIRECT). If
+    /// that connection is lost, the cache is bypassed from then on. It is
+    /// opened with the settings of the `ClientBuilder`, including AUTH and
+    /// SELECT.
+    ///
+    /// Commands sent by this client evict the keys they name from the cache,
+    /// so that its own writes are read back immediately. SELECT, FLUSHDB,
+    /// FLUSHALL and SWAPDB, e.g. sent with `command`, clear the whole cache.
+    ///
+    /// The cache sits below the middlewares: it is keyed on the commands they
+    /// pass down, such as prefixed keys, and cached reads still go through them.
+    pub async fn enable_cache(&mut self, capacity: usize) -> Result<(), Error> {
+        let connection = self.builder.open(self.addr).await?;
+        let (cache, id) = Cache::start(connection, capacity).await?;
+
+        let tracking = Resp::from(command::ClientTracking::on(Some(id)));
+        self.request(tracking).await?.into_ok()?;
+        self.cache = Some(cache);
+        Ok(())
+    }
+
+    pub async fn disable_cache(&mut self) -> Result<(), Error> {
+        if self.cache.take().is_some() {
+            let tracking = Resp::from(command::ClientTracking::off());
+            self.request(tracking).await?.into_ok()?;
+        }
+        Ok(())
+    }
+
+    /// Returns true if the cache is enabled and still receiving invalidation messages.
+    pub fn is_cache_active(&self) -> bool {
+        matches!(&self.cache, Some(cache) if !cache.is_broken())
+    }
+
+    // Same as request, but served from the cache if enabled. The first argument
+    // of the command must be the only key it reads
+    pub(crate) async fn cached_request(&mut self, command: Resp) -> Result<Resp, Error> {
+        self.dispatch(command, self.command_timeout, true).await
+    }
+}
@@ -0,0 +1,194 @@
+use super::Client;
+use crate::{command::SetOptions, error::Error};
+use bytes::Bytes;
+use serde::{de::DeserializeOwned, Serialize};
+use std::fmt;
+
+fn encode_error(format: &str, key: &str, e: impl fmt::Display) -> Error {
+    Error::Serialization(format!(
+        "failed to encode the {} value of {:?}: {}",
+        format, key, e
+    ))
+}
+
+fn decode_error(format: &str, key: &str, e: impl fmt::Display) -> Error {
+    Error::Serialization(format!(
+        "failed to decode the {} value of {:?}: {}",
+        format, key, e
+    ))
+}
+
+// Generates the set/get methods of a format enabled by a cargo feature
+macro_rules! encoded_methods {
+    (
+        feature: $feature:literal,
+        format: $format:literal,
+        set: $set:ident,
+        set_with_options: $set_with_options:ident,
+        get: $get:ident,
+        encode: $encode:path,
+        decode: $decode:path,
+    ) => {
+        #[cfg(feature = $feature)]
+        impl Client {
+            #[doc = concat!("SET of the ", $format, " encoding of `value`.")]
+            pub async fn $set<T: Serialize + ?Sized>(
+                &mut self,
+                key: &str,
+                value: &T,
+            ) -> Result<(), Error> {
+                let value = $encode(value).map_err(|e| encode_error($format, key, e))?;
+                self.set(key, Bytes::from(value)).await.map(|_| ())
+            }
+
+            #[doc = concat!("Same as `", stringify!($set), "` with options such as an expiry.")]
+            #[doc = "Returns false if the key was not set because of NX or XX."]
+            pub async fn $set_with_options<T: Serialize + ?Sized>(
+                &mut self,
+                key: &str,
+                value: &T,
+                options: SetOptions,
+            ) -> Result<bool, Error> {
+                let value = $encode(value).map_err(|e| encode_error($format, key, e))?;
+                self.set_with_options(key, Bytes::from(value), options)
+                    .await
+            }
+
+            #[doc = concat!("GET of a value stored by `", stringify!($set), "`. A value")]
+            #[doc = "which cannot be decoded into `T` fails with `Error::Serialization`."]
+            pub async fn $get<T: DeserializeOwned>(
+                &mut self,
+                key: &str,
+            ) -> Result<Option<T>, Error> {
+                match self.get(key).await? {
+                    Some(value) => $decode(&value)
+                        .map(Some)
+                        .map_err(|e| decode_error($format, key, e)),
+                    None => Ok(None),
+                }
+            }
+        }
+    };
+}
+
+encoded_methods! {
+    feature: "json",
+    format: "JSON",
+    set: set_json,
+    set_with_options: set_json_with_options,
+    get: get_json,
+    encode: serde_json::to_vec,
+    decode: serde_json::from_slice,
+}
+
+encoded_methods! {
+    feature: "bincode",
+    format: "bincode",
+    set: set_bincode,
+    set_with_options: set_bincode_with_options,
+    get: get_bincode,
+    encode: bincode::serialize,
+    decode: bincode::deserialize,
+}
+
+// Structs are encoded as maps so that fields can be added or reordered
+encoded_methods! {
+    feature: "msgpack",
+    format: "MessagePack",
+    set: set_msgpack,
+    set_with_options: set_msgpack_with_options,
+    get: get_msgpack,
+    encode: rmp_serde::to_vec_named,
+    decode: rmp_serde::from_slice,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{cmd, resp::Resp, testing::MockServer};
+    use serde::Deserialize;
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Session {
+        user: String,
+        visits: u32,
+    }
+
+    fn session() -> Session {
+        Session {
+            user: "alice".to_string(),
+            visits: 3,
+        }
+    }
+
+    #[cfg(feature = "json")]
+    #[tokio::test]
+    async fn json() {
+        let server = MockServer::start().await;
+        server
+            .expect(cmd!["SET", "s", r#"{"user":"alice","visits":3}"#])
+            .reply(Resp::SimpleString("OK".into()));
+        server
+            .expect(cmd!["GET", "s"])
+            .reply(Resp::BulkString(r#"{"user":"alice","visits":3}"#.into()));
+        server
+            .expect(cmd!["GET", "t"])
+            .reply(Resp::BulkString("not json".into()));
+        server.expect(cmd!["GET", "u"]).reply(Resp::Null);
+
+        let mut client = crate::client::connect(server.addr()).await.unwrap();
+        client.set_json("s", &session()).await.unwrap();
+        assert_eq!(Some(session()), client.get_json("s").await.unwrap());
+        match client.get_json::<Session>("t").await {
+            Err(Error::Serialization(msg)) => {
+                assert!(msg.starts_with(r#"failed to decode the JSON value of "t""#))
+            }
+            others => panic!("unexpected result {:?}", others),
+        }
+        assert_eq!(None, client.get_json::<Session>("u").await.unwrap());
+        server.assert_done();
+    }
+
+    #[cfg(feature = "bincode")]
+    #[tokio::test]
+    async fn bincode() {
+        let encoded = bincode::serialize(&session()).unwrap();
+        let server = MockServer::start().await;
+        server
+            .expect(cmd!["SET", "s", &encoded])
+            .reply(Resp::SimpleString("OK".into()));
+        server
+            .expect(cmd!["GET", "s"])
+            .reply(Resp::BulkString(encoded.into()));
+
+        let mut client = crate::client::connect(server.addr()).await.unwrap();
+        client.set_bincode("s", &session()).await.unwrap();
+        assert_eq!(Some(session()), client.get_bincode("s").await.unwrap());
+        server.assert_done();
+    }
+
+    #[cfg(feature = "msgpack")]
+    #[tokio::test]
+    async fn msgpack() {
+        let encoded = rmp_serde::to_vec_named(&session()).unwrap();
+        let server = MockServer::start().await;
+        server
+            .expect(cmd!["SET", "s", &encoded])
+            .reply(Resp::SimpleString("OK".into()));
+        server
+            .expect(cmd!["GET", "s"])
+            .reply(Resp::BulkString(encoded.into()));
+        server
+            .expect(cmd!["GET", "t"])
+            .reply(Resp::BulkString(Bytes::from_static(b"\xc1")));
+
+        let mut client = crate::client::connect(server.addr()).await.unwrap();
+        client.set_msgpack("s", &session()).await.unwrap();
+        assert_eq!(Some(session()), client.get_msgpack("s").await.unwrap());
+        assert!(matches!(
+            client.get_msgpack::<Session>("t").await,
+            Err(Error::Serialization(_))
+        ));
+        server.assert_done();
+    }
+}
@@ -0,0 +1,138 @@
+use super::Client;
+use crate::{
+    command::{self, FlushMode},
+    error::Error,
+    functions::{LibraryInfo, RestorePolicy},
+    resp::{FromResp, Resp},
+};
+use bytes::Bytes;
+
+impl Client {
+    /// Loads a library and returns its name. With `replace`, an existing
+    /// library with the same name is replaced.
+    pub async fn function_load(&mut self, code: &str, replace: bool) -> Result<String, Error> {
+        let load = Resp::from(command::FunctionLoad::new(code, replace));
+        self.request(load).await?.into_string()
+    }
+
+    pub async fn function_list(
+        &mut self,
+        pattern: Option<&str>,
+        withcode: bool,
+    ) -> Result<Vec<LibraryInfo>, Error> {
+        let list = Resp::from(command::FunctionList::new(pattern, withcode));
+        self.request(list)
+            .await?
+            .into_array()?
+            .into_iter()
+            .map(LibraryInfo::from_resp)
+            .collect()
+    }
+
+    pub async fn function_delete(&mut self, library: &str) -> Result<(), Error> {
+        let delete = Resp::from(command::FunctionDelete::new(library));
+        self.request(delete).await?.into_ok()
+    }
+
+    /// Returns a serialized payload of all libraries, to be passed to `function_restore`.
+    pub async fn function_dump(&mut self) -> Result<Bytes, Error> {
+        let dump = Resp::from(command::FunctionDump::new());
+        self.request(dump).await?.into_bulk()
+    }
+
+    pub async fn function_restore(
+        &mut self,
+        payload: Bytes,
+        policy: Option<RestorePolicy>,
+    ) -> Result<(), Error> {
+        let restore = Resp::from(command::FunctionRestore::new(payload, policy));
+        self.request(restore).await?.into_ok()
+    }
+
+    pub async fn function_flush(&mut self, mode: Option<FlushMode>) -> Result<(), Error> {
+        let flush = Resp::from(command::FunctionFlush::new(mode));
+        self.request(flush).await?.into_ok()
+    }
+
+    /// Calls a function and converts its reply into `T`.
+    pub async fn fcall<T: FromResp>(
+        &mut self,
+        function: &str,
+        keys: &[&str],
+        args: &[Bytes],
+    ) -> Result<T, Error> {
+        let fcall = Resp::from(command::FCall::new(function, keys, args, false));
+        T::from_resp(self.request(fcall).await?)
+    }
+
+    /// Same as `fcall` for functions flagged `no-writes`, which can run on replicas.
+    pub async fn fcall_ro<T: FromResp>(
+        &mut self,
+        function: &str,
+        keys: &[&str],
+        args: &[Bytes],
+    ) -> Result<T, Error> {
+        let fcall = Resp::from(command::FCall::new(function, keys, args, true));
+        T::from_resp(self.request(fcall).await?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{client, cmd, testing::bulk, testing::MockServer};
+
+    #[tokio::test]
+    async fn function_list() {
+        let server = MockServer::start().await;
+        server
+            .expect(cmd!["FUNCTION", "LIST", "LIBRARYNAME", "my*", "WITHCODE"])
+            .reply(Resp::Array(vec![Resp::Array(vec![
+                bulk("library_name"),
+                bulk("mylib"),
+                bulk("engine"),
+                bulk("LUA"),
+                bulk("functions"),
+                Resp::Array(vec![]),
+                bulk("library_code"),
+                bulk("#!lua name=mylib"),
+            ])]));
+
+        let mut client = client::connect(server.addr()).await.unwrap();
+        let libraries = client.function_list(Some("my*"), true).await.unwrap();
+        assert_eq!(1, libraries.len());
+        assert_eq!("mylib", libraries[0].name);
+        assert_eq!(Some("#!lua name=mylib"), libraries[0].code.as_deref());
+        server.assert_done();
+    }
+
+    #[tokio::test]
+    async fn fcall_arguments_and_replies() {
+        let server = MockServer::start().await;
+        server
+            .expect(cmd!["FCALL", "incr_by", "2", "a", "b", "10"])
+            .reply(Resp::Integer(12));
+        server
+            .expect(cmd!["FCALL_RO", "get_all", "1", "a"])
+            .reply(Resp::Array(vec![
+                Resp::BulkString(Bytes::from("x")),
+                Resp::Null,
+            ]));
+        server
+            .expect(cmd!["FCALL_RO", "get_all", "0"])
+            .reply(Resp::SimpleString("OK".into()));
+
+        let mut client = client::connect(server.addr()).await.unwrap();
+        let args = [Bytes::from("10")];
+        let total: i64 = client.fcall("incr_by", &["a", "b"], &args).await.unwrap();
+        assert_eq!(12, total);
+
+        let values: Vec<Option<String>> = client.fcall_ro("get_all", &["a"], &[]).await.unwrap();
+        assert_eq!(vec![Some("x".to_string()), None], values);
+
+        // a reply which cannot be converted into the requested type
+        let result: Result<i64, Error> = client.fcall_ro("get_all", &[], &[]).await;
+        assert!(result.is_err());
+        server.assert_done();
+    }
+}
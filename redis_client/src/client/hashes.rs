@@ -0,0 +1,256 @@
+use super::Client;
+use crate::{command, error::Error, resp::Resp, scan};
+use bytes::Bytes;
+use futures_core::Stream;
+use std::collections::HashMap;
+
+impl Client {
+    /// Returns the number of fields that were added.
+    pub async fn hset(&mut self, key: &str, pairs: &[(&str, Bytes)]) -> Result<u64, Error> {
+        let hset = Resp::from(command::HSet::new(key, pairs));
+        self.request(hset).await?.into_integer().map(|v| v as u64)
+    }
+
+    pub async fn hsetnx(&mut self, key: &str, field: &str, value: Bytes) -> Result<bool, Error> {
+        let hsetnx = Resp::from(command::HSet::new_nx(key, field, value));
+        self.request(hsetnx).await?.into_bool()
+    }
+
+    pub async fn hget(&mut self, key: &str, field: &str) -> Result<Option<Bytes>, Error> {
+        let hget = Resp::from(command::HGet::new(key, field));
+        self.cached_request(hget).await?.into_bytes()
+    }
+
+    pub async fn hmget(&mut self, key: &str, fields: &[&str]) -> Result<Vec<Option<Bytes>>, Error> {
+        let hmget = Resp::from(command::HMGet::new(key, fields));
+        self.request(hmget)
+            .await?
+            .into_array()?
+            .into_iter()
+            .map(Resp::into_bytes)
+            .collect()
+    }
+
+    /// Field names must be UTF-8 as with `hset`: a single other field name
+    /// fails the whole reply with `Error::Invalid`.
+    pub async fn hgetall(&mut self, key: &str) -> Result<HashMap<String, Bytes>, Error> {
+        let hgetall = Resp::from(command::HashKey::getall(key));
+        let pairs = self.cached_request(hgetall).await?.into_pairs()?;
+        Ok(pairs.into_iter().collect())
+    }
+
+    /// Returns the number of fields that were removed.
+    pub async fn hdel(&mut self, key: &str, fields: &[&str]) -> Result<u64, Error> {
+        let hdel = Resp::from(command::HDel::new(key, fields));
+        self.request(hdel).await?.into_integer().map(|v| v as u64)
+    }
+
+    pub async fn hexists(&mut self, key: &str, field: &str) -> Result<bool, Error> {
+        let hexists = Resp::from(command::HExists::new(key, field));
+        self.request(hexists).await?.into_bool()
+    }
+
+    pub async fn hincrby(&mut self, key: &str, field: &str, increment: i64) -> Result<i64, Error> {
+        let hincrby = Resp::from(command::HIncrBy::new(key, field, increment));
+        self.request(hincrby).await?.into_integer()
+    }
+
+    pub async fn hincrbyfloat(
+        &mut self,
+        key: &str,
+        field: &str,
+        increment: f64,
+    ) -> Result<f64, Error> {
+        let hincrbyfloat = Resp::from(command::HIncrByFloat::new(key, field, increment));
+        self.request(hincrbyfloat).await?.into_float()
+    }
+
+    /// Field names must be UTF-8, see `hgetall`.
+    pub async fn hkeys(&mut self, key: &str) -> Result<Vec<String>, Error> {
+        let hkeys = Resp::from(command::HashKey::keys(key));
+        self.request(hkeys)
+            .await?
+            .into_array()?
+            .into_iter()
+            .map(Resp::into_string)
+            .collect()
+    }
+
+    pub async fn hvals(&mut self, key: &str) -> Result<Vec<Bytes>, Error> {
+        let hvals = Resp::from(command::HashKey::vals(key));
+        self.request(hvals)
+            .await?
+            .into_array()?
+            .into_iter()
+            .map(Resp::into_bulk)
+            .collect()
+    }
+
+    pub async fn hlen(&mut self, key: &str) -> Result<u64, Error> {
+        let hlen = Resp::from(command::HashKey::len(key));
+        self.request(hlen).await?.into_integer().map(|v| v as u64)
+    }
+
+    /// Returns a random field, or None if the key does not exist.
+    pub async fn hrandfield(&mut self, key: &str) -> Result<Option<String>, Error> {
+        let hrandfield = Resp::from(command::HRandField::new(key, None, false));
+        match self.request(hrandfield).await? {
+            Resp::Null => Ok(None),
+            resp => resp.into_string().map(Some),
+        }
+    }
+
+    /// Returns up to `count` distinct fields, or exactly `count.abs()` fields
+    /// allowing repetition if `count` is negative.
+    pub async fn hrandfield_count(&mut self, key: &str, count: i64) -> Result<Vec<String>, Error> {
+        let hrandfield = Resp::from(command::HRandField::new(key, Some(count), false));
+        self.request(hrandfield)
+            .await?
+            .into_array()?
+            .into_iter()
+            .map(Resp::into_string)
+            .collect()
+    }
+
+    /// Same as `hrandfield_count` but returns fields with their values.
+    pub async fn hrandfield_withvalues(
+        &mut self,
+        key: &str,
+        count: i64,
+    ) -> Result<Vec<(String, Bytes)>, Error> {
+        let hrandfield = Resp::from(command::HRandField::new(key, Some(count), true));
+        self.request(hrandfield).await?.into_pairs()
+    }
+
+    /// Iterates over fields and values of a hash with HSCAN until the cursor wraps to 0.
+    /// Field names must be UTF-8, see `hgetall`.
+    pub fn hscan<'a>(
+        &'a mut self,
+        key: &str,
+        pattern: Option<&str>,
+        count: Option<u64>,
+    ) -> impl Stream<Item = Result<(String, Bytes), Error>> + 'a {
+        let hscan = command::Scan::hscan(key, pattern, count);
+        scan::stream(self, hscan, |elements| Resp::Array(elements).into_pairs())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{client, cmd, testing::bulk, testing::MockServer};
+    use futures_util::StreamExt;
+
+    #[tokio::test]
+    async fn hash_commands() {
+        let server = MockServer::start().await;
+        server
+            .expect(cmd!["HSET", "h", "a", "1", "b", "2"])
+            .reply(Resp::Integer(2));
+        server
+            .expect(cmd!["HSETNX", "h", "a", "3"])
+            .reply(Resp::Integer(0));
+        server
+            .expect(cmd!["HMGET", "h", "a", "c"])
+            .reply(Resp::Array(vec![bulk("1"), Resp::Null]));
+        server.expect(cmd!["HGETALL", "h"]).reply(Resp::Array(vec![
+            bulk("a"),
+            bulk("1"),
+            bulk("b"),
+            bulk("2"),
+        ]));
+        server
+            .expect(cmd!["HINCRBY", "h", "a", "-2"])
+            .reply(Resp::Integer(-1));
+        server
+            .expect(cmd!["HINCRBYFLOAT", "h", "b", "0.5"])
+            .reply(bulk("2.5"));
+        server
+            .expect(cmd!["HDEL", "h", "a", "c"])
+            .reply(Resp::Integer(1));
+        server.expect(cmd!["HLEN", "h"]).reply(Resp::Integer(1));
+        server
+            .expect(cmd!["HRANDFIELD", "h", "-2", "WITHVALUES"])
+            .reply(Resp::Array(vec![
+                bulk("b"),
+                bulk("2.5"),
+                bulk("b"),
+                bulk("2.5"),
+            ]));
+        server.expect(cmd!["HRANDFIELD", "x"]).reply(Resp::Null);
+
+        let mut client = client::connect(server.addr()).await.unwrap();
+        let pairs = [("a", Bytes::from("1")), ("b", Bytes::from("2"))];
+        assert_eq!(2, client.hset("h", &pairs).await.unwrap());
+        assert!(!client.hsetnx("h", "a", Bytes::from("3")).await.unwrap());
+        assert_eq!(
+            vec![Some(Bytes::from("1")), None],
+            client.hmget("h", &["a", "c"]).await.unwrap()
+        );
+        assert_eq!(
+            HashMap::from([
+                ("a".to_string(), Bytes::from("1")),
+                ("b".to_string(), Bytes::from("2")),
+            ]),
+            client.hgetall("h").await.unwrap()
+        );
+        assert_eq!(-1, client.hincrby("h", "a", -2).await.unwrap());
+        assert_eq!(2.5, client.hincrbyfloat("h", "b", 0.5).await.unwrap());
+        assert_eq!(1, client.hdel("h", &["a", "c"]).await.unwrap());
+        assert_eq!(1, client.hlen("h").await.unwrap());
+        assert_eq!(
+            vec![
+                ("b".to_string(), Bytes::from("2.5")),
+                ("b".to_string(), Bytes::from("2.5")),
+            ],
+            client.hrandfield_withvalues("h", -2).await.unwrap()
+        );
+        assert_eq!(None, client.hrandfield("x").await.unwrap());
+        server.assert_done();
+    }
+
+    #[tokio::test]
+    async fn hgetall_binary_field() {
+        let server = MockServer::start().await;
+        server
+            .expect(cmd!["HGETALL", "h"])
+            .reply(Resp::Array(vec![bulk(b"\xff"), bulk("1")]));
+
+        let mut client = client::connect(server.addr()).await.unwrap();
+        assert!(matches!(client.hgetall("h").await, Err(Error::Invalid(_))));
+        server.assert_done();
+    }
+
+    #[tokio::test]
+    async fn hscan_stream() {
+        let server = MockServer::start().await;
+        server
+            .expect(cmd!["HSCAN", "h", "0", "MATCH", "f*", "COUNT", "2"])
+            .reply(Resp::Array(vec![
+                bulk("5"),
+                Resp::Array(vec![bulk("f1"), bulk("1"), bulk("f2"), bulk("2")]),
+            ]));
+        server
+            .expect(cmd!["HSCAN", "h", "5", "MATCH", "f*", "COUNT", "2"])
+            .reply(Resp::Array(vec![
+                bulk("0"),
+                Resp::Array(vec![bulk("f3"), bulk("3")]),
+            ]));
+
+        let mut client = client::connect(server.addr()).await.unwrap();
+        let pairs: Vec<_> = client
+            .hscan("h", Some("f*"), Some(2))
+            .map(|v| v.unwrap())
+            .collect()
+            .await;
+        assert_eq!(
+            vec![
+                ("f1".to_string(), Bytes::from("1")),
+                ("f2".to_string(), Bytes::from("2")),
+                ("f3".to_string(), Bytes::from("3")),
+            ],
+            pairs
+        );
+        server.assert_done();
+    }
+}
@@ -0,0 +1,154 @@
+use super::Client;
+use crate::{command, error::Error, resp::Resp, scan};
+use bytes::Bytes;
+use futures_core::Stream;
+
+impl Client {
+    pub async fn del(&mut self, keys: &[&str]) -> Result<u64, Error> {
+        let del = Resp::from(command::Del::new(keys));
+        self.request(del).await?.into_integer().map(|v| v as u64)
+    }
+
+    pub async fn unlink(&mut self, keys: &[&str]) -> Result<u64, Error> {
+        let unlink = Resp::from(command::Unlink::new(keys));
+        self.request(unlink).await?.into_integer().map(|v| v as u64)
+    }
+
+    pub async fn exists(&mut self, keys: &[&str]) -> Result<u64, Error> {
+        let exists = Resp::from(command::Exists::new(keys));
+        self.request(exists).await?.into_integer().map(|v| v as u64)
+    }
+
+    /// Returns false if the key does not exist or the condition was not met.
+    pub async fn expire(
+        &mut self,
+        key: &str,
+        seconds: u64,
+        condition: Option<command::ExpireCondition>,
+    ) -> Result<bool, Error> {
+        let expire = Resp::from(command::Expire::new(key, seconds, condition));
+        self.request(expire).await?.into_bool()
+    }
+
+    /// Returns false if the key does not exist or the condition was not met.
+    pub async fn pexpire(
+        &mut self,
+        key: &str,
+        millis: u64,
+        condition: Option<command::ExpireCondition>,
+    ) -> Result<bool, Error> {
+        let pexpire = Resp::from(command::Expire::new_millis(key, millis, condition));
+        self.request(pexpire).await?.into_bool()
+    }
+
+    /// Returns the remaining time to live in seconds, -1 if the key has no
+    /// expiry, or -2 if the key does not exist.
+    pub async fn ttl(&mut self, key: &str) -> Result<i64, Error> {
+        let ttl = Resp::from(command::Ttl::new(key));
+        self.request(ttl).await?.into_integer()
+    }
+
+    /// Same as `ttl` but in milliseconds.
+    pub async fn pttl(&mut self, key: &str) -> Result<i64, Error> {
+        let pttl = Resp::from(command::Ttl::new_millis(key));
+        self.request(pttl).await?.into_integer()
+    }
+
+    pub async fn persist(&mut self, key: &str) -> Result<bool, Error> {
+        let persist = Resp::from(command::Persist::new(key));
+        self.request(persist).await?.into_bool()
+    }
+
+    /// Returns the type name of the value stored at key ("none" if it does not exist).
+    pub async fn key_type(&mut self, key: &str) -> Result<String, Error> {
+        let typ = Resp::from(command::Type::new(key));
+        self.request(typ).await?.into_string()
+    }
+
+    pub async fn rename(&mut self, key: &str, newkey: &str) -> Result<(), Error> {
+        let rename = Resp::from(command::Rename::new(key, newkey, false));
+        self.request(rename).await?.into_ok()
+    }
+
+    /// Returns false if newkey already exists.
+    pub async fn renamenx(&mut self, key: &str, newkey: &str) -> Result<bool, Error> {
+        let renamenx = Resp::from(command::Rename::new(key, newkey, true));
+        self.request(renamenx).await?.into_bool()
+    }
+
+    pub async fn copy(
+        &mut self,
+        source: &str,
+        destination: &str,
+        db: Option<u64>,
+        replace: bool,
+    ) -> Result<bool, Error> {
+        let copy = Resp::from(command::Copy::new(source, destination, db, replace));
+        self.request(copy).await?.into_bool()
+    }
+
+    pub async fn touch(&mut self, keys: &[&str]) -> Result<u64, Error> {
+        let touch = Resp::from(command::Touch::new(keys));
+        self.request(touch).await?.into_integer().map(|v| v as u64)
+    }
+
+    /// Iterates over the keyspace with SCAN until the cursor wraps to 0.
+    /// As with SCAN itself, a key may be returned more than once.
+    pub fn scan<'a>(
+        &'a mut self,
+        pattern: Option<&str>,
+        count: Option<u64>,
+        typ: Option<&str>,
+    ) -> impl Stream<Item = Result<Bytes, Error>> + 'a {
+        let scan = command::Scan::new(pattern, count, typ);
+        scan::stream(self, scan, |elements| {
+            elements.into_iter().map(Resp::into_bulk).collect()
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{client, cmd, testing::MockServer};
+
+    #[tokio::test]
+    async fn replies() {
+        let server = MockServer::start().await;
+        server.expect(cmd!["DEL", "a", "b"]).reply(Resp::Integer(1));
+        server
+            .expect(cmd!["EXPIRE", "a", "10", "NX"])
+            .reply(Resp::Integer(0));
+        server.expect(cmd!["PTTL", "a"]).reply(Resp::Integer(-2));
+        server
+            .expect(cmd!["TYPE", "a"])
+            .reply(Resp::SimpleString("none".into()));
+        server
+            .expect(cmd!["RENAME", "a", "b"])
+            .reply_error("ERR no such key");
+        server
+            .expect(cmd!["RENAMENX", "a", "b"])
+            .reply(Resp::Integer(1));
+        server
+            .expect(cmd!["COPY", "a", "b", "DB", "1", "REPLACE"])
+            .reply(Resp::Integer(1));
+        server.expect(cmd!["EXISTS", "a"]).reply(Resp::Null);
+
+        let mut client = client::connect(server.addr()).await.unwrap();
+        assert_eq!(1, client.del(&["a", "b"]).await.unwrap());
+        assert!(!client
+            .expire("a", 10, Some(command::ExpireCondition::Nx))
+            .await
+            .unwrap());
+        assert_eq!(-2, client.pttl("a").await.unwrap());
+        assert_eq!("none", client.key_type("a").await.unwrap());
+        assert!(matches!(
+            client.rename("a", "b").await,
+            Err(Error::Generic { code, .. }) if code == "ERR"
+        ));
+        assert!(client.renamenx("a", "b").await.unwrap());
+        assert!(client.copy("a", "b", Some(1), true).await.unwrap());
+        assert!(client.exists(&["a"]).await.is_err());
+        server.assert_done();
+    }
+}
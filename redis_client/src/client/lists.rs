@@ -0,0 +1,275 @@
+use super::Client;
+use crate::{
+    command::{self, Direction, InsertPosition},
+    error::Error,
+    resp::Resp,
+};
+use bytes::Bytes;
+use std::time::Duration;
+
+impl Client {
+    /// Returns the length of the list after the push.
+    pub async fn lpush(&mut self, key: &str, values: &[Bytes]) -> Result<u64, Error> {
+        let lpush = Resp::from(command::Push::new(Direction::Left, key, values));
+        self.request(lpush).await?.into_integer().map(|v| v as u64)
+    }
+
+    /// Returns the length of the list after the push.
+    pub async fn rpush(&mut self, key: &str, values: &[Bytes]) -> Result<u64, Error> {
+        let rpush = Resp::from(command::Push::new(Direction::Right, key, values));
+        self.request(rpush).await?.into_integer().map(|v| v as u64)
+    }
+
+    pub async fn lpop(&mut self, key: &str) -> Result<Option<Bytes>, Error> {
+        let lpop = Resp::from(command::Pop::new(Direction::Left, key, None));
+        self.request(lpop).await?.into_bytes()
+    }
+
+    pub async fn rpop(&mut self, key: &str) -> Result<Option<Bytes>, Error> {
+        let rpop = Resp::from(command::Pop::new(Direction::Right, key, None));
+        self.request(rpop).await?.into_bytes()
+    }
+
+    /// Pops up to `count` elements. Returns None if the key does not exist.
+    pub async fn lpop_count(&mut self, key: &str, count: u64) -> Result<Option<Vec<Bytes>>, Error> {
+        let lpop = Resp::from(command::Pop::new(Direction::Left, key, Some(count)));
+        into_optional_bulks(self.request(lpop).await?)
+    }
+
+    /// Pops up to `count` elements. Returns None if the key does not exist.
+    pub async fn rpop_count(&mut self, key: &str, count: u64) -> Result<Option<Vec<Bytes>>, Error> {
+        let rpop = Resp::from(command::Pop::new(Direction::Right, key, Some(count)));
+        into_optional_bulks(self.request(rpop).await?)
+    }
+
+    pub async fn lrange(&mut self, key: &str, start: i64, stop: i64) -> Result<Vec<Bytes>, Error> {
+        let lrange = Resp::from(command::ListRange::range(key, start, stop));
+        self.cached_request(lrange)
+            .await?
+            .into_array()?
+            .into_iter()
+            .map(Resp::into_bulk)
+            .collect()
+    }
+
+    pub async fn llen(&mut self, key: &str) -> Result<u64, Error> {
+        let llen = Resp::from(command::LLen::new(key));
+        self.request(llen).await?.into_integer().map(|v| v as u64)
+    }
+
+    pub async fn lindex(&mut self, key: &str, index: i64) -> Result<Option<Bytes>, Error> {
+        let lindex = Resp::from(command::LIndex::new(key, index));
+        self.request(lindex).await?.into_bytes()
+    }
+
+    pub async fn lset(&mut self, key: &str, index: i64, value: Bytes) -> Result<(), Error> {
+        let lset = Resp::from(command::LSet::new(key, index, value));
+        self.request(lset).await?.into_ok()
+    }
+
+    /// Returns the number of removed elements.
+    pub async fn lrem(&mut self, key: &str, count: i64, value: Bytes) -> Result<u64, Error> {
+        let lrem = Resp::from(command::LRem::new(key, count, value));
+        self.request(lrem).await?.into_integer().map(|v| v as u64)
+    }
+
+    pub async fn ltrim(&mut self, key: &str, start: i64, stop: i64) -> Result<(), Error> {
+        let ltrim = Resp::from(command::ListRange::trim(key, start, stop));
+        self.request(ltrim).await?.into_ok()
+    }
+
+    /// Returns the length of the list after the insert, -1 if the pivot was
+    /// not found, or 0 if the key does not exist.
+    pub async fn linsert(
+        &mut self,
+        key: &str,
+        position: InsertPosition,
+        pivot: Bytes,
+        value: Bytes,
+    ) -> Result<i64, Error> {
+        let linsert = Resp::from(command::LInsert::new(key, position, pivot, value));
+        self.request(linsert).await?.into_integer()
+    }
+
+    pub async fn lpos(
+        &mut self,
+        key: &str,
+        element: Bytes,
+        rank: Option<i64>,
+        maxlen: Option<u64>,
+    ) -> Result<Option<u64>, Error> {
+        let lpos = Resp::from(command::LPos::new(key, element, rank, None, maxlen));
+        match self.request(lpos).await? {
+            Resp::Null => Ok(None),
+            resp => resp.into_integer().map(|v| Some(v as u64)),
+        }
+    }
+
+    /// Returns positions of up to `count` matching elements (0 means all of them).
+    pub async fn lpos_count(
+        &mut self,
+        key: &str,
+        element: Bytes,
+        count: u64,
+        rank: Option<i64>,
+        maxlen: Option<u64>,
+    ) -> Result<Vec<u64>, Error> {
+        let lpos = Resp::from(command::LPos::new(key, element, rank, Some(count), maxlen));
+        self.request(lpos)
+            .await?
+            .into_array()?
+            .into_iter()
+            .map(|v| v.into_integer().map(|v| v as u64))
+            .collect()
+    }
+
+    pub async fn lmove(
+        &mut self,
+        source: &str,
+        destination: &str,
+        wherefrom: Direction,
+        whereto: Direction,
+    ) -> Result<Option<Bytes>, Error> {
+        let lmove = Resp::from(command::LMove::new(
+            source,
+            destination,
+            wherefrom,
+            whereto,
+            None,
+        ));
+        self.request(lmove).await?.into_bytes()
+    }
+
+    /// Blocks until an element is available on one of the keys. Returns the key
+    /// and the popped element, or None if `timeout` expired on the server.
+    /// A zero `timeout` blocks forever.
+    pub async fn blpop(
+        &mut self,
+        keys: &[&str],
+        timeout: Duration,
+    ) -> Result<Option<(String, Bytes)>, Error> {
+        let blpop = Resp::from(command::BPop::new(Direction::Left, keys, timeout));
+        into_key_value(self.request_blocking(blpop, timeout).await?)
+    }
+
+    /// Same as `blpop` but pops from the tail.
+    pub async fn brpop(
+        &mut self,
+        keys: &[&str],
+        timeout: Duration,
+    ) -> Result<Option<(String, Bytes)>, Error> {
+        let brpop = Resp::from(command::BPop::new(Direction::Right, keys, timeout));
+        into_key_value(self.request_blocking(brpop, timeout).await?)
+    }
+
+    /// Blocking variant of `lmove`. Returns None if `timeout` expired on the server.
+    pub async fn blmove(
+        &mut self,
+        source: &str,
+        destination: &str,
+        wherefrom: Direction,
+        whereto: Direction,
+        timeout: Duration,
+    ) -> Result<Option<Bytes>, Error> {
+        let blmove = Resp::from(command::LMove::new(
+            source,
+            destination,
+            wherefrom,
+            whereto,
+            Some(timeout),
+        ));
+        self.request_blocking(blmove, timeout).await?.into_bytes()
+    }
+
+    /// Pops up to `count` elements from the first non-empty list of `keys`.
+    /// Returns the key and the popped elements, or None if `timeout` expired on the server.
+    pub async fn blmpop(
+        &mut self,
+        keys: &[&str],
+        direction: Direction,
+        count: Option<u64>,
+        timeout: Duration,
+    ) -> Result<Option<(String, Vec<Bytes>)>, Error> {
+        let blmpop = Resp::from(command::BLMPop::new(keys, direction, count, timeout));
+        match self.request_blocking(blmpop, timeout).await? {
+            Resp::Null => Ok(None),
+            resp => match resp.into_array()?.as_mut_slice() {
+                [key, values] => {
+                    let key = std::mem::replace(key, Resp::Null).into_string()?;
+                    let values = into_optional_bulks(std::mem::replace(values, Resp::Null))?;
+                    Ok(Some((key, values.unwrap_or_default())))
+                }
+                others => Err(Error::Invalid(format!(
+                    "invalid blmpop response {:?}",
+                    others
+                ))),
+            },
+        }
+    }
+}
+
+fn into_optional_bulks(resp: Resp) -> Result<Option<Vec<Bytes>>, Error> {
+    match resp {
+        Resp::Null => Ok(None),
+        resp => resp
+            .into_array()?
+            .into_iter()
+            .map(Resp::into_bulk)
+            .collect::<Result<_, _>>()
+            .map(Some),
+    }
+}
+
+// [key, value] or nil (on timeout)
+fn into_key_value(resp: Resp) -> Result<Option<(String, Bytes)>, Error> {
+    match resp {
+        Resp::Null => Ok(None),
+        resp => match resp.into_pairs()?.pop() {
+            Some(pair) => Ok(Some(pair)),
+            None => Err(Error::Invalid("empty response".to_string())),
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{client, cmd, testing::bulk, testing::MockServer};
+
+    #[tokio::test]
+    async fn blocking_pops() {
+        let server = MockServer::start().await;
+        server
+            .expect(cmd!["BLPOP", "a", "b", "0.1"])
+            .reply(Resp::Null);
+        server
+            .expect(cmd!["BLPOP", "a", "b", "0.1"])
+            .reply(Resp::Array(vec![bulk("b"), bulk("x")]));
+        server
+            .expect(cmd!["BLMPOP", "0.1", "1", "a", "LEFT", "COUNT", "2"])
+            .reply(Resp::Null);
+        server
+            .expect(cmd!["BLMPOP", "0.1", "1", "a", "LEFT", "COUNT", "2"])
+            .reply(Resp::Array(vec![
+                bulk("a"),
+                Resp::Array(vec![bulk("x"), bulk("y")]),
+            ]));
+
+        let mut client = client::connect(server.addr()).await.unwrap();
+        let timeout = Duration::from_millis(100);
+        assert_eq!(None, client.blpop(&["a", "b"], timeout).await.unwrap());
+        assert_eq!(
+            Some(("b".to_string(), Bytes::from("x"))),
+            client.blpop(&["a", "b"], timeout).await.unwrap()
+        );
+
+        let blmpop = client.blmpop(&["a"], Direction::Left, Some(2), timeout);
+        assert_eq!(None, blmpop.await.unwrap());
+        let blmpop = client.blmpop(&["a"], Direction::Left, Some(2), timeout);
+        assert_eq!(
+            Some(("a".to_string(), vec![Bytes::from("x"), Bytes::from("y")])),
+            blmpop.await.unwrap()
+        );
+        server.assert_done();
+    }
+}
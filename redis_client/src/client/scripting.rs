@@ -0,0 +1,51 @@
+use super::Client;
+use crate::{
+    command::{self, FlushMode},
+    error::Error,
+    resp::{FromResp, Resp},
+};
+use bytes::Bytes;
+
+impl Client {
+    /// Runs a script and converts its reply into `T`.
+    pub async fn eval<T: FromResp>(
+        &mut self,
+        script: &str,
+        keys: &[&str],
+        args: &[Bytes],
+    ) -> Result<T, Error> {
+        let eval = Resp::from(command::Eval::new(script, keys, args));
+        T::from_resp(self.request(eval).await?)
+    }
+
+    pub async fn evalsha<T: FromResp>(
+        &mut self,
+        sha: &str,
+        keys: &[&str],
+        args: &[Bytes],
+    ) -> Result<T, Error> {
+        let evalsha = Resp::from(command::Eval::new_sha(sha, keys, args));
+        T::from_resp(self.request(evalsha).await?)
+    }
+
+    /// Returns the SHA1 digest of the loaded script.
+    pub async fn script_load(&mut self, script: &str) -> Result<String, Error> {
+        let load = Resp::from(command::ScriptLoad::new(script));
+        self.request(load).await?.into_string()
+    }
+
+    pub async fn script_exists(&mut self, shas: &[&str]) -> Result<Vec<bool>, Error> {
+        let exists = Resp::from(command::ScriptExists::new(shas));
+        self.request(exists)
+            .await?
+            .into_array()?
+            .into_iter()
+            .map(Resp::into_bool)
+            .collect()
+    }
+
+    pub async fn script_flush(&mut self, mode: Option<FlushMode>) -> Result<(), Error> {
+        let flush = Resp::from(command::ScriptFlush::new(mode));
+        self.request(flush).await?.into_ok()
+    }
+}
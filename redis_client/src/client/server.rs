@@ -0,0 +1,147 @@
+use super::Client;
+use crate::{
+    command,
+    diagnostics::{Info, LatencyEvent, LatencySample, MemoryStats, SlowlogEntry},
+    error::Error,
+    monitor::MonitorEntry,
+    resp::Resp,
+};
+use futures_core::Stream;
+use futures_util::stream;
+use std::collections::HashMap;
+
+impl Client {
+    /// Returns configuration parameters matching `parameter` (glob-style) with their values.
+    pub async fn config_get(&mut self, parameter: &str) -> Result<HashMap<String, String>, Error> {
+        let get = Resp::from(command::ConfigGet::new(parameter));
+        self.request(get)
+            .await?
+            .into_fields()?
+            .into_iter()
+            .map(|(name, value)| Ok((name, value.into_string()?)))
+            .collect()
+    }
+
+    pub async fn config_set(&mut self, parameter: &str, value: &str) -> Result<(), Error> {
+        let set = Resp::from(command::ConfigSet::new(parameter, value));
+        self.request(set).await?.into_ok()
+    }
+
+    /// Returns the INFO of the given section (such as "memory", "all" or
+    /// "everything"), or of the default sections.
+    pub async fn info(&mut self, section: Option<&str>) -> Result<Info, Error> {
+        let info = Resp::from(command::Info::new(section));
+        Info::parse(&self.request(info).await?.into_string()?)
+    }
+
+    /// Returns the `count` most recent slow log entries (10 by default, -1 for all).
+    pub async fn slowlog_get(&mut self, count: Option<i64>) -> Result<Vec<SlowlogEntry>, Error> {
+        let get = Resp::from(command::Slowlog::get(count));
+        self.request(get)
+            .await?
+            .into_array()?
+            .into_iter()
+            .map(SlowlogEntry::from_resp)
+            .collect()
+    }
+
+    pub async fn slowlog_len(&mut self) -> Result<u64, Error> {
+        let len = Resp::from(command::Slowlog::len());
+        self.request(len).await?.into_integer().map(|v| v as u64)
+    }
+
+    pub async fn slowlog_reset(&mut self) -> Result<(), Error> {
+        let reset = Resp::from(command::Slowlog::reset());
+        self.request(reset).await?.into_ok()
+    }
+
+    pub async fn latency_latest(&mut self) -> Result<Vec<LatencyEvent>, Error> {
+        let latest = Resp::from(command::Latency::latest());
+        self.request(latest)
+            .await?
+            .into_array()?
+            .into_iter()
+            .map(LatencyEvent::from_resp)
+            .collect()
+    }
+
+    pub async fn latency_history(&mut self, event: &str) -> Result<Vec<LatencySample>, Error> {
+        let history = Resp::from(command::Latency::history(event));
+        self.request(history)
+            .await?
+            .into_array()?
+            .into_iter()
+            .map(LatencySample::from_resp)
+            .collect()
+    }
+
+    /// Returns the bytes used by the key and its value, or None if the key does not exist.
+    pub async fn memory_usage(
+        &mut self,
+        key: &str,
+        samples: Option<u64>,
+    ) -> Result<Option<u64>, Error> {
+        let usage = Resp::from(command::Memory::usage(key, samples));
+        match self.request(usage).await? {
+            Resp::Null => Ok(None),
+            resp => resp.into_integer().map(|v| Some(v as u64)),
+        }
+    }
+
+    pub async fn memory_stats(&mut self) -> Result<MemoryStats, Error> {
+        let stats = Resp::from(command::Memory::stats());
+        MemoryStats::from_resp(self.request(stats).await?)
+    }
+
+    /// Sends MONITOR and streams every command processed by the server. The
+    /// connection cannot run other commands afterwards. The stream ends after
+    /// yielding an error of the connection.
+    pub async fn monitor(
+        &mut self,
+    ) -> Result<impl Stream<Item = Result<MonitorEntry, Error>> + '_, Error> {
+        let monitor = Resp::from(command::Monitor::new());
+        self.request(monitor).await?.into_ok()?;
+
+        Ok(stream::unfold(Some(self), |client| async move {
+            let client = client?;
+            match client.read_message().await {
+                Ok(Resp::SimpleString(line)) => Some((MonitorEntry::parse(&line), Some(client))),
+                Ok(others) => Some((Err(others.to_error()), Some(client))),
+                // the connection cannot be read anymore, so the error ends the stream
+                Err(e) => Some((Err(e), None)),
+            }
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{client, cmd, resp::Resp, testing::MockServer};
+    use bytes::Bytes;
+    use futures_util::{pin_mut, StreamExt};
+
+    #[tokio::test]
+    async fn monitor_stream_ends_on_connection_error() {
+        let server = MockServer::start().await;
+        server.expect(cmd!["MONITOR"]).replies(vec![
+            Resp::SimpleString("OK".into()),
+            Resp::SimpleString(r#"1339518083.107412 [0 127.0.0.1:60866] "get" "k""#.into()),
+            Resp::SimpleString("not a monitor line".into()),
+        ]);
+
+        let mut client = client::connect(server.addr()).await.unwrap();
+        let stream = client.monitor().await.unwrap();
+        pin_mut!(stream);
+
+        let entry = stream.next().await.unwrap().unwrap();
+        assert_eq!(vec![Bytes::from("get"), Bytes::from("k")], entry.args);
+        // a line which cannot be parsed does not end the stream
+        assert!(stream.next().await.unwrap().is_err());
+
+        server.assert_done();
+        drop(server);
+        let e = stream.next().await.unwrap().unwrap_err();
+        assert!(e.is_connection_error());
+        assert!(stream.next().await.is_none());
+    }
+}
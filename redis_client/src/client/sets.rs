@@ -0,0 +1,170 @@
+use super::Client;
+use crate::{
+    command::{self, SetOperation},
+    error::Error,
+    resp::Resp,
+    scan,
+};
+use bytes::Bytes;
+use futures_core::Stream;
+
+impl Client {
+    /// Returns the number of members that were added.
+    pub async fn sadd(&mut self, key: &str, members: &[Bytes]) -> Result<u64, Error> {
+        let sadd = Resp::from(command::SetMembers::add(key, members));
+        self.request(sadd).await?.into_integer().map(|v| v as u64)
+    }
+
+    /// Returns the number of members that were removed.
+    pub async fn srem(&mut self, key: &str, members: &[Bytes]) -> Result<u64, Error> {
+        let srem = Resp::from(command::SetMembers::rem(key, members));
+        self.request(srem).await?.into_integer().map(|v| v as u64)
+    }
+
+    pub async fn smembers(&mut self, key: &str) -> Result<Vec<Bytes>, Error> {
+        let smembers = Resp::from(command::SetKey::members(key));
+        into_bulks(self.cached_request(smembers).await?)
+    }
+
+    pub async fn sismember(&mut self, key: &str, member: Bytes) -> Result<bool, Error> {
+        let sismember = Resp::from(command::SIsMember::new(key, member));
+        self.request(sismember).await?.into_bool()
+    }
+
+    pub async fn smismember(&mut self, key: &str, members: &[Bytes]) -> Result<Vec<bool>, Error> {
+        let smismember = Resp::from(command::SetMembers::mismember(key, members));
+        self.request(smismember)
+            .await?
+            .into_array()?
+            .into_iter()
+            .map(Resp::into_bool)
+            .collect()
+    }
+
+    pub async fn scard(&mut self, key: &str) -> Result<u64, Error> {
+        let scard = Resp::from(command::SetKey::card(key));
+        self.request(scard).await?.into_integer().map(|v| v as u64)
+    }
+
+    pub async fn sinter(&mut self, keys: &[&str]) -> Result<Vec<Bytes>, Error> {
+        let sinter = Resp::from(command::SetOp::new(SetOperation::Inter, keys));
+        into_bulks(self.request(sinter).await?)
+    }
+
+    pub async fn sunion(&mut self, keys: &[&str]) -> Result<Vec<Bytes>, Error> {
+        let sunion = Resp::from(command::SetOp::new(SetOperation::Union, keys));
+        into_bulks(self.request(sunion).await?)
+    }
+
+    pub async fn sdiff(&mut self, keys: &[&str]) -> Result<Vec<Bytes>, Error> {
+        let sdiff = Resp::from(command::SetOp::new(SetOperation::Diff, keys));
+        into_bulks(self.request(sdiff).await?)
+    }
+
+    /// SINTERSTORE, SUNIONSTORE or SDIFFSTORE. Returns the size of the resulting set.
+    pub async fn sstore(
+        &mut self,
+        operation: SetOperation,
+        destination: &str,
+        keys: &[&str],
+    ) -> Result<u64, Error> {
+        let sstore = Resp::from(command::SetOp::store(operation, destination, keys));
+        self.request(sstore).await?.into_integer().map(|v| v as u64)
+    }
+
+    /// Iterates over members of a set with SSCAN until the cursor wraps to 0.
+    pub fn sscan<'a>(
+        &'a mut self,
+        key: &str,
+        pattern: Option<&str>,
+        count: Option<u64>,
+    ) -> impl Stream<Item = Result<Bytes, Error>> + 'a {
+        let sscan = command::Scan::sscan(key, pattern, count);
+        scan::stream(self, sscan, |elements| {
+            elements.into_iter().map(Resp::into_bulk).collect()
+        })
+    }
+}
+
+fn into_bulks(resp: Resp) -> Result<Vec<Bytes>, Error> {
+    resp.into_array()?
+        .into_iter()
+        .map(Resp::into_bulk)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{client, cmd, testing::bulk, testing::MockServer};
+    use futures_util::StreamExt;
+
+    #[tokio::test]
+    async fn replies() {
+        let server = MockServer::start().await;
+        server
+            .expect(cmd!["SADD", "s", "a", "b"])
+            .reply(Resp::Integer(2));
+        server
+            .expect(cmd!["SMISMEMBER", "s", "a", "c"])
+            .reply(Resp::Array(vec![Resp::Integer(1), Resp::Integer(0)]));
+        server
+            .expect(cmd!["SISMEMBER", "s", "c"])
+            .reply(Resp::Integer(0));
+        server
+            .expect(cmd!["SMEMBERS", "s"])
+            .reply(Resp::Array(vec![bulk("a"), bulk(b"\xff")]));
+        server
+            .expect(cmd!["SINTER", "s", "t"])
+            .reply(Resp::Array(vec![]));
+        server
+            .expect(cmd!["SINTERSTORE", "d", "s", "t"])
+            .reply(Resp::Integer(0));
+        server.expect(cmd!["SCARD", "s"]).reply(bulk("2"));
+
+        let mut client = client::connect(server.addr()).await.unwrap();
+        let members = [Bytes::from("a"), Bytes::from("b")];
+        assert_eq!(2, client.sadd("s", &members).await.unwrap());
+        let members = [Bytes::from("a"), Bytes::from("c")];
+        assert_eq!(
+            vec![true, false],
+            client.smismember("s", &members).await.unwrap()
+        );
+        assert!(!client.sismember("s", Bytes::from("c")).await.unwrap());
+        assert_eq!(
+            vec![Bytes::from("a"), Bytes::from(&b"\xff"[..])],
+            client.smembers("s").await.unwrap()
+        );
+        assert!(client.sinter(&["s", "t"]).await.unwrap().is_empty());
+        assert_eq!(
+            0,
+            client
+                .sstore(SetOperation::Inter, "d", &["s", "t"])
+                .await
+                .unwrap()
+        );
+        // an integer is expected
+        assert!(client.scard("s").await.is_err());
+        server.assert_done();
+    }
+
+    #[tokio::test]
+    async fn sscan_stream() {
+        let server = MockServer::start().await;
+        server
+            .expect(cmd!["SSCAN", "s", "0"])
+            .reply(Resp::Array(vec![bulk("3"), Resp::Array(vec![bulk("a")])]));
+        server
+            .expect(cmd!["SSCAN", "s", "3"])
+            .reply(Resp::Array(vec![bulk("0"), Resp::Array(vec![bulk("b")])]));
+
+        let mut client = client::connect(server.addr()).await.unwrap();
+        let members: Vec<_> = client
+            .sscan("s", None, None)
+            .map(|v| v.unwrap())
+            .collect()
+            .await;
+        assert_eq!(vec![Bytes::from("a"), Bytes::from("b")], members);
+        server.assert_done();
+    }
+}
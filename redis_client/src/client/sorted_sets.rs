@@ -0,0 +1,364 @@
+use super::Client;
+use crate::{
+    command::{self, Aggregate, ZAddOptions, ZRange},
+    error::Error,
+    resp::Resp,
+    scan,
+};
+use bytes::Bytes;
+use futures_core::Stream;
+use std::time::Duration;
+
+impl Client {
+    /// Returns the number of added members (or changed members with CH).
+    pub async fn zadd(
+        &mut self,
+        key: &str,
+        options: ZAddOptions,
+        members: &[(f64, Bytes)],
+    ) -> Result<u64, Error> {
+        let zadd = Resp::from(command::ZAdd::new(key, options, members));
+        self.request(zadd).await?.into_integer().map(|v| v as u64)
+    }
+
+    /// ZADD with INCR. Returns the new score, or None if the operation was
+    /// aborted by NX/XX/GT/LT.
+    pub async fn zadd_incr(
+        &mut self,
+        key: &str,
+        options: ZAddOptions,
+        increment: f64,
+        member: Bytes,
+    ) -> Result<Option<f64>, Error> {
+        let zadd = Resp::from(command::ZAdd::new_incr(key, options, increment, member));
+        into_optional_float(self.request(zadd).await?)
+    }
+
+    pub async fn zrange(&mut self, key: &str, range: ZRange) -> Result<Vec<Bytes>, Error> {
+        let zrange = Resp::from(command::ZRangeCommand::new(key, range, false));
+        self.request(zrange)
+            .await?
+            .into_array()?
+            .into_iter()
+            .map(Resp::into_bulk)
+            .collect()
+    }
+
+    pub async fn zrange_withscores(
+        &mut self,
+        key: &str,
+        range: ZRange,
+    ) -> Result<Vec<(Bytes, f64)>, Error> {
+        let zrange = Resp::from(command::ZRangeCommand::new(key, range, true));
+        into_scored(self.request(zrange).await?.into_array()?)
+    }
+
+    pub async fn zrank(&mut self, key: &str, member: Bytes) -> Result<Option<u64>, Error> {
+        let zrank = Resp::from(command::ZMember::rank(key, member));
+        into_optional_rank(self.request(zrank).await?)
+    }
+
+    pub async fn zrevrank(&mut self, key: &str, member: Bytes) -> Result<Option<u64>, Error> {
+        let zrevrank = Resp::from(command::ZMember::revrank(key, member));
+        into_optional_rank(self.request(zrevrank).await?)
+    }
+
+    pub async fn zscore(&mut self, key: &str, member: Bytes) -> Result<Option<f64>, Error> {
+        let zscore = Resp::from(command::ZMember::score(key, member));
+        into_optional_float(self.cached_request(zscore).await?)
+    }
+
+    pub async fn zmscore(
+        &mut self,
+        key: &str,
+        members: &[Bytes],
+    ) -> Result<Vec<Option<f64>>, Error> {
+        let zmscore = Resp::from(command::ZMember::mscore(key, members));
+        self.request(zmscore)
+            .await?
+            .into_array()?
+            .into_iter()
+            .map(into_optional_float)
+            .collect()
+    }
+
+    /// Returns the new score of the member.
+    pub async fn zincrby(
+        &mut self,
+        key: &str,
+        increment: f64,
+        member: Bytes,
+    ) -> Result<f64, Error> {
+        let zincrby = Resp::from(command::ZIncrBy::new(key, increment, member));
+        self.request(zincrby).await?.into_float()
+    }
+
+    /// Returns the number of members that were removed.
+    pub async fn zrem(&mut self, key: &str, members: &[Bytes]) -> Result<u64, Error> {
+        let zrem = Resp::from(command::ZMember::rem(key, members));
+        self.request(zrem).await?.into_integer().map(|v| v as u64)
+    }
+
+    pub async fn zpopmin(
+        &mut self,
+        key: &str,
+        count: Option<u64>,
+    ) -> Result<Vec<(Bytes, f64)>, Error> {
+        let zpopmin = Resp::from(command::ZPop::new(false, key, count));
+        into_scored(self.request(zpopmin).await?.into_array()?)
+    }
+
+    pub async fn zpopmax(
+        &mut self,
+        key: &str,
+        count: Option<u64>,
+    ) -> Result<Vec<(Bytes, f64)>, Error> {
+        let zpopmax = Resp::from(command::ZPop::new(true, key, count));
+        into_scored(self.request(zpopmax).await?.into_array()?)
+    }
+
+    /// Blocks until a member is available on one of the keys. Returns the key,
+    /// the member and its score, or None if `timeout` expired on the server.
+    /// A zero `timeout` blocks forever.
+    pub async fn bzpopmin(
+        &mut self,
+        keys: &[&str],
+        timeout: Duration,
+    ) -> Result<Option<(String, Bytes, f64)>, Error> {
+        let bzpopmin = Resp::from(command::BZPop::new(false, keys, timeout));
+        into_key_scored(self.request_blocking(bzpopmin, timeout).await?)
+    }
+
+    /// Same as `bzpopmin` but pops the member with the highest score.
+    pub async fn bzpopmax(
+        &mut self,
+        keys: &[&str],
+        timeout: Duration,
+    ) -> Result<Option<(String, Bytes, f64)>, Error> {
+        let bzpopmax = Resp::from(command::BZPop::new(true, keys, timeout));
+        into_key_scored(self.request_blocking(bzpopmax, timeout).await?)
+    }
+
+    /// Counts members with a score between `min` and `max` (e.g. "-inf", "(1.5").
+    pub async fn zcount(&mut self, key: &str, min: &str, max: &str) -> Result<u64, Error> {
+        let zcount = Resp::from(command::ZCount::new(key, min, max));
+        self.request(zcount).await?.into_integer().map(|v| v as u64)
+    }
+
+    /// Returns the number of members in the resulting sorted set.
+    pub async fn zunionstore(
+        &mut self,
+        destination: &str,
+        keys: &[&str],
+        weights: Option<&[f64]>,
+        aggregate: Option<Aggregate>,
+    ) -> Result<u64, Error> {
+        let zunionstore = Resp::from(command::ZStore::new(
+            true,
+            destination,
+            keys,
+            weights,
+            aggregate,
+        ));
+        self.request(zunionstore)
+            .await?
+            .into_integer()
+            .map(|v| v as u64)
+    }
+
+    /// Returns the number of members in the resulting sorted set.
+    pub async fn zinterstore(
+        &mut self,
+        destination: &str,
+        keys: &[&str],
+        weights: Option<&[f64]>,
+        aggregate: Option<Aggregate>,
+    ) -> Result<u64, Error> {
+        let zinterstore = Resp::from(command::ZStore::new(
+            false,
+            destination,
+            keys,
+            weights,
+            aggregate,
+        ));
+        self.request(zinterstore)
+            .await?
+            .into_integer()
+            .map(|v| v as u64)
+    }
+
+    /// Iterates over members and scores of a sorted set with ZSCAN until the cursor wraps to 0.
+    pub fn zscan<'a>(
+        &'a mut self,
+        key: &str,
+        pattern: Option<&str>,
+        count: Option<u64>,
+    ) -> impl Stream<Item = Result<(Bytes, f64), Error>> + 'a {
+        let zscan = command::Scan::zscan(key, pattern, count);
+        scan::stream(self, zscan, into_scored)
+    }
+}
+
+// [member1, score1, member2, score2, ...] into [(member1, score1), ...]
+fn into_scored(ary: Vec<Resp>) -> Result<Vec<(Bytes, f64)>, Error> {
+    if !ary.len().is_multiple_of(2) {
+        return Err(Error::Invalid(format!(
+            "even number of elements is expected, but {}",
+            ary.len()
+        )));
+    }
+
+    let mut scored = Vec::with_capacity(ary.len() / 2);
+    let mut iter = ary.into_iter();
+    while let (Some(member), Some(score)) = (iter.next(), iter.next()) {
+        scored.push((member.into_bulk()?, score.into_float()?));
+    }
+    Ok(scored)
+}
+
+// [key, member, score] or nil (on timeout)
+fn into_key_scored(resp: Resp) -> Result<Option<(String, Bytes, f64)>, Error> {
+    match resp {
+        Resp::Null => Ok(None),
+        resp => {
+            let mut iter = resp.into_array()?.into_iter();
+            match (iter.next(), iter.next(), iter.next()) {
+                (Some(key), Some(member), Some(score)) => Ok(Some((
+                    key.into_string()?,
+                    member.into_bulk()?,
+                    score.into_float()?,
+                ))),
+                _ => Err(Error::Invalid(
+                    "[key, member, score] is expected".to_string(),
+                )),
+            }
+        }
+    }
+}
+
+fn into_optional_float(resp: Resp) -> Result<Option<f64>, Error> {
+    match resp {
+        Resp::Null => Ok(None),
+        resp => resp.into_float().map(Some),
+    }
+}
+
+fn into_optional_rank(resp: Resp) -> Result<Option<u64>, Error> {
+    match resp {
+        Resp::Null => Ok(None),
+        resp => resp.into_integer().map(|v| Some(v as u64)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{client, cmd, testing::bulk, testing::MockServer};
+    use futures_util::StreamExt;
+
+    #[tokio::test]
+    async fn replies() {
+        let server = MockServer::start().await;
+        server
+            .expect(cmd!["ZADD", "z", "XX", "INCR", "1", "m"])
+            .reply(Resp::Null);
+        server
+            .expect(cmd!["ZRANGE", "z", "0", "-1", "WITHSCORES"])
+            .reply(Resp::Array(vec![
+                bulk("a"),
+                bulk("1"),
+                bulk("b"),
+                bulk("inf"),
+            ]));
+        server.expect(cmd!["ZRANK", "z", "x"]).reply(Resp::Null);
+        server
+            .expect(cmd!["ZREVRANK", "z", "a"])
+            .reply(Resp::Integer(1));
+        server
+            .expect(cmd!["ZMSCORE", "z", "a", "x"])
+            .reply(Resp::Array(vec![bulk("1.5"), Resp::Null]));
+        server
+            .expect(cmd!["ZPOPMIN", "z", "2"])
+            .reply(Resp::Array(vec![bulk("a"), bulk("1")]));
+        server
+            .expect(cmd!["BZPOPMAX", "z", "0.1"])
+            .reply(Resp::Array(vec![bulk("z"), bulk("b"), bulk("-2")]));
+        server
+            .expect(cmd!["BZPOPMIN", "z", "0.1"])
+            .reply(Resp::Null);
+        server
+            .expect(cmd!["ZRANGE", "z", "0", "-1", "WITHSCORES"])
+            .reply(Resp::Array(vec![bulk("a")]));
+
+        let mut client = client::connect(server.addr()).await.unwrap();
+        let options = ZAddOptions {
+            xx: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            None,
+            client
+                .zadd_incr("z", options, 1.0, Bytes::from("m"))
+                .await
+                .unwrap()
+        );
+        assert_eq!(
+            vec![(Bytes::from("a"), 1.0), (Bytes::from("b"), f64::INFINITY)],
+            client
+                .zrange_withscores("z", ZRange::index(0, -1))
+                .await
+                .unwrap()
+        );
+        assert_eq!(None, client.zrank("z", Bytes::from("x")).await.unwrap());
+        assert_eq!(
+            Some(1),
+            client.zrevrank("z", Bytes::from("a")).await.unwrap()
+        );
+        assert_eq!(
+            vec![Some(1.5), None],
+            client
+                .zmscore("z", &[Bytes::from("a"), Bytes::from("x")])
+                .await
+                .unwrap()
+        );
+        assert_eq!(
+            vec![(Bytes::from("a"), 1.0)],
+            client.zpopmin("z", Some(2)).await.unwrap()
+        );
+        let timeout = Duration::from_millis(100);
+        assert_eq!(
+            Some(("z".to_string(), Bytes::from("b"), -2.0)),
+            client.bzpopmax(&["z"], timeout).await.unwrap()
+        );
+        assert_eq!(None, client.bzpopmin(&["z"], timeout).await.unwrap());
+        // an odd number of elements
+        assert!(client
+            .zrange_withscores("z", ZRange::index(0, -1))
+            .await
+            .is_err());
+        server.assert_done();
+    }
+
+    #[tokio::test]
+    async fn zscan_stream() {
+        let server = MockServer::start().await;
+        server
+            .expect(cmd!["ZSCAN", "z", "0", "COUNT", "10"])
+            .reply(Resp::Array(vec![
+                bulk("0"),
+                Resp::Array(vec![bulk("a"), bulk("1"), bulk("b"), bulk("2.5")]),
+            ]));
+
+        let mut client = client::connect(server.addr()).await.unwrap();
+        let members: Vec<_> = client
+            .zscan("z", None, Some(10))
+            .map(|v| v.unwrap())
+            .collect()
+            .await;
+        assert_eq!(
+            vec![(Bytes::from("a"), 1.0), (Bytes::from("b"), 2.5)],
+            members
+        );
+        server.assert_done();
+    }
+}
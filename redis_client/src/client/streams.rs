@@ -0,0 +1,239 @@
+use super::Client;
+use crate::{
+    command,
+    error::Error,
+    resp::Resp,
+    streams::{
+        self, AutoClaim, ConsumerInfo, GroupInfo, PendingEntry, PendingSummary, StreamEntry,
+        StreamId, StreamInfo, StreamTrimOptions,
+    },
+};
+use bytes::Bytes;
+use std::time::Duration;
+
+impl Client {
+    /// Appends an entry and returns its ID. `id` is generated by the server (`*`) if None.
+    pub async fn xadd(
+        &mut self,
+        key: &str,
+        id: Option<StreamId>,
+        trim: Option<StreamTrimOptions>,
+        fields: &[(&str, Bytes)],
+    ) -> Result<StreamId, Error> {
+        let xadd = Resp::from(command::XAdd::new(key, id, trim, fields));
+        StreamId::from_resp(self.request(xadd).await?)
+    }
+
+    /// Returns entries between `start` and `end` ("-", "+", "(<id>" and plain IDs are accepted).
+    pub async fn xrange(
+        &mut self,
+        key: &str,
+        start: &str,
+        end: &str,
+        count: Option<u64>,
+    ) -> Result<Vec<StreamEntry>, Error> {
+        let xrange = Resp::from(command::XRange::new(key, start, end, count));
+        StreamEntry::from_resp_array(self.request(xrange).await?)
+    }
+
+    /// Same as `xrange` in reverse order. Note that `end` comes first.
+    pub async fn xrevrange(
+        &mut self,
+        key: &str,
+        end: &str,
+        start: &str,
+        count: Option<u64>,
+    ) -> Result<Vec<StreamEntry>, Error> {
+        let xrevrange = Resp::from(command::XRange::new_rev(key, end, start, count));
+        StreamEntry::from_resp_array(self.request(xrevrange).await?)
+    }
+
+    pub async fn xlen(&mut self, key: &str) -> Result<u64, Error> {
+        let xlen = Resp::from(command::XLen::new(key));
+        self.request(xlen).await?.into_integer().map(|v| v as u64)
+    }
+
+    /// Returns the number of deleted entries.
+    pub async fn xdel(&mut self, key: &str, ids: &[StreamId]) -> Result<u64, Error> {
+        let xdel = Resp::from(command::XDel::new(key, ids));
+        self.request(xdel).await?.into_integer().map(|v| v as u64)
+    }
+
+    /// Returns the number of evicted entries.
+    pub async fn xtrim(&mut self, key: &str, trim: StreamTrimOptions) -> Result<u64, Error> {
+        let xtrim = Resp::from(command::XTrim::new(key, trim));
+        self.request(xtrim).await?.into_integer().map(|v| v as u64)
+    }
+
+    /// Reads entries with IDs greater than the given ones from each `(key, id)`
+    /// stream (`$` for new entries only). With `block`, waits until an entry
+    /// arrives and returns an empty Vec on timeout; zero blocks forever.
+    pub async fn xread(
+        &mut self,
+        streams: &[(&str, &str)],
+        count: Option<u64>,
+        block: Option<Duration>,
+    ) -> Result<Vec<(String, Vec<StreamEntry>)>, Error> {
+        let xread = Resp::from(command::XRead::new(streams, count, block));
+        streams::from_read_resp(match block {
+            Some(block) => self.request_blocking(xread, block).await?,
+            None => self.request(xread).await?,
+        })
+    }
+
+    /// Reads entries as `consumer` of `group`. Use `>` as the ID for entries never
+    /// delivered to other consumers, or an ID to read the consumer's pending entries.
+    pub async fn xreadgroup(
+        &mut self,
+        group: &str,
+        consumer: &str,
+        streams: &[(&str, &str)],
+        count: Option<u64>,
+        block: Option<Duration>,
+        noack: bool,
+    ) -> Result<Vec<(String, Vec<StreamEntry>)>, Error> {
+        let xreadgroup = Resp::from(command::XRead::new_group(
+            group, consumer, streams, count, block, noack,
+        ));
+        streams::from_read_resp(match block {
+            Some(block) => self.request_blocking(xreadgroup, block).await?,
+            None => self.request(xreadgroup).await?,
+        })
+    }
+
+    /// Creates a consumer group starting at `id` (`$` for new entries only).
+    /// With `mkstream`, the stream is created if it does not exist.
+    pub async fn xgroup_create(
+        &mut self,
+        key: &str,
+        group: &str,
+        id: &str,
+        mkstream: bool,
+    ) -> Result<(), Error> {
+        let xgroup = Resp::from(command::XGroup::create(key, group, id, mkstream));
+        self.request(xgroup).await?.into_ok()
+    }
+
+    pub async fn xgroup_destroy(&mut self, key: &str, group: &str) -> Result<bool, Error> {
+        let xgroup = Resp::from(command::XGroup::destroy(key, group));
+        self.request(xgroup).await?.into_bool()
+    }
+
+    pub async fn xgroup_setid(&mut self, key: &str, group: &str, id: &str) -> Result<(), Error> {
+        let xgroup = Resp::from(command::XGroup::setid(key, group, id));
+        self.request(xgroup).await?.into_ok()
+    }
+
+    pub async fn xgroup_createconsumer(
+        &mut self,
+        key: &str,
+        group: &str,
+        consumer: &str,
+    ) -> Result<bool, Error> {
+        let xgroup = Resp::from(command::XGroup::createconsumer(key, group, consumer));
+        self.request(xgroup).await?.into_bool()
+    }
+
+    /// Returns the number of pending entries the consumer had.
+    pub async fn xgroup_delconsumer(
+        &mut self,
+        key: &str,
+        group: &str,
+        consumer: &str,
+    ) -> Result<u64, Error> {
+        let xgroup = Resp::from(command::XGroup::delconsumer(key, group, consumer));
+        self.request(xgroup).await?.into_integer().map(|v| v as u64)
+    }
+
+    /// Returns the number of acknowledged entries.
+    pub async fn xack(&mut self, key: &str, group: &str, ids: &[StreamId]) -> Result<u64, Error> {
+        let xack = Resp::from(command::XAck::new(key, group, ids));
+        self.request(xack).await?.into_integer().map(|v| v as u64)
+    }
+
+    pub async fn xpending(&mut self, key: &str, group: &str) -> Result<PendingSummary, Error> {
+        let xpending = Resp::from(command::XPending::new(key, group));
+        PendingSummary::from_resp(self.request(xpending).await?)
+    }
+
+    /// XPENDING in the extended form. `idle` filters entries idle for at least that long.
+    #[allow(clippy::too_many_arguments)]
+    pub async fn xpending_range(
+        &mut self,
+        key: &str,
+        group: &str,
+        idle: Option<Duration>,
+        start: &str,
+        end: &str,
+        count: u64,
+        consumer: Option<&str>,
+    ) -> Result<Vec<PendingEntry>, Error> {
+        let xpending = Resp::from(command::XPending::new_range(
+            key, group, idle, start, end, count, consumer,
+        ));
+        self.request(xpending)
+            .await?
+            .into_array()?
+            .into_iter()
+            .map(PendingEntry::from_resp)
+            .collect()
+    }
+
+    /// Changes the owner of pending entries idle for at least `min_idle` to `consumer`.
+    pub async fn xclaim(
+        &mut self,
+        key: &str,
+        group: &str,
+        consumer: &str,
+        min_idle: Duration,
+        ids: &[StreamId],
+    ) -> Result<Vec<StreamEntry>, Error> {
+        let xclaim = Resp::from(command::XClaim::new(key, group, consumer, min_idle, ids));
+        StreamEntry::from_resp_array(self.request(xclaim).await?)
+    }
+
+    /// Claims pending entries idle for at least `min_idle` scanning the PEL from `start`.
+    pub async fn xautoclaim(
+        &mut self,
+        key: &str,
+        group: &str,
+        consumer: &str,
+        min_idle: Duration,
+        start: StreamId,
+        count: Option<u64>,
+    ) -> Result<AutoClaim, Error> {
+        let xautoclaim = Resp::from(command::XAutoClaim::new(
+            key, group, consumer, min_idle, start, count,
+        ));
+        AutoClaim::from_resp(self.request(xautoclaim).await?)
+    }
+
+    pub async fn xinfo_stream(&mut self, key: &str) -> Result<StreamInfo, Error> {
+        let xinfo = Resp::from(command::XInfo::stream(key));
+        StreamInfo::from_resp(self.request(xinfo).await?)
+    }
+
+    pub async fn xinfo_groups(&mut self, key: &str) -> Result<Vec<GroupInfo>, Error> {
+        let xinfo = Resp::from(command::XInfo::groups(key));
+        self.request(xinfo)
+            .await?
+            .into_array()?
+            .into_iter()
+            .map(GroupInfo::from_resp)
+            .collect()
+    }
+
+    pub async fn xinfo_consumers(
+        &mut self,
+        key: &str,
+        group: &str,
+    ) -> Result<Vec<ConsumerInfo>, Error> {
+        let xinfo = Resp::from(command::XInfo::consumers(key, group));
+        self.request(xinfo)
+            .await?
+            .into_array()?
+            .into_iter()
+            .map(ConsumerInfo::from_resp)
+            .collect()
+    }
+}
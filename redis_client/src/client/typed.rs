@@ -0,0 +1,95 @@
+use super::Client;
+use crate::{command, de, error::Error, resp::Resp, ser, streams::StreamId};
+use bytes::Bytes;
+use serde::{de::DeserializeOwned, Serialize};
+
+impl Client {
+    /// Stores the fields of a struct or a map in a hash, see `redis_client::ser`.
+    /// `None` fields are removed from the hash with HDEL first, so the two
+    /// writes are not atomic. Returns the number of fields that were added.
+    pub async fn hset_struct<T: Serialize + ?Sized>(
+        &mut self,
+        key: &str,
+        value: &T,
+    ) -> Result<u64, Error> {
+        let fields = ser::to_hash_fields(value)?;
+        if !fields.cleared.is_empty() {
+            let cleared: Vec<&str> = fields.cleared.iter().map(String::as_str).collect();
+            self.hdel(key, &cleared).await?;
+        }
+        // HSET without any field is a syntax error
+        if fields.set.is_empty() {
+            return Ok(0);
+        }
+        let pairs: Vec<(&str, Bytes)> = fields
+            .set
+            .iter()
+            .map(|(f, v)| (f.as_str(), v.clone()))
+            .collect();
+        self.hset(key, &pairs).await
+    }
+
+    /// HGETALL deserialized into `T`, see `redis_client::de`. A missing key
+    /// is read as an empty hash.
+    pub async fn hgetall_as<T: DeserializeOwned>(&mut self, key: &str) -> Result<T, Error> {
+        let hgetall = Resp::from(command::HashKey::getall(key));
+        de::from_resp(self.cached_request(hgetall).await?)
+    }
+
+    /// Same as `xrange`, with the fields of each entry deserialized into `T`.
+    pub async fn xrange_as<T: DeserializeOwned>(
+        &mut self,
+        key: &str,
+        start: &str,
+        end: &str,
+        count: Option<u64>,
+    ) -> Result<Vec<(StreamId, T)>, Error> {
+        let xrange = Resp::from(command::XRange::new(key, start, end, count));
+        let entries: Vec<(String, T)> = de::from_resp(self.request(xrange).await?)?;
+        entries
+            .into_iter()
+            .map(|(id, fields)| Ok((id.parse()?, fields)))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{client, cmd, resp::Resp, testing::MockServer};
+    use serde::Serialize;
+
+    #[derive(Serialize)]
+    struct User {
+        name: Option<String>,
+        email: Option<String>,
+    }
+
+    #[tokio::test]
+    async fn hset_struct_clears_none_fields() {
+        let server = MockServer::start().await;
+        server
+            .expect(cmd!["HDEL", "user", "email"])
+            .reply(Resp::Integer(1));
+        server
+            .expect(cmd!["HSET", "user", "name", "alice"])
+            .reply(Resp::Integer(0));
+        server
+            .expect(cmd!["HDEL", "user", "name", "email"])
+            .reply(Resp::Integer(2));
+
+        let mut client = client::connect(server.addr()).await.unwrap();
+        let user = User {
+            name: Some("alice".to_string()),
+            email: None,
+        };
+        assert_eq!(0, client.hset_struct("user", &user).await.unwrap());
+
+        // no HSET without fields
+        let user = User {
+            name: None,
+            email: None,
+        };
+        assert_eq!(0, client.hset_struct("user", &user).await.unwrap());
+        server.assert_done();
+    }
+}
@@ -0,0 +1,107 @@
+use crate::error::Error;
+use crate::resp::{ParseErr, Resp, RespDecoder};
+use bytes::BytesMut;
+use tokio_util::codec::{Decoder, Encoder};
+
+/// RESP codec for `tokio_util::codec::Framed` and friends, to build servers,
+/// proxies or test doubles on top of the crate's protocol code.
+///
+/// ```no_run
+/// use futures_util::{SinkExt, StreamExt};
+/// use redis_client::{codec::RespCodec, resp::Resp};
+/// use tokio::net::TcpStream;
+/// use tokio_util::codec::Framed;
+///
+/// # async fn run() -> Result<(), redis_client::error::Error> {
+/// let stream = TcpStream::connect("127.0.0.1:6379").await?;
+/// let mut framed = Framed::new(stream, RespCodec::new());
+///
+/// framed.send(Resp::Array(vec![Resp::BulkString("PING".into())])).await?;
+/// assert_eq!(Some(Resp::SimpleString("PONG".into())), framed.next().await.transpose()?);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Default)]
+pub struct RespCodec {
+    decoder: RespDecoder,
+}
+
+impl RespCodec {
+    pub fn new() -> RespCodec {
+        RespCodec::default()
+    }
+}
+
+impl Decoder for RespCodec {
+    type Item = Resp;
+    type Error = Error;
+
+    fn decode(&mut self, src: &mut BytesMut) -> Result<Option<Resp>, Error> {
+        match self.decoder.decode(src) {
+            Ok(resp) => Ok(resp),
+            Err(ParseErr::Imcomplete) => Ok(None),
+            Err(ParseErr::Invalid(e)) => Err(Error::Serialization(e)),
+        }
+    }
+}
+
+impl Encoder<Resp> for RespCodec {
+    type Error = Error;
+
+    fn encode(&mut self, item: Resp, dst: &mut BytesMut) -> Result<(), Error> {
+        item.encode(dst);
+        Ok(())
+    }
+}
+
+impl Encoder<&Resp> for RespCodec {
+    type Error = Error;
+
+    fn encode(&mut self, item: &Resp, dst: &mut BytesMut) -> Result<(), Error> {
+        item.encode(dst);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bytes::Bytes;
+
+    #[test]
+    fn round_trip() {
+        let values = vec![
+            Resp::SimpleString("OK".into()),
+            Resp::Error("ERR bad".into()),
+            Resp::Integer(-42),
+            Resp::BulkString(Bytes::from("foo\r\nbar")),
+            Resp::Array(vec![
+                Resp::Integer(1),
+                Resp::Array(vec![Resp::BulkString(Bytes::new()), Resp::Array(vec![])]),
+                Resp::Null,
+            ]),
+            Resp::Null,
+        ];
+
+        let mut codec = RespCodec::new();
+        let mut buf = BytesMut::new();
+        for value in &values {
+            codec.encode(value, &mut buf).unwrap();
+        }
+
+        let mut decoded = vec![];
+        while let Some(resp) = codec.decode(&mut buf).unwrap() {
+            decoded.push(resp);
+        }
+        assert_eq!(values, decoded);
+        assert!(buf.is_empty());
+    }
+
+    #[test]
+    fn encode_null_and_integer() {
+        let mut buf = BytesMut::new();
+        RespCodec::new().encode(Resp::Null, &mut buf).unwrap();
+        RespCodec::new().encode(Resp::Integer(7), &mut buf).unwrap();
+        assert_eq!(&b"$-1\r\n:7\r\n"[..], &buf[..]);
+    }
+}
@@ -0,0 +1,104 @@
+use crate::resp::Resp;
+use bytes::Bytes;
+
+#[derive(Debug)]
+pub struct ClientId {}
+
+impl ClientId {
+    pub fn new() -> Self {
+        ClientId {}
+    }
+}
+
+impl From<ClientId> for Resp {
+    fn from(_id: ClientId) -> Self {
+        let mut ary = Resp::array();
+        ary.push_bulk_strings(Bytes::from("CLIENT"));
+        ary.push_bulk_strings(Bytes::from("ID"));
+        ary
+    }
+}
+
+#[derive(Debug)]
+pub struct ClientTracking {
+    on: bool,
+    redirect: Option<u64>,
+}
+
+impl ClientTracking {
+    pub fn on(redirect: Option<u64>) -> Self {
+        ClientTracking { on: true, redirect }
+    }
+
+    pub fn off() -> Self {
+        ClientTracking {
+            on: false,
+            redirect: None,
+        }
+    }
+}
+
+impl From<ClientTracking> for Resp {
+    fn from(tracking: ClientTracking) -> Self {
+        let mut ary = Resp::array();
+        ary.push_bulk_strings(Bytes::from("CLIENT"));
+        ary.push_bulk_strings(Bytes::from("TRACKING"));
+        if tracking.on {
+            ary.push_bulk_strings(Bytes::from("ON"));
+        } else {
+            ary.push_bulk_strings(Bytes::from("OFF"));
+        }
+        if let Some(id) = tracking.redirect {
+            ary.push_bulk_strings(Bytes::from("REDIRECT"));
+            ary.push_bulk_strings(Bytes::from(id.to_string()));
+        }
+        ary
+    }
+}
+
+#[derive(Debug)]
+pub struct Auth {
+    username: Option<String>,
+    password: String,
+}
+
+impl Auth {
+    pub fn new(username: Option<&str>, password: &str) -> Self {
+        Auth {
+            username: username.map(str::to_string),
+            password: password.to_string(),
+        }
+    }
+}
+
+impl From<Auth> for Resp {
+    fn from(auth: Auth) -> Self {
+        let mut ary = Resp::array();
+        ary.push_bulk_strings(Bytes::from("AUTH"));
+        if let Some(username) = auth.username {
+            ary.push_bulk_strings(Bytes::from(username));
+        }
+        ary.push_bulk_strings(Bytes::from(auth.password));
+        ary
+    }
+}
+
+#[derive(Debug)]
+pub struct Select {
+    db: u64,
+}
+
+impl Select {
+    pub fn new(db: u64) -> Self {
+        Select { db }
+    }
+}
+
+impl From<Select> for Resp {
+    fn from(select: Select) -> Self {
+        let mut ary = Resp::array();
+        ary.push_bulk_strings(Bytes::from("SELECT"));
+        ary.push_bulk_strings(Bytes::from(select.db.to_string()));
+        ary
+    }
+}
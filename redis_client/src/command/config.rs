@@ -0,0 +1,51 @@
+use crate::resp::Resp;
+use bytes::Bytes;
+
+#[derive(Debug)]
+pub struct ConfigGet {
+    parameter: String,
+}
+
+impl ConfigGet {
+    pub fn new(parameter: impl ToString) -> Self {
+        ConfigGet {
+            parameter: parameter.to_string(),
+        }
+    }
+}
+
+impl From<ConfigGet> for Resp {
+    fn from(get: ConfigGet) -> Self {
+        let mut ary = Resp::array();
+        ary.push_bulk_strings(Bytes::from("CONFIG"));
+        ary.push_bulk_strings(Bytes::from("GET"));
+        ary.push_bulk_strings(Bytes::from(get.parameter.into_bytes()));
+        ary
+    }
+}
+
+#[derive(Debug)]
+pub struct ConfigSet {
+    parameter: String,
+    value: String,
+}
+
+impl ConfigSet {
+    pub fn new(parameter: impl ToString, value: impl ToString) -> Self {
+        ConfigSet {
+            parameter: parameter.to_string(),
+            value: value.to_string(),
+        }
+    }
+}
+
+impl From<ConfigSet> for Resp {
+    fn from(set: ConfigSet) -> Self {
+        let mut ary = Resp::array();
+        ary.push_bulk_strings(Bytes::from("CONFIG"));
+        ary.push_bulk_strings(Bytes::from("SET"));
+        ary.push_bulk_strings(Bytes::from(set.parameter.into_bytes()));
+        ary.push_bulk_strings(Bytes::from(set.value.into_bytes()));
+        ary
+    }
+}
@@ -0,0 +1,195 @@
+use crate::command::FlushMode;
+use crate::functions::RestorePolicy;
+use crate::resp::Resp;
+use bytes::Bytes;
+
+#[derive(Debug)]
+pub struct FunctionLoad {
+    code: String,
+    replace: bool,
+}
+
+impl FunctionLoad {
+    pub fn new(code: impl ToString, replace: bool) -> Self {
+        FunctionLoad {
+            code: code.to_string(),
+            replace,
+        }
+    }
+}
+
+impl From<FunctionLoad> for Resp {
+    fn from(load: FunctionLoad) -> Self {
+        let mut ary = Resp::array();
+        ary.push_bulk_strings(Bytes::from("FUNCTION"));
+        ary.push_bulk_strings(Bytes::from("LOAD"));
+        if load.replace {
+            ary.push_bulk_strings(Bytes::from("REPLACE"));
+        }
+        ary.push_bulk_strings(Bytes::from(load.code.into_bytes()));
+        ary
+    }
+}
+
+#[derive(Debug)]
+pub struct FunctionList {
+    pattern: Option<String>,
+    withcode: bool,
+}
+
+impl FunctionList {
+    pub fn new(pattern: Option<&str>, withcode: bool) -> Self {
+        FunctionList {
+            pattern: pattern.map(|p| p.to_string()),
+            withcode,
+        }
+    }
+}
+
+impl From<FunctionList> for Resp {
+    fn from(list: FunctionList) -> Self {
+        let mut ary = Resp::array();
+        ary.push_bulk_strings(Bytes::from("FUNCTION"));
+        ary.push_bulk_strings(Bytes::from("LIST"));
+        if let Some(pattern) = list.pattern {
+            ary.push_bulk_strings(Bytes::from("LIBRARYNAME"));
+            ary.push_bulk_strings(Bytes::from(pattern.into_bytes()));
+        }
+        if list.withcode {
+            ary.push_bulk_strings(Bytes::from("WITHCODE"));
+        }
+        ary
+    }
+}
+
+#[derive(Debug)]
+pub struct FunctionDelete {
+    library: String,
+}
+
+impl FunctionDelete {
+    pub fn new(library: impl ToString) -> Self {
+        FunctionDelete {
+            library: library.to_string(),
+        }
+    }
+}
+
+impl From<FunctionDelete> for Resp {
+    fn from(delete: FunctionDelete) -> Self {
+        let mut ary = Resp::array();
+        ary.push_bulk_strings(Bytes::from("FUNCTION"));
+        ary.push_bulk_strings(Bytes::from("DELETE"));
+        ary.push_bulk_strings(Bytes::from(delete.library.into_bytes()));
+        ary
+    }
+}
+
+#[derive(Debug)]
+pub struct FunctionDump {}
+
+impl FunctionDump {
+    pub fn new() -> Self {
+        FunctionDump {}
+    }
+}
+
+impl From<FunctionDump> for Resp {
+    fn from(_dump: FunctionDump) -> Self {
+        let mut ary = Resp::array();
+        ary.push_bulk_strings(Bytes::from("FUNCTION"));
+        ary.push_bulk_strings(Bytes::from("DUMP"));
+        ary
+    }
+}
+
+#[derive(Debug)]
+pub struct FunctionRestore {
+    payload: Bytes,
+    policy: Option<RestorePolicy>,
+}
+
+impl FunctionRestore {
+    pub fn new(payload: Bytes, policy: Option<RestorePolicy>) -> Self {
+        FunctionRestore { payload, policy }
+    }
+}
+
+impl From<FunctionRestore> for Resp {
+    fn from(restore: FunctionRestore) -> Self {
+        let mut ary = Resp::array();
+        ary.push_bulk_strings(Bytes::from("FUNCTION"));
+        ary.push_bulk_strings(Bytes::from("RESTORE"));
+        ary.push_bulk_strings(restore.payload);
+        match restore.policy {
+            Some(RestorePolicy::Flush) => ary.push_bulk_strings(Bytes::from("FLUSH")),
+            Some(RestorePolicy::Append) => ary.push_bulk_strings(Bytes::from("APPEND")),
+            Some(RestorePolicy::Replace) => ary.push_bulk_strings(Bytes::from("REPLACE")),
+            None => {}
+        }
+        ary
+    }
+}
+
+#[derive(Debug)]
+pub struct FunctionFlush {
+    mode: Option<FlushMode>,
+}
+
+impl FunctionFlush {
+    pub fn new(mode: Option<FlushMode>) -> Self {
+        FunctionFlush { mode }
+    }
+}
+
+impl From<FunctionFlush> for Resp {
+    fn from(flush: FunctionFlush) -> Self {
+        let mut ary = Resp::array();
+        ary.push_bulk_strings(Bytes::from("FUNCTION"));
+        ary.push_bulk_strings(Bytes::from("FLUSH"));
+        if let Some(mode) = flush.mode {
+            ary.push_bulk_strings(Bytes::from(mode.as_str()));
+        }
+        ary
+    }
+}
+
+// FCALL, or FCALL_RO when readonly is true
+#[derive(Debug)]
+pub struct FCall {
+    function: String,
+    keys: Vec<String>,
+    args: Vec<Bytes>,
+    readonly: bool,
+}
+
+impl FCall {
+    pub fn new(function: impl ToString, keys: &[&str], args: &[Bytes], readonly: bool) -> Self {
+        FCall {
+            function: function.to_string(),
+            keys: keys.iter().map(|k| k.to_string()).collect(),
+            args: args.to_vec(),
+            readonly,
+        }
+    }
+}
+
+impl From<FCall> for Resp {
+    fn from(fcall: FCall) -> Self {
+        let mut ary = Resp::array();
+        if fcall.readonly {
+            ary.push_bulk_strings(Bytes::from("FCALL_RO"));
+        } else {
+            ary.push_bulk_strings(Bytes::from("FCALL"));
+        }
+        ary.push_bulk_strings(Bytes::from(fcall.function.into_bytes()));
+        ary.push_bulk_strings(Bytes::from(fcall.keys.len().to_string()));
+        for key in fcall.keys {
+            ary.push_bulk_strings(Bytes::from(key.into_bytes()));
+        }
+        for arg in fcall.args {
+            ary.push_bulk_strings(arg);
+        }
+        ary
+    }
+}
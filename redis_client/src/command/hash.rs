@@ -0,0 +1,280 @@
+use crate::resp::Resp;
+use bytes::Bytes;
+
+#[derive(Debug)]
+pub struct HSet {
+    key: String,
+    pairs: Vec<(String, Bytes)>,
+    nx: bool, // HSETNX if true
+}
+
+impl HSet {
+    pub fn new(key: impl ToString, pairs: &[(&str, Bytes)]) -> Self {
+        HSet {
+            key: key.to_string(),
+            pairs: pairs
+                .iter()
+                .map(|(f, v)| (f.to_string(), v.clone()))
+                .collect(),
+            nx: false,
+        }
+    }
+
+    pub fn new_nx(key: impl ToString, field: impl ToString, value: Bytes) -> Self {
+        HSet {
+            key: key.to_string(),
+            pairs: vec![(field.to_string(), value)],
+            nx: true,
+        }
+    }
+}
+
+impl From<HSet> for Resp {
+    fn from(hset: HSet) -> Self {
+        let mut ary = Resp::array();
+        if hset.nx {
+            ary.push_bulk_strings(Bytes::from("HSETNX"));
+        } else {
+            ary.push_bulk_strings(Bytes::from("HSET"));
+        }
+        ary.push_bulk_strings(Bytes::from(hset.key.into_bytes()));
+        for (field, value) in hset.pairs {
+            ary.push_bulk_strings(Bytes::from(field.into_bytes()));
+            ary.push_bulk_strings(value);
+        }
+        ary
+    }
+}
+
+#[derive(Debug)]
+pub struct HGet {
+    key: String,
+    field: String,
+}
+
+impl HGet {
+    pub fn new(key: impl ToString, field: impl ToString) -> Self {
+        HGet {
+            key: key.to_string(),
+            field: field.to_string(),
+        }
+    }
+}
+
+impl From<HGet> for Resp {
+    fn from(hget: HGet) -> Self {
+        let mut ary = Resp::array();
+        ary.push_bulk_strings(Bytes::from("HGET"));
+        ary.push_bulk_strings(Bytes::from(hget.key.into_bytes()));
+        ary.push_bulk_strings(Bytes::from(hget.field.into_bytes()));
+        ary
+    }
+}
+
+#[derive(Debug)]
+pub struct HMGet {
+    key: String,
+    fields: Vec<String>,
+}
+
+impl HMGet {
+    pub fn new(key: impl ToString, fields: &[&str]) -> Self {
+        HMGet {
+            key: key.to_string(),
+            fields: fields.iter().map(|f| f.to_string()).collect(),
+        }
+    }
+}
+
+impl From<HMGet> for Resp {
+    fn from(hmget: HMGet) -> Self {
+        let mut ary = Resp::array();
+        ary.push_bulk_strings(Bytes::from("HMGET"));
+        ary.push_bulk_strings(Bytes::from(hmget.key.into_bytes()));
+        for field in hmget.fields {
+            ary.push_bulk_strings(Bytes::from(field.into_bytes()));
+        }
+        ary
+    }
+}
+
+#[derive(Debug)]
+pub struct HDel {
+    key: String,
+    fields: Vec<String>,
+}
+
+impl HDel {
+    pub fn new(key: impl ToString, fields: &[&str]) -> Self {
+        HDel {
+            key: key.to_string(),
+            fields: fields.iter().map(|f| f.to_string()).collect(),
+        }
+    }
+}
+
+impl From<HDel> for Resp {
+    fn from(hdel: HDel) -> Self {
+        let mut ary = Resp::array();
+        ary.push_bulk_strings(Bytes::from("HDEL"));
+        ary.push_bulk_strings(Bytes::from(hdel.key.into_bytes()));
+        for field in hdel.fields {
+            ary.push_bulk_strings(Bytes::from(field.into_bytes()));
+        }
+        ary
+    }
+}
+
+#[derive(Debug)]
+pub struct HExists {
+    key: String,
+    field: String,
+}
+
+impl HExists {
+    pub fn new(key: impl ToString, field: impl ToString) -> Self {
+        HExists {
+            key: key.to_string(),
+            field: field.to_string(),
+        }
+    }
+}
+
+impl From<HExists> for Resp {
+    fn from(hexists: HExists) -> Self {
+        let mut ary = Resp::array();
+        ary.push_bulk_strings(Bytes::from("HEXISTS"));
+        ary.push_bulk_strings(Bytes::from(hexists.key.into_bytes()));
+        ary.push_bulk_strings(Bytes::from(hexists.field.into_bytes()));
+        ary
+    }
+}
+
+#[derive(Debug)]
+pub struct HIncrBy {
+    key: String,
+    field: String,
+    increment: i64,
+}
+
+impl HIncrBy {
+    pub fn new(key: impl ToString, field: impl ToString, increment: i64) -> Self {
+        HIncrBy {
+            key: key.to_string(),
+            field: field.to_string(),
+            increment,
+        }
+    }
+}
+
+impl From<HIncrBy> for Resp {
+    fn from(hincrby: HIncrBy) -> Self {
+        let mut ary = Resp::array();
+        ary.push_bulk_strings(Bytes::from("HINCRBY"));
+        ary.push_bulk_strings(Bytes::from(hincrby.key.into_bytes()));
+        ary.push_bulk_strings(Bytes::from(hincrby.field.into_bytes()));
+        ary.push_bulk_strings(Bytes::from(hincrby.increment.to_string()));
+        ary
+    }
+}
+
+#[derive(Debug)]
+pub struct HIncrByFloat {
+    key: String,
+    field: String,
+    increment: f64,
+}
+
+impl HIncrByFloat {
+    pub fn new(key: impl ToString, field: impl ToString, increment: f64) -> Self {
+        HIncrByFloat {
+            key: key.to_string(),
+            field: field.to_string(),
+            increment,
+        }
+    }
+}
+
+impl From<HIncrByFloat> for Resp {
+    fn from(hincrbyfloat: HIncrByFloat) -> Self {
+        let mut ary = Resp::array();
+        ary.push_bulk_strings(Bytes::from("HINCRBYFLOAT"));
+        ary.push_bulk_strings(Bytes::from(hincrbyfloat.key.into_bytes()));
+        ary.push_bulk_strings(Bytes::from(hincrbyfloat.field.into_bytes()));
+        ary.push_bulk_strings(Bytes::from(hincrbyfloat.increment.to_string()));
+        ary
+    }
+}
+
+// HGETALL, HKEYS, HVALS and HLEN only take a key
+#[derive(Debug)]
+pub struct HashKey {
+    command: &'static str,
+    key: String,
+}
+
+impl HashKey {
+    pub fn getall(key: impl ToString) -> Self {
+        HashKey::new("HGETALL", key)
+    }
+
+    pub fn keys(key: impl ToString) -> Self {
+        HashKey::new("HKEYS", key)
+    }
+
+    pub fn vals(key: impl ToString) -> Self {
+        HashKey::new("HVALS", key)
+    }
+
+    pub fn len(key: impl ToString) -> Self {
+        HashKey::new("HLEN", key)
+    }
+
+    fn new(command: &'static str, key: impl ToString) -> Self {
+        HashKey {
+            command,
+            key: key.to_string(),
+        }
+    }
+}
+
+impl From<HashKey> for Resp {
+    fn from(hash: HashKey) -> Self {
+        let mut ary = Resp::array();
+        ary.push_bulk_strings(Bytes::from(hash.command));
+        ary.push_bulk_strings(Bytes::from(hash.key.into_bytes()));
+        ary
+    }
+}
+
+#[derive(Debug)]
+pub struct HRandField {
+    key: String,
+    count: Option<i64>,
+    withvalues: bool,
+}
+
+impl HRandField {
+    pub fn new(key: impl ToString, count: Option<i64>, withvalues: bool) -> Self {
+        HRandField {
+            key: key.to_string(),
+            count,
+            withvalues,
+        }
+    }
+}
+
+impl From<HRandField> for Resp {
+    fn from(hrandfield: HRandField) -> Self {
+        let mut ary = Resp::array();
+        ary.push_bulk_strings(Bytes::from("HRANDFIELD"));
+        ary.push_bulk_strings(Bytes::from(hrandfield.key.into_bytes()));
+        if let Some(count) = hrandfield.count {
+            ary.push_bulk_strings(Bytes::from(count.to_string()));
+            if hrandfield.withvalues {
+                ary.push_bulk_strings(Bytes::from("WITHVALUES"));
+            }
+        }
+        ary
+    }
+}
@@ -0,0 +1,381 @@
+use crate::resp::Resp;
+use bytes::Bytes;
+
+#[derive(Debug)]
+pub struct Del {
+    keys: Vec<String>,
+}
+
+impl Del {
+    pub fn new(keys: &[&str]) -> Self {
+        Del {
+            keys: keys.iter().map(|k| k.to_string()).collect(),
+        }
+    }
+}
+
+impl From<Del> for Resp {
+    fn from(del: Del) -> Self {
+        let mut ary = Resp::array();
+        ary.push_bulk_strings(Bytes::from("DEL"));
+        for key in del.keys {
+            ary.push_bulk_strings(Bytes::from(key.into_bytes()));
+        }
+        ary
+    }
+}
+
+#[derive(Debug)]
+pub struct Unlink {
+    keys: Vec<String>,
+}
+
+impl Unlink {
+    pub fn new(keys: &[&str]) -> Self {
+        Unlink {
+            keys: keys.iter().map(|k| k.to_string()).collect(),
+        }
+    }
+}
+
+impl From<Unlink> for Resp {
+    fn from(unlink: Unlink) -> Self {
+        let mut ary = Resp::array();
+        ary.push_bulk_strings(Bytes::from("UNLINK"));
+        for key in unlink.keys {
+            ary.push_bulk_strings(Bytes::from(key.into_bytes()));
+        }
+        ary
+    }
+}
+
+#[derive(Debug)]
+pub struct Exists {
+    keys: Vec<String>,
+}
+
+impl Exists {
+    pub fn new(keys: &[&str]) -> Self {
+        Exists {
+            keys: keys.iter().map(|k| k.to_string()).collect(),
+        }
+    }
+}
+
+impl From<Exists> for Resp {
+    fn from(exists: Exists) -> Self {
+        let mut ary = Resp::array();
+        ary.push_bulk_strings(Bytes::from("EXISTS"));
+        for key in exists.keys {
+            ary.push_bulk_strings(Bytes::from(key.into_bytes()));
+        }
+        ary
+    }
+}
+
+#[derive(Debug)]
+pub struct Touch {
+    keys: Vec<String>,
+}
+
+impl Touch {
+    pub fn new(keys: &[&str]) -> Self {
+        Touch {
+            keys: keys.iter().map(|k| k.to_string()).collect(),
+        }
+    }
+}
+
+impl From<Touch> for Resp {
+    fn from(touch: Touch) -> Self {
+        let mut ary = Resp::array();
+        ary.push_bulk_strings(Bytes::from("TOUCH"));
+        for key in touch.keys {
+            ary.push_bulk_strings(Bytes::from(key.into_bytes()));
+        }
+        ary
+    }
+}
+
+/// Condition flag of EXPIRE/PEXPIRE (Redis 7.0 or later).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExpireCondition {
+    /// Set expiry only when the key has no expiry
+    Nx,
+    /// Set expiry only when the key has an existing expiry
+    Xx,
+    /// Set expiry only when the new expiry is greater than current one
+    Gt,
+    /// Set expiry only when the new expiry is less than current one
+    Lt,
+}
+
+impl ExpireCondition {
+    fn as_str(&self) -> &'static str {
+        match self {
+            ExpireCondition::Nx => "NX",
+            ExpireCondition::Xx => "XX",
+            ExpireCondition::Gt => "GT",
+            ExpireCondition::Lt => "LT",
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct Expire {
+    key: String,
+    ttl: u64,
+    millis: bool, // PEXPIRE if true
+    condition: Option<ExpireCondition>,
+}
+
+impl Expire {
+    pub fn new(key: impl ToString, ttl: u64, condition: Option<ExpireCondition>) -> Self {
+        Expire {
+            key: key.to_string(),
+            ttl,
+            millis: false,
+            condition,
+        }
+    }
+
+    pub fn new_millis(key: impl ToString, ttl: u64, condition: Option<ExpireCondition>) -> Self {
+        Expire {
+            millis: true,
+            ..Expire::new(key, ttl, condition)
+        }
+    }
+}
+
+impl From<Expire> for Resp {
+    fn from(expire: Expire) -> Self {
+        let mut ary = Resp::array();
+        if expire.millis {
+            ary.push_bulk_strings(Bytes::from("PEXPIRE"));
+        } else {
+            ary.push_bulk_strings(Bytes::from("EXPIRE"));
+        }
+        ary.push_bulk_strings(Bytes::from(expire.key.into_bytes()));
+        ary.push_bulk_strings(Bytes::from(expire.ttl.to_string()));
+        if let Some(cond) = expire.condition {
+            ary.push_bulk_strings(Bytes::from(cond.as_str()));
+        }
+        ary
+    }
+}
+
+#[derive(Debug)]
+pub struct Ttl {
+    key: String,
+    millis: bool, // PTTL if true
+}
+
+impl Ttl {
+    pub fn new(key: impl ToString) -> Self {
+        Ttl {
+            key: key.to_string(),
+            millis: false,
+        }
+    }
+
+    pub fn new_millis(key: impl ToString) -> Self {
+        Ttl {
+            key: key.to_string(),
+            millis: true,
+        }
+    }
+}
+
+impl From<Ttl> for Resp {
+    fn from(ttl: Ttl) -> Self {
+        let mut ary = Resp::array();
+        if ttl.millis {
+            ary.push_bulk_strings(Bytes::from("PTTL"));
+        } else {
+            ary.push_bulk_strings(Bytes::from("TTL"));
+        }
+        ary.push_bulk_strings(Bytes::from(ttl.key.into_bytes()));
+        ary
+    }
+}
+
+#[derive(Debug)]
+pub struct Persist {
+    key: String,
+}
+
+impl Persist {
+    pub fn new(key: impl ToString) -> Self {
+        Persist {
+            key: key.to_string(),
+        }
+    }
+}
+
+impl From<Persist> for Resp {
+    fn from(persist: Persist) -> Self {
+        let mut ary = Resp::array();
+        ary.push_bulk_strings(Bytes::from("PERSIST"));
+        ary.push_bulk_strings(Bytes::from(persist.key.into_bytes()));
+        ary
+    }
+}
+
+#[derive(Debug)]
+pub struct Type {
+    key: String,
+}
+
+impl Type {
+    pub fn new(key: impl ToString) -> Self {
+        Type {
+            key: key.to_string(),
+        }
+    }
+}
+
+impl From<Type> for Resp {
+    fn from(typ: Type) -> Self {
+        let mut ary = Resp::array();
+        ary.push_bulk_strings(Bytes::from("TYPE"));
+        ary.push_bulk_strings(Bytes::from(typ.key.into_bytes()));
+        ary
+    }
+}
+
+#[derive(Debug)]
+pub struct Rename {
+    key: String,
+    newkey: String,
+    nx: bool, // RENAMENX if true
+}
+
+impl Rename {
+    pub fn new(key: impl ToString, newkey: impl ToString, nx: bool) -> Self {
+        Rename {
+            key: key.to_string(),
+            newkey: newkey.to_string(),
+            nx,
+        }
+    }
+}
+
+impl From<Rename> for Resp {
+    fn from(rename: Rename) -> Self {
+        let mut ary = Resp::array();
+        if rename.nx {
+            ary.push_bulk_strings(Bytes::from("RENAMENX"));
+        } else {
+            ary.push_bulk_strings(Bytes::from("RENAME"));
+        }
+        ary.push_bulk_strings(Bytes::from(rename.key.into_bytes()));
+        ary.push_bulk_strings(Bytes::from(rename.newkey.into_bytes()));
+        ary
+    }
+}
+
+#[derive(Debug)]
+pub struct Copy {
+    source: String,
+    destination: String,
+    db: Option<u64>,
+    replace: bool,
+}
+
+impl Copy {
+    pub fn new(
+        source: impl ToString,
+        destination: impl ToString,
+        db: Option<u64>,
+        replace: bool,
+    ) -> Self {
+        Copy {
+            source: source.to_string(),
+            destination: destination.to_string(),
+            db,
+            replace,
+        }
+    }
+}
+
+impl From<Copy> for Resp {
+    fn from(copy: Copy) -> Self {
+        let mut ary = Resp::array();
+        ary.push_bulk_strings(Bytes::from("COPY"));
+        ary.push_bulk_strings(Bytes::from(copy.source.into_bytes()));
+        ary.push_bulk_strings(Bytes::from(copy.destination.into_bytes()));
+        if let Some(db) = copy.db {
+            ary.push_bulk_strings(Bytes::from("DB"));
+            ary.push_bulk_strings(Bytes::from(db.to_string()));
+        }
+        if copy.replace {
+            ary.push_bulk_strings(Bytes::from("REPLACE"));
+        }
+        ary
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cmd;
+
+    #[test]
+    fn multi_key_commands() {
+        assert_eq!(cmd!["DEL", "a", "b"], Resp::from(Del::new(&["a", "b"])));
+        assert_eq!(cmd!["UNLINK", "a"], Resp::from(Unlink::new(&["a"])));
+        assert_eq!(
+            cmd!["EXISTS", "a", "b"],
+            Resp::from(Exists::new(&["a", "b"]))
+        );
+        assert_eq!(cmd!["TOUCH", "a"], Resp::from(Touch::new(&["a"])));
+    }
+
+    #[test]
+    fn expire_options() {
+        assert_eq!(
+            cmd!["EXPIRE", "k", "10"],
+            Resp::from(Expire::new("k", 10, None))
+        );
+        assert_eq!(
+            cmd!["EXPIRE", "k", "10", "NX"],
+            Resp::from(Expire::new("k", 10, Some(ExpireCondition::Nx)))
+        );
+        assert_eq!(
+            cmd!["PEXPIRE", "k", "1500", "GT"],
+            Resp::from(Expire::new_millis("k", 1500, Some(ExpireCondition::Gt)))
+        );
+        assert_eq!(
+            cmd!["PEXPIRE", "k", "1500", "LT"],
+            Resp::from(Expire::new_millis("k", 1500, Some(ExpireCondition::Lt)))
+        );
+        assert_eq!(
+            cmd!["EXPIRE", "k", "0", "XX"],
+            Resp::from(Expire::new("k", 0, Some(ExpireCondition::Xx)))
+        );
+        assert_eq!(cmd!["TTL", "k"], Resp::from(Ttl::new("k")));
+        assert_eq!(cmd!["PTTL", "k"], Resp::from(Ttl::new_millis("k")));
+        assert_eq!(cmd!["PERSIST", "k"], Resp::from(Persist::new("k")));
+    }
+
+    #[test]
+    fn rename_and_copy() {
+        assert_eq!(cmd!["TYPE", "k"], Resp::from(Type::new("k")));
+        assert_eq!(
+            cmd!["RENAME", "a", "b"],
+            Resp::from(Rename::new("a", "b", false))
+        );
+        assert_eq!(
+            cmd!["RENAMENX", "a", "b"],
+            Resp::from(Rename::new("a", "b", true))
+        );
+        assert_eq!(
+            cmd!["COPY", "a", "b"],
+            Resp::from(Copy::new("a", "b", None, false))
+        );
+        assert_eq!(
+            cmd!["COPY", "a", "b", "DB", "2", "REPLACE"],
+            Resp::from(Copy::new("a", "b", Some(2), true))
+        );
+    }
+}
@@ -0,0 +1,499 @@
+use super::timeout_arg;
+use crate::resp::Resp;
+use bytes::Bytes;
+use std::time::Duration;
+
+/// Which end of a list to operate on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    Left,
+    Right,
+}
+
+impl Direction {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Direction::Left => "LEFT",
+            Direction::Right => "RIGHT",
+        }
+    }
+}
+
+/// Where LINSERT puts the element relative to the pivot.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InsertPosition {
+    Before,
+    After,
+}
+
+// LPUSH and RPUSH
+#[derive(Debug)]
+pub struct Push {
+    direction: Direction,
+    key: String,
+    values: Vec<Bytes>,
+}
+
+impl Push {
+    pub fn new(direction: Direction, key: impl ToString, values: &[Bytes]) -> Self {
+        Push {
+            direction,
+            key: key.to_string(),
+            values: values.to_vec(),
+        }
+    }
+}
+
+impl From<Push> for Resp {
+    fn from(push: Push) -> Self {
+        let mut ary = Resp::array();
+        match push.direction {
+            Direction::Left => ary.push_bulk_strings(Bytes::from("LPUSH")),
+            Direction::Right => ary.push_bulk_strings(Bytes::from("RPUSH")),
+        }
+        ary.push_bulk_strings(Bytes::from(push.key.into_bytes()));
+        for value in push.values {
+            ary.push_bulk_strings(value);
+        }
+        ary
+    }
+}
+
+// LPOP and RPOP
+#[derive(Debug)]
+pub struct Pop {
+    direction: Direction,
+    key: String,
+    count: Option<u64>,
+}
+
+impl Pop {
+    pub fn new(direction: Direction, key: impl ToString, count: Option<u64>) -> Self {
+        Pop {
+            direction,
+            key: key.to_string(),
+            count,
+        }
+    }
+}
+
+impl From<Pop> for Resp {
+    fn from(pop: Pop) -> Self {
+        let mut ary = Resp::array();
+        match pop.direction {
+            Direction::Left => ary.push_bulk_strings(Bytes::from("LPOP")),
+            Direction::Right => ary.push_bulk_strings(Bytes::from("RPOP")),
+        }
+        ary.push_bulk_strings(Bytes::from(pop.key.into_bytes()));
+        if let Some(count) = pop.count {
+            ary.push_bulk_strings(Bytes::from(count.to_string()));
+        }
+        ary
+    }
+}
+
+// BLPOP and BRPOP
+#[derive(Debug)]
+pub struct BPop {
+    direction: Direction,
+    keys: Vec<String>,
+    timeout: Duration,
+}
+
+impl BPop {
+    pub fn new(direction: Direction, keys: &[&str], timeout: Duration) -> Self {
+        BPop {
+            direction,
+            keys: keys.iter().map(|k| k.to_string()).collect(),
+            timeout,
+        }
+    }
+}
+
+impl From<BPop> for Resp {
+    fn from(bpop: BPop) -> Self {
+        let mut ary = Resp::array();
+        match bpop.direction {
+            Direction::Left => ary.push_bulk_strings(Bytes::from("BLPOP")),
+            Direction::Right => ary.push_bulk_strings(Bytes::from("BRPOP")),
+        }
+        for key in bpop.keys {
+            ary.push_bulk_strings(Bytes::from(key.into_bytes()));
+        }
+        ary.push_bulk_strings(timeout_arg(bpop.timeout));
+        ary
+    }
+}
+
+#[derive(Debug)]
+pub struct BLMPop {
+    keys: Vec<String>,
+    direction: Direction,
+    count: Option<u64>,
+    timeout: Duration,
+}
+
+impl BLMPop {
+    pub fn new(keys: &[&str], direction: Direction, count: Option<u64>, timeout: Duration) -> Self {
+        BLMPop {
+            keys: keys.iter().map(|k| k.to_string()).collect(),
+            direction,
+            count,
+            timeout,
+        }
+    }
+}
+
+impl From<BLMPop> for Resp {
+    fn from(blmpop: BLMPop) -> Self {
+        let mut ary = Resp::array();
+        ary.push_bulk_strings(Bytes::from("BLMPOP"));
+        ary.push_bulk_strings(timeout_arg(blmpop.timeout));
+        ary.push_bulk_strings(Bytes::from(blmpop.keys.len().to_string()));
+        for key in blmpop.keys {
+            ary.push_bulk_strings(Bytes::from(key.into_bytes()));
+        }
+        ary.push_bulk_strings(Bytes::from(blmpop.direction.as_str()));
+        if let Some(count) = blmpop.count {
+            ary.push_bulk_strings(Bytes::from("COUNT"));
+            ary.push_bulk_strings(Bytes::from(count.to_string()));
+        }
+        ary
+    }
+}
+
+// LRANGE and LTRIM
+#[derive(Debug)]
+pub struct ListRange {
+    command: &'static str,
+    key: String,
+    start: i64,
+    stop: i64,
+}
+
+impl ListRange {
+    pub fn range(key: impl ToString, start: i64, stop: i64) -> Self {
+        ListRange {
+            command: "LRANGE",
+            key: key.to_string(),
+            start,
+            stop,
+        }
+    }
+
+    pub fn trim(key: impl ToString, start: i64, stop: i64) -> Self {
+        ListRange {
+            command: "LTRIM",
+            ..ListRange::range(key, start, stop)
+        }
+    }
+}
+
+impl From<ListRange> for Resp {
+    fn from(range: ListRange) -> Self {
+        let mut ary = Resp::array();
+        ary.push_bulk_strings(Bytes::from(range.command));
+        ary.push_bulk_strings(Bytes::from(range.key.into_bytes()));
+        ary.push_bulk_strings(Bytes::from(range.start.to_string()));
+        ary.push_bulk_strings(Bytes::from(range.stop.to_string()));
+        ary
+    }
+}
+
+#[derive(Debug)]
+pub struct LLen {
+    key: String,
+}
+
+impl LLen {
+    pub fn new(key: impl ToString) -> Self {
+        LLen {
+            key: key.to_string(),
+        }
+    }
+}
+
+impl From<LLen> for Resp {
+    fn from(llen: LLen) -> Self {
+        let mut ary = Resp::array();
+        ary.push_bulk_strings(Bytes::from("LLEN"));
+        ary.push_bulk_strings(Bytes::from(llen.key.into_bytes()));
+        ary
+    }
+}
+
+#[derive(Debug)]
+pub struct LIndex {
+    key: String,
+    index: i64,
+}
+
+impl LIndex {
+    pub fn new(key: impl ToString, index: i64) -> Self {
+        LIndex {
+            key: key.to_string(),
+            index,
+        }
+    }
+}
+
+impl From<LIndex> for Resp {
+    fn from(lindex: LIndex) -> Self {
+        let mut ary = Resp::array();
+        ary.push_bulk_strings(Bytes::from("LINDEX"));
+        ary.push_bulk_strings(Bytes::from(lindex.key.into_bytes()));
+        ary.push_bulk_strings(Bytes::from(lindex.index.to_string()));
+        ary
+    }
+}
+
+#[derive(Debug)]
+pub struct LSet {
+    key: String,
+    index: i64,
+    value: Bytes,
+}
+
+impl LSet {
+    pub fn new(key: impl ToString, index: i64, value: Bytes) -> Self {
+        LSet {
+            key: key.to_string(),
+            index,
+            value,
+        }
+    }
+}
+
+impl From<LSet> for Resp {
+    fn from(lset: LSet) -> Self {
+        let mut ary = Resp::array();
+        ary.push_bulk_strings(Bytes::from("LSET"));
+        ary.push_bulk_strings(Bytes::from(lset.key.into_bytes()));
+        ary.push_bulk_strings(Bytes::from(lset.index.to_string()));
+        ary.push_bulk_strings(lset.value);
+        ary
+    }
+}
+
+#[derive(Debug)]
+pub struct LRem {
+    key: String,
+    count: i64,
+    value: Bytes,
+}
+
+impl LRem {
+    pub fn new(key: impl ToString, count: i64, value: Bytes) -> Self {
+        LRem {
+            key: key.to_string(),
+            count,
+            value,
+        }
+    }
+}
+
+impl From<LRem> for Resp {
+    fn from(lrem: LRem) -> Self {
+        let mut ary = Resp::array();
+        ary.push_bulk_strings(Bytes::from("LREM"));
+        ary.push_bulk_strings(Bytes::from(lrem.key.into_bytes()));
+        ary.push_bulk_strings(Bytes::from(lrem.count.to_string()));
+        ary.push_bulk_strings(lrem.value);
+        ary
+    }
+}
+
+#[derive(Debug)]
+pub struct LInsert {
+    key: String,
+    position: InsertPosition,
+    pivot: Bytes,
+    value: Bytes,
+}
+
+impl LInsert {
+    pub fn new(key: impl ToString, position: InsertPosition, pivot: Bytes, value: Bytes) -> Self {
+        LInsert {
+            key: key.to_string(),
+            position,
+            pivot,
+            value,
+        }
+    }
+}
+
+impl From<LInsert> for Resp {
+    fn from(linsert: LInsert) -> Self {
+        let mut ary = Resp::array();
+        ary.push_bulk_strings(Bytes::from("LINSERT"));
+        ary.push_bulk_strings(Bytes::from(linsert.key.into_bytes()));
+        match linsert.position {
+            InsertPosition::Before => ary.push_bulk_strings(Bytes::from("BEFORE")),
+            InsertPosition::After => ary.push_bulk_strings(Bytes::from("AFTER")),
+        }
+        ary.push_bulk_strings(linsert.pivot);
+        ary.push_bulk_strings(linsert.value);
+        ary
+    }
+}
+
+#[derive(Debug)]
+pub struct LPos {
+    key: String,
+    element: Bytes,
+    rank: Option<i64>,
+    count: Option<u64>,
+    maxlen: Option<u64>,
+}
+
+impl LPos {
+    pub fn new(
+        key: impl ToString,
+        element: Bytes,
+        rank: Option<i64>,
+        count: Option<u64>,
+        maxlen: Option<u64>,
+    ) -> Self {
+        LPos {
+            key: key.to_string(),
+            element,
+            rank,
+            count,
+            maxlen,
+        }
+    }
+}
+
+impl From<LPos> for Resp {
+    fn from(lpos: LPos) -> Self {
+        let mut ary = Resp::array();
+        ary.push_bulk_strings(Bytes::from("LPOS"));
+        ary.push_bulk_strings(Bytes::from(lpos.key.into_bytes()));
+        ary.push_bulk_strings(lpos.element);
+        if let Some(rank) = lpos.rank {
+            ary.push_bulk_strings(Bytes::from("RANK"));
+            ary.push_bulk_strings(Bytes::from(rank.to_string()));
+        }
+        if let Some(count) = lpos.count {
+            ary.push_bulk_strings(Bytes::from("COUNT"));
+            ary.push_bulk_strings(Bytes::from(count.to_string()));
+        }
+        if let Some(maxlen) = lpos.maxlen {
+            ary.push_bulk_strings(Bytes::from("MAXLEN"));
+            ary.push_bulk_strings(Bytes::from(maxlen.to_string()));
+        }
+        ary
+    }
+}
+
+// LMOVE, or BLMOVE when timeout is given
+#[derive(Debug)]
+pub struct LMove {
+    source: String,
+    destination: String,
+    wherefrom: Direction,
+    whereto: Direction,
+    timeout: Option<Duration>,
+}
+
+impl LMove {
+    pub fn new(
+        source: impl ToString,
+        destination: impl ToString,
+        wherefrom: Direction,
+        whereto: Direction,
+        timeout: Option<Duration>,
+    ) -> Self {
+        LMove {
+            source: source.to_string(),
+            destination: destination.to_string(),
+            wherefrom,
+            whereto,
+            timeout,
+        }
+    }
+}
+
+impl From<LMove> for Resp {
+    fn from(lmove: LMove) -> Self {
+        let mut ary = Resp::array();
+        if lmove.timeout.is_some() {
+            ary.push_bulk_strings(Bytes::from("BLMOVE"));
+        } else {
+            ary.push_bulk_strings(Bytes::from("LMOVE"));
+        }
+        ary.push_bulk_strings(Bytes::from(lmove.source.into_bytes()));
+        ary.push_bulk_strings(Bytes::from(lmove.destination.into_bytes()));
+        ary.push_bulk_strings(Bytes::from(lmove.wherefrom.as_str()));
+        ary.push_bulk_strings(Bytes::from(lmove.whereto.as_str()));
+        if let Some(timeout) = lmove.timeout {
+            ary.push_bulk_strings(timeout_arg(timeout));
+        }
+        ary
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cmd;
+
+    #[test]
+    fn timeout_seconds() {
+        assert_eq!(Bytes::from("0"), timeout_arg(Duration::ZERO));
+        assert_eq!(Bytes::from("2"), timeout_arg(Duration::from_secs(2)));
+        assert_eq!(Bytes::from("0.25"), timeout_arg(Duration::from_millis(250)));
+        assert_eq!(Bytes::from("0.001"), timeout_arg(Duration::from_nanos(1)));
+        assert_eq!(
+            Bytes::from("1.001"),
+            timeout_arg(Duration::from_micros(1_000_500))
+        );
+
+        let brpop = BPop::new(Direction::Right, &["a", "b"], Duration::from_millis(1500));
+        assert_eq!(cmd!["BRPOP", "a", "b", "1.5"], Resp::from(brpop));
+    }
+
+    #[test]
+    fn lpos_options() {
+        let lpos = LPos::new("l", Bytes::from("x"), None, None, None);
+        assert_eq!(cmd!["LPOS", "l", "x"], Resp::from(lpos));
+
+        let lpos = LPos::new("l", Bytes::from("x"), Some(-2), Some(0), Some(100));
+        assert_eq!(
+            cmd!["LPOS", "l", "x", "RANK", "-2", "COUNT", "0", "MAXLEN", "100"],
+            Resp::from(lpos)
+        );
+    }
+
+    #[test]
+    fn lmove_directions() {
+        let lmove = LMove::new("src", "dst", Direction::Left, Direction::Right, None);
+        assert_eq!(
+            cmd!["LMOVE", "src", "dst", "LEFT", "RIGHT"],
+            Resp::from(lmove)
+        );
+
+        let timeout = Some(Duration::from_secs(5));
+        let blmove = LMove::new("src", "dst", Direction::Right, Direction::Left, timeout);
+        assert_eq!(
+            cmd!["BLMOVE", "src", "dst", "RIGHT", "LEFT", "5"],
+            Resp::from(blmove)
+        );
+    }
+
+    #[test]
+    fn blmpop_count() {
+        let timeout = Duration::from_millis(100);
+        let blmpop = BLMPop::new(&["a", "b"], Direction::Left, None, timeout);
+        assert_eq!(
+            cmd!["BLMPOP", "0.1", "2", "a", "b", "LEFT"],
+            Resp::from(blmpop)
+        );
+
+        let blmpop = BLMPop::new(&["a"], Direction::Right, Some(3), timeout);
+        assert_eq!(
+            cmd!["BLMPOP", "0.1", "1", "a", "RIGHT", "COUNT", "3"],
+            Resp::from(blmpop)
+        );
+    }
+}
@@ -1,15 +1,65 @@
+use bytes::Bytes;
+use std::time::Duration;
+
+mod client;
+mod config;
 mod decr;
+mod function;
 mod get;
+mod hash;
 mod incr;
+mod keys;
+mod list;
 mod ping;
 mod publish;
+mod scan;
+mod script;
+mod server;
 mod set;
+mod sets;
+mod stream;
 mod subscribe;
+mod zset;
 
+pub use client::{Auth, ClientId, ClientTracking, Select};
+pub use config::{ConfigGet, ConfigSet};
 pub use decr::Decr;
+pub use function::{
+    FCall, FunctionDelete, FunctionDump, FunctionFlush, FunctionList, FunctionLoad, FunctionRestore,
+};
 pub use get::Get;
+pub use hash::{HDel, HExists, HGet, HIncrBy, HIncrByFloat, HMGet, HRandField, HSet, HashKey};
 pub use incr::Incr;
+pub use keys::{
+    Copy, Del, Exists, Expire, ExpireCondition, Persist, Rename, Touch, Ttl, Type, Unlink,
+};
+pub use list::{
+    BLMPop, BPop, Direction, InsertPosition, LIndex, LInsert, LLen, LMove, LPos, LRem, LSet,
+    ListRange, Pop, Push,
+};
 pub use ping::Ping;
 pub use publish::Publish;
-pub use set::Set;
+pub use scan::Scan;
+pub use script::{Eval, FlushMode, ScriptExists, ScriptFlush, ScriptLoad};
+pub use server::{Info, Latency, Memory, Monitor, Slowlog};
+pub use set::{Set, SetOptions};
+pub use sets::{SIsMember, SetKey, SetMembers, SetOp, SetOperation};
+pub use stream::{
+    XAck, XAdd, XAutoClaim, XClaim, XDel, XGroup, XInfo, XLen, XPending, XRange, XRead, XTrim,
+};
 pub use subscribe::Subscribe;
+pub use zset::{
+    Aggregate, BZPop, ZAdd, ZAddOptions, ZCount, ZIncrBy, ZMember, ZPop, ZRange, ZRangeCommand,
+    ZStore,
+};
+
+// Blocking commands take their timeout in seconds, and 0 blocks forever
+fn timeout_arg(timeout: Duration) -> Bytes {
+    Bytes::from((timeout_millis(timeout) as f64 / 1000.0).to_string())
+}
+
+// The server truncates timeouts to milliseconds: a shorter non-zero timeout
+// is rounded up so that it does not become 0, blocking forever
+fn timeout_millis(timeout: Duration) -> u128 {
+    timeout.as_nanos().div_ceil(1_000_000)
+}
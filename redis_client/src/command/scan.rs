@@ -0,0 +1,75 @@
+use crate::resp::Resp;
+use bytes::Bytes;
+
+#[derive(Debug, Clone)]
+pub struct Scan {
+    command: &'static str,
+    key: Option<String>, // None for SCAN
+    cursor: u64,
+    pattern: Option<String>,
+    count: Option<u64>,
+    typ: Option<String>,
+}
+
+impl Scan {
+    pub fn new(pattern: Option<&str>, count: Option<u64>, typ: Option<&str>) -> Self {
+        Scan {
+            command: "SCAN",
+            key: None,
+            cursor: 0,
+            pattern: pattern.map(|v| v.to_string()),
+            count,
+            typ: typ.map(|v| v.to_string()),
+        }
+    }
+
+    pub fn hscan(key: impl ToString, pattern: Option<&str>, count: Option<u64>) -> Self {
+        Scan {
+            command: "HSCAN",
+            key: Some(key.to_string()),
+            ..Scan::new(pattern, count, None)
+        }
+    }
+
+    pub fn sscan(key: impl ToString, pattern: Option<&str>, count: Option<u64>) -> Self {
+        Scan {
+            command: "SSCAN",
+            ..Scan::hscan(key, pattern, count)
+        }
+    }
+
+    pub fn zscan(key: impl ToString, pattern: Option<&str>, count: Option<u64>) -> Self {
+        Scan {
+            command: "ZSCAN",
+            ..Scan::hscan(key, pattern, count)
+        }
+    }
+
+    pub fn set_cursor(&mut self, cursor: u64) {
+        self.cursor = cursor;
+    }
+}
+
+impl From<Scan> for Resp {
+    fn from(scan: Scan) -> Self {
+        let mut ary = Resp::array();
+        ary.push_bulk_strings(Bytes::from(scan.command));
+        if let Some(key) = scan.key {
+            ary.push_bulk_strings(Bytes::from(key.into_bytes()));
+        }
+        ary.push_bulk_strings(Bytes::from(scan.cursor.to_string()));
+        if let Some(pattern) = scan.pattern {
+            ary.push_bulk_strings(Bytes::from("MATCH"));
+            ary.push_bulk_strings(Bytes::from(pattern.into_bytes()));
+        }
+        if let Some(count) = scan.count {
+            ary.push_bulk_strings(Bytes::from("COUNT"));
+            ary.push_bulk_strings(Bytes::from(count.to_string()));
+        }
+        if let Some(typ) = scan.typ {
+            ary.push_bulk_strings(Bytes::from("TYPE"));
+            ary.push_bulk_strings(Bytes::from(typ.into_bytes()));
+        }
+        ary
+    }
+}
@@ -0,0 +1,136 @@
+use crate::resp::Resp;
+use bytes::Bytes;
+
+// EVAL, or EVALSHA when sha is true
+#[derive(Debug)]
+pub struct Eval {
+    sha: bool,
+    script: String,
+    keys: Vec<String>,
+    args: Vec<Bytes>,
+}
+
+impl Eval {
+    pub fn new(script: impl ToString, keys: &[&str], args: &[Bytes]) -> Self {
+        Eval {
+            sha: false,
+            script: script.to_string(),
+            keys: keys.iter().map(|k| k.to_string()).collect(),
+            args: args.to_vec(),
+        }
+    }
+
+    pub fn new_sha(sha: impl ToString, keys: &[&str], args: &[Bytes]) -> Self {
+        Eval {
+            sha: true,
+            ..Eval::new(sha, keys, args)
+        }
+    }
+}
+
+impl From<Eval> for Resp {
+    fn from(eval: Eval) -> Self {
+        let mut ary = Resp::array();
+        if eval.sha {
+            ary.push_bulk_strings(Bytes::from("EVALSHA"));
+        } else {
+            ary.push_bulk_strings(Bytes::from("EVAL"));
+        }
+        ary.push_bulk_strings(Bytes::from(eval.script.into_bytes()));
+        ary.push_bulk_strings(Bytes::from(eval.keys.len().to_string()));
+        for key in eval.keys {
+            ary.push_bulk_strings(Bytes::from(key.into_bytes()));
+        }
+        for arg in eval.args {
+            ary.push_bulk_strings(arg);
+        }
+        ary
+    }
+}
+
+#[derive(Debug)]
+pub struct ScriptLoad {
+    script: String,
+}
+
+impl ScriptLoad {
+    pub fn new(script: impl ToString) -> Self {
+        ScriptLoad {
+            script: script.to_string(),
+        }
+    }
+}
+
+impl From<ScriptLoad> for Resp {
+    fn from(load: ScriptLoad) -> Self {
+        let mut ary = Resp::array();
+        ary.push_bulk_strings(Bytes::from("SCRIPT"));
+        ary.push_bulk_strings(Bytes::from("LOAD"));
+        ary.push_bulk_strings(Bytes::from(load.script.into_bytes()));
+        ary
+    }
+}
+
+#[derive(Debug)]
+pub struct ScriptExists {
+    shas: Vec<String>,
+}
+
+impl ScriptExists {
+    pub fn new(shas: &[&str]) -> Self {
+        ScriptExists {
+            shas: shas.iter().map(|s| s.to_string()).collect(),
+        }
+    }
+}
+
+impl From<ScriptExists> for Resp {
+    fn from(exists: ScriptExists) -> Self {
+        let mut ary = Resp::array();
+        ary.push_bulk_strings(Bytes::from("SCRIPT"));
+        ary.push_bulk_strings(Bytes::from("EXISTS"));
+        for sha in exists.shas {
+            ary.push_bulk_strings(Bytes::from(sha.into_bytes()));
+        }
+        ary
+    }
+}
+
+/// Flushing mode of SCRIPT FLUSH and FUNCTION FLUSH.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FlushMode {
+    Async,
+    Sync,
+}
+
+impl FlushMode {
+    pub(crate) fn as_str(&self) -> &'static str {
+        match self {
+            FlushMode::Async => "ASYNC",
+            FlushMode::Sync => "SYNC",
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct ScriptFlush {
+    mode: Option<FlushMode>,
+}
+
+impl ScriptFlush {
+    pub fn new(mode: Option<FlushMode>) -> Self {
+        ScriptFlush { mode }
+    }
+}
+
+impl From<ScriptFlush> for Resp {
+    fn from(flush: ScriptFlush) -> Self {
+        let mut ary = Resp::array();
+        ary.push_bulk_strings(Bytes::from("SCRIPT"));
+        ary.push_bulk_strings(Bytes::from("FLUSH"));
+        if let Some(mode) = flush.mode {
+            ary.push_bulk_strings(Bytes::from(mode.as_str()));
+        }
+        ary
+    }
+}
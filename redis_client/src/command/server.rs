@@ -0,0 +1,159 @@
+use crate::resp::Resp;
+use bytes::Bytes;
+
+#[derive(Debug)]
+pub struct Monitor {}
+
+impl Monitor {
+    pub fn new() -> Self {
+        Monitor {}
+    }
+}
+
+impl From<Monitor> for Resp {
+    fn from(_monitor: Monitor) -> Self {
+        let mut ary = Resp::array();
+        ary.push_bulk_strings(Bytes::from("MONITOR"));
+        ary
+    }
+}
+
+#[derive(Debug)]
+pub struct Info {
+    section: Option<String>,
+}
+
+impl Info {
+    pub fn new(section: Option<&str>) -> Self {
+        Info {
+            section: section.map(str::to_string),
+        }
+    }
+}
+
+impl From<Info> for Resp {
+    fn from(info: Info) -> Self {
+        let mut ary = Resp::array();
+        ary.push_bulk_strings(Bytes::from("INFO"));
+        if let Some(section) = info.section {
+            ary.push_bulk_strings(Bytes::from(section.into_bytes()));
+        }
+        ary
+    }
+}
+
+#[derive(Debug)]
+pub struct Slowlog {
+    subcommand: &'static str,
+    count: Option<i64>,
+}
+
+impl Slowlog {
+    pub fn get(count: Option<i64>) -> Self {
+        Slowlog {
+            subcommand: "GET",
+            count,
+        }
+    }
+
+    pub fn len() -> Self {
+        Slowlog {
+            subcommand: "LEN",
+            count: None,
+        }
+    }
+
+    pub fn reset() -> Self {
+        Slowlog {
+            subcommand: "RESET",
+            count: None,
+        }
+    }
+}
+
+impl From<Slowlog> for Resp {
+    fn from(slowlog: Slowlog) -> Self {
+        let mut ary = Resp::array();
+        ary.push_bulk_strings(Bytes::from("SLOWLOG"));
+        ary.push_bulk_strings(Bytes::from(slowlog.subcommand));
+        if let Some(count) = slowlog.count {
+            ary.push_bulk_strings(Bytes::from(count.to_string()));
+        }
+        ary
+    }
+}
+
+#[derive(Debug)]
+pub struct Latency {
+    subcommand: &'static str,
+    event: Option<String>,
+}
+
+impl Latency {
+    pub fn latest() -> Self {
+        Latency {
+            subcommand: "LATEST",
+            event: None,
+        }
+    }
+
+    pub fn history(event: impl ToString) -> Self {
+        Latency {
+            subcommand: "HISTORY",
+            event: Some(event.to_string()),
+        }
+    }
+}
+
+impl From<Latency> for Resp {
+    fn from(latency: Latency) -> Self {
+        let mut ary = Resp::array();
+        ary.push_bulk_strings(Bytes::from("LATENCY"));
+        ary.push_bulk_strings(Bytes::from(latency.subcommand));
+        if let Some(event) = latency.event {
+            ary.push_bulk_strings(Bytes::from(event.into_bytes()));
+        }
+        ary
+    }
+}
+
+#[derive(Debug)]
+pub struct Memory {
+    subcommand: &'static str,
+    key: Option<String>,
+    samples: Option<u64>,
+}
+
+impl Memory {
+    pub fn usage(key: impl ToString, samples: Option<u64>) -> Self {
+        Memory {
+            subcommand: "USAGE",
+            key: Some(key.to_string()),
+            samples,
+        }
+    }
+
+    pub fn stats() -> Self {
+        Memory {
+            subcommand: "STATS",
+            key: None,
+            samples: None,
+        }
+    }
+}
+
+impl From<Memory> for Resp {
+    fn from(memory: Memory) -> Self {
+        let mut ary = Resp::array();
+        ary.push_bulk_strings(Bytes::from("MEMORY"));
+        ary.push_bulk_strings(Bytes::from(memory.subcommand));
+        if let Some(key) = memory.key {
+            ary.push_bulk_strings(Bytes::from(key.into_bytes()));
+        }
+        if let Some(samples) = memory.samples {
+            ary.push_bulk_strings(Bytes::from("SAMPLES"));
+            ary.push_bulk_strings(Bytes::from(samples.to_string()));
+        }
+        ary
+    }
+}
@@ -1,17 +1,37 @@
 use crate::resp::Resp;
 use bytes::Bytes;
+use std::time::Duration;
+
+/// Options of SET.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SetOptions {
+    /// NX: only set the key if it does not exist
+    pub nx: bool,
+    /// XX: only set the key if it already exists
+    pub xx: bool,
+    /// PX: expire the key after this duration (in milliseconds precision)
+    pub expire: Option<Duration>,
+    /// KEEPTTL: retain the time to live associated with the key
+    pub keepttl: bool,
+}
 
 #[derive(Debug)]
 pub struct Set {
     key: String,
     value: Bytes,
+    options: SetOptions,
 }
 
 impl Set {
     pub fn new(key: impl ToString, value: Bytes) -> Self {
+        Set::new_with_options(key, value, SetOptions::default())
+    }
+
+    pub fn new_with_options(key: impl ToString, value: Bytes, options: SetOptions) -> Self {
         Set {
             key: key.to_string(),
             value,
+            options,
         }
     }
 }
@@ -22,6 +42,19 @@ impl From<Set> for Resp {
         ary.push_bulk_strings(Bytes::from("SET".as_bytes()));
         ary.push_bulk_strings(Bytes::from(set.key.into_bytes()));
         ary.push_bulk_strings(set.value);
+        if set.options.nx {
+            ary.push_bulk_strings(Bytes::from("NX"));
+        }
+        if set.options.xx {
+            ary.push_bulk_strings(Bytes::from("XX"));
+        }
+        if let Some(expire) = set.options.expire {
+            ary.push_bulk_strings(Bytes::from("PX"));
+            ary.push_bulk_strings(Bytes::from(expire.as_millis().to_string()));
+        }
+        if set.options.keepttl {
+            ary.push_bulk_strings(Bytes::from("KEEPTTL"));
+        }
         ary
     }
 }
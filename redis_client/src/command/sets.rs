@@ -0,0 +1,205 @@
+use crate::resp::Resp;
+use bytes::Bytes;
+
+// SADD, SREM and SMISMEMBER
+#[derive(Debug)]
+pub struct SetMembers {
+    command: &'static str,
+    key: String,
+    members: Vec<Bytes>,
+}
+
+impl SetMembers {
+    pub fn add(key: impl ToString, members: &[Bytes]) -> Self {
+        SetMembers::new("SADD", key, members)
+    }
+
+    pub fn rem(key: impl ToString, members: &[Bytes]) -> Self {
+        SetMembers::new("SREM", key, members)
+    }
+
+    pub fn mismember(key: impl ToString, members: &[Bytes]) -> Self {
+        SetMembers::new("SMISMEMBER", key, members)
+    }
+
+    fn new(command: &'static str, key: impl ToString, members: &[Bytes]) -> Self {
+        SetMembers {
+            command,
+            key: key.to_string(),
+            members: members.to_vec(),
+        }
+    }
+}
+
+impl From<SetMembers> for Resp {
+    fn from(set: SetMembers) -> Self {
+        let mut ary = Resp::array();
+        ary.push_bulk_strings(Bytes::from(set.command));
+        ary.push_bulk_strings(Bytes::from(set.key.into_bytes()));
+        for member in set.members {
+            ary.push_bulk_strings(member);
+        }
+        ary
+    }
+}
+
+#[derive(Debug)]
+pub struct SIsMember {
+    key: String,
+    member: Bytes,
+}
+
+impl SIsMember {
+    pub fn new(key: impl ToString, member: Bytes) -> Self {
+        SIsMember {
+            key: key.to_string(),
+            member,
+        }
+    }
+}
+
+impl From<SIsMember> for Resp {
+    fn from(sismember: SIsMember) -> Self {
+        let mut ary = Resp::array();
+        ary.push_bulk_strings(Bytes::from("SISMEMBER"));
+        ary.push_bulk_strings(Bytes::from(sismember.key.into_bytes()));
+        ary.push_bulk_strings(sismember.member);
+        ary
+    }
+}
+
+// SMEMBERS and SCARD
+#[derive(Debug)]
+pub struct SetKey {
+    command: &'static str,
+    key: String,
+}
+
+impl SetKey {
+    pub fn members(key: impl ToString) -> Self {
+        SetKey {
+            command: "SMEMBERS",
+            key: key.to_string(),
+        }
+    }
+
+    pub fn card(key: impl ToString) -> Self {
+        SetKey {
+            command: "SCARD",
+            key: key.to_string(),
+        }
+    }
+}
+
+impl From<SetKey> for Resp {
+    fn from(set: SetKey) -> Self {
+        let mut ary = Resp::array();
+        ary.push_bulk_strings(Bytes::from(set.command));
+        ary.push_bulk_strings(Bytes::from(set.key.into_bytes()));
+        ary
+    }
+}
+
+/// Set operations of SINTER, SUNION and SDIFF.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SetOperation {
+    Inter,
+    Union,
+    Diff,
+}
+
+// SINTER, SUNION, SDIFF, or their STORE variants when destination is given
+#[derive(Debug)]
+pub struct SetOp {
+    operation: SetOperation,
+    destination: Option<String>,
+    keys: Vec<String>,
+}
+
+impl SetOp {
+    pub fn new(operation: SetOperation, keys: &[&str]) -> Self {
+        SetOp {
+            operation,
+            destination: None,
+            keys: keys.iter().map(|k| k.to_string()).collect(),
+        }
+    }
+
+    pub fn store(operation: SetOperation, destination: impl ToString, keys: &[&str]) -> Self {
+        SetOp {
+            destination: Some(destination.to_string()),
+            ..SetOp::new(operation, keys)
+        }
+    }
+}
+
+impl From<SetOp> for Resp {
+    fn from(op: SetOp) -> Self {
+        let name = match op.operation {
+            SetOperation::Inter => "SINTER",
+            SetOperation::Union => "SUNION",
+            SetOperation::Diff => "SDIFF",
+        };
+
+        let mut ary = Resp::array();
+        if let Some(destination) = op.destination {
+            ary.push_bulk_strings(Bytes::from(format!("{}STORE", name)));
+            ary.push_bulk_strings(Bytes::from(destination.into_bytes()));
+        } else {
+            ary.push_bulk_strings(Bytes::from(name));
+        }
+        for key in op.keys {
+            ary.push_bulk_strings(Bytes::from(key.into_bytes()));
+        }
+        ary
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cmd;
+
+    #[test]
+    fn members() {
+        let members = [Bytes::from("a"), Bytes::from(&b"\xff"[..])];
+        assert_eq!(
+            cmd!["SADD", "s", "a", b"\xff"],
+            Resp::from(SetMembers::add("s", &members))
+        );
+        assert_eq!(
+            cmd!["SREM", "s", "a", b"\xff"],
+            Resp::from(SetMembers::rem("s", &members))
+        );
+        assert_eq!(
+            cmd!["SMISMEMBER", "s", "a", b"\xff"],
+            Resp::from(SetMembers::mismember("s", &members))
+        );
+        assert_eq!(
+            cmd!["SISMEMBER", "s", "a"],
+            Resp::from(SIsMember::new("s", Bytes::from("a")))
+        );
+        assert_eq!(cmd!["SMEMBERS", "s"], Resp::from(SetKey::members("s")));
+        assert_eq!(cmd!["SCARD", "s"], Resp::from(SetKey::card("s")));
+    }
+
+    #[test]
+    fn operations() {
+        assert_eq!(
+            cmd!["SINTER", "a", "b"],
+            Resp::from(SetOp::new(SetOperation::Inter, &["a", "b"]))
+        );
+        assert_eq!(
+            cmd!["SUNION", "a"],
+            Resp::from(SetOp::new(SetOperation::Union, &["a"]))
+        );
+        assert_eq!(
+            cmd!["SDIFFSTORE", "dst", "a", "b"],
+            Resp::from(SetOp::store(SetOperation::Diff, "dst", &["a", "b"]))
+        );
+        assert_eq!(
+            cmd!["SUNIONSTORE", "dst", "a"],
+            Resp::from(SetOp::store(SetOperation::Union, "dst", &["a"]))
+        );
+    }
+}
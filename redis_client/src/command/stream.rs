@@ -0,0 +1,592 @@
+use crate::resp::Resp;
+use crate::streams::{StreamId, StreamTrimOptions};
+use bytes::Bytes;
+use std::time::Duration;
+
+#[derive(Debug)]
+pub struct XAdd {
+    key: String,
+    id: Option<StreamId>, // None for `*`
+    trim: Option<StreamTrimOptions>,
+    fields: Vec<(String, Bytes)>,
+}
+
+impl XAdd {
+    pub fn new(
+        key: impl ToString,
+        id: Option<StreamId>,
+        trim: Option<StreamTrimOptions>,
+        fields: &[(&str, Bytes)],
+    ) -> Self {
+        XAdd {
+            key: key.to_string(),
+            id,
+            trim,
+            fields: fields
+                .iter()
+                .map(|(f, v)| (f.to_string(), v.clone()))
+                .collect(),
+        }
+    }
+}
+
+impl From<XAdd> for Resp {
+    fn from(xadd: XAdd) -> Self {
+        let mut ary = Resp::array();
+        ary.push_bulk_strings(Bytes::from("XADD"));
+        ary.push_bulk_strings(Bytes::from(xadd.key.into_bytes()));
+        if let Some(trim) = xadd.trim {
+            trim.push_to(&mut ary);
+        }
+        match xadd.id {
+            Some(id) => ary.push_bulk_strings(Bytes::from(id.to_string())),
+            None => ary.push_bulk_strings(Bytes::from("*")),
+        }
+        for (field, value) in xadd.fields {
+            ary.push_bulk_strings(Bytes::from(field.into_bytes()));
+            ary.push_bulk_strings(value);
+        }
+        ary
+    }
+}
+
+// XRANGE and XREVRANGE
+#[derive(Debug)]
+pub struct XRange {
+    rev: bool,
+    key: String,
+    start: String,
+    end: String,
+    count: Option<u64>,
+}
+
+impl XRange {
+    pub fn new(key: impl ToString, start: &str, end: &str, count: Option<u64>) -> Self {
+        XRange {
+            rev: false,
+            key: key.to_string(),
+            start: start.to_string(),
+            end: end.to_string(),
+            count,
+        }
+    }
+
+    // XREVRANGE takes `end` first
+    pub fn new_rev(key: impl ToString, end: &str, start: &str, count: Option<u64>) -> Self {
+        XRange {
+            rev: true,
+            ..XRange::new(key, end, start, count)
+        }
+    }
+}
+
+impl From<XRange> for Resp {
+    fn from(xrange: XRange) -> Self {
+        let mut ary = Resp::array();
+        if xrange.rev {
+            ary.push_bulk_strings(Bytes::from("XREVRANGE"));
+        } else {
+            ary.push_bulk_strings(Bytes::from("XRANGE"));
+        }
+        ary.push_bulk_strings(Bytes::from(xrange.key.into_bytes()));
+        ary.push_bulk_strings(Bytes::from(xrange.start.into_bytes()));
+        ary.push_bulk_strings(Bytes::from(xrange.end.into_bytes()));
+        if let Some(count) = xrange.count {
+            ary.push_bulk_strings(Bytes::from("COUNT"));
+            ary.push_bulk_strings(Bytes::from(count.to_string()));
+        }
+        ary
+    }
+}
+
+#[derive(Debug)]
+pub struct XLen {
+    key: String,
+}
+
+impl XLen {
+    pub fn new(key: impl ToString) -> Self {
+        XLen {
+            key: key.to_string(),
+        }
+    }
+}
+
+impl From<XLen> for Resp {
+    fn from(xlen: XLen) -> Self {
+        let mut ary = Resp::array();
+        ary.push_bulk_strings(Bytes::from("XLEN"));
+        ary.push_bulk_strings(Bytes::from(xlen.key.into_bytes()));
+        ary
+    }
+}
+
+#[derive(Debug)]
+pub struct XDel {
+    key: String,
+    ids: Vec<StreamId>,
+}
+
+impl XDel {
+    pub fn new(key: impl ToString, ids: &[StreamId]) -> Self {
+        XDel {
+            key: key.to_string(),
+            ids: ids.to_vec(),
+        }
+    }
+}
+
+impl From<XDel> for Resp {
+    fn from(xdel: XDel) -> Self {
+        let mut ary = Resp::array();
+        ary.push_bulk_strings(Bytes::from("XDEL"));
+        ary.push_bulk_strings(Bytes::from(xdel.key.into_bytes()));
+        for id in xdel.ids {
+            ary.push_bulk_strings(Bytes::from(id.to_string()));
+        }
+        ary
+    }
+}
+
+#[derive(Debug)]
+pub struct XTrim {
+    key: String,
+    trim: StreamTrimOptions,
+}
+
+impl XTrim {
+    pub fn new(key: impl ToString, trim: StreamTrimOptions) -> Self {
+        XTrim {
+            key: key.to_string(),
+            trim,
+        }
+    }
+}
+
+impl From<XTrim> for Resp {
+    fn from(xtrim: XTrim) -> Self {
+        let mut ary = Resp::array();
+        ary.push_bulk_strings(Bytes::from("XTRIM"));
+        ary.push_bulk_strings(Bytes::from(xtrim.key.into_bytes()));
+        xtrim.trim.push_to(&mut ary);
+        ary
+    }
+}
+
+// XREAD, or XREADGROUP when group is given
+#[derive(Debug)]
+pub struct XRead {
+    group: Option<(String, String)>, // (group, consumer)
+    count: Option<u64>,
+    block: Option<Duration>,
+    noack: bool,
+    streams: Vec<(String, String)>, // (key, id)
+}
+
+impl XRead {
+    pub fn new(streams: &[(&str, &str)], count: Option<u64>, block: Option<Duration>) -> Self {
+        XRead {
+            group: None,
+            count,
+            block,
+            noack: false,
+            streams: streams
+                .iter()
+                .map(|(k, id)| (k.to_string(), id.to_string()))
+                .collect(),
+        }
+    }
+
+    pub fn new_group(
+        group: impl ToString,
+        consumer: impl ToString,
+        streams: &[(&str, &str)],
+        count: Option<u64>,
+        block: Option<Duration>,
+        noack: bool,
+    ) -> Self {
+        XRead {
+            group: Some((group.to_string(), consumer.to_string())),
+            noack,
+            ..XRead::new(streams, count, block)
+        }
+    }
+}
+
+impl From<XRead> for Resp {
+    fn from(xread: XRead) -> Self {
+        let mut ary = Resp::array();
+        if let Some((group, consumer)) = xread.group {
+            ary.push_bulk_strings(Bytes::from("XREADGROUP"));
+            ary.push_bulk_strings(Bytes::from("GROUP"));
+            ary.push_bulk_strings(Bytes::from(group.into_bytes()));
+            ary.push_bulk_strings(Bytes::from(consumer.into_bytes()));
+        } else {
+            ary.push_bulk_strings(Bytes::from("XREAD"));
+        }
+        if let Some(count) = xread.count {
+            ary.push_bulk_strings(Bytes::from("COUNT"));
+            ary.push_bulk_strings(Bytes::from(count.to_string()));
+        }
+        if let Some(block) = xread.block {
+            ary.push_bulk_strings(Bytes::from("BLOCK"));
+            ary.push_bulk_strings(Bytes::from(super::timeout_millis(block).to_string()));
+        }
+        if xread.noack {
+            ary.push_bulk_strings(Bytes::from("NOACK"));
+        }
+        ary.push_bulk_strings(Bytes::from("STREAMS"));
+        let (keys, ids): (Vec<_>, Vec<_>) = xread.streams.into_iter().unzip();
+        for key in keys {
+            ary.push_bulk_strings(Bytes::from(key.into_bytes()));
+        }
+        for id in ids {
+            ary.push_bulk_strings(Bytes::from(id.into_bytes()));
+        }
+        ary
+    }
+}
+
+#[derive(Debug)]
+enum XGroupSubcommand {
+    Create { id: String, mkstream: bool },
+    Destroy,
+    SetId { id: String },
+    CreateConsumer { consumer: String },
+    DelConsumer { consumer: String },
+}
+
+#[derive(Debug)]
+pub struct XGroup {
+    key: String,
+    group: String,
+    subcommand: XGroupSubcommand,
+}
+
+impl XGroup {
+    pub fn create(key: impl ToString, group: impl ToString, id: &str, mkstream: bool) -> Self {
+        XGroup::new(
+            key,
+            group,
+            XGroupSubcommand::Create {
+                id: id.to_string(),
+                mkstream,
+            },
+        )
+    }
+
+    pub fn destroy(key: impl ToString, group: impl ToString) -> Self {
+        XGroup::new(key, group, XGroupSubcommand::Destroy)
+    }
+
+    pub fn setid(key: impl ToString, group: impl ToString, id: &str) -> Self {
+        XGroup::new(key, group, XGroupSubcommand::SetId { id: id.to_string() })
+    }
+
+    pub fn createconsumer(key: impl ToString, group: impl ToString, consumer: &str) -> Self {
+        let consumer = consumer.to_string();
+        XGroup::new(key, group, XGroupSubcommand::CreateConsumer { consumer })
+    }
+
+    pub fn delconsumer(key: impl ToString, group: impl ToString, consumer: &str) -> Self {
+        let consumer = consumer.to_string();
+        XGroup::new(key, group, XGroupSubcommand::DelConsumer { consumer })
+    }
+
+    fn new(key: impl ToString, group: impl ToString, subcommand: XGroupSubcommand) -> Self {
+        XGroup {
+            key: key.to_string(),
+            group: group.to_string(),
+            subcommand,
+        }
+    }
+}
+
+impl From<XGroup> for Resp {
+    fn from(xgroup: XGroup) -> Self {
+        let mut ary = Resp::array();
+        ary.push_bulk_strings(Bytes::from("XGROUP"));
+        let name = match &xgroup.subcommand {
+            XGroupSubcommand::Create { .. } => "CREATE",
+            XGroupSubcommand::Destroy => "DESTROY",
+            XGroupSubcommand::SetId { .. } => "SETID",
+            XGroupSubcommand::CreateConsumer { .. } => "CREATECONSUMER",
+            XGroupSubcommand::DelConsumer { .. } => "DELCONSUMER",
+        };
+        ary.push_bulk_strings(Bytes::from(name));
+        ary.push_bulk_strings(Bytes::from(xgroup.key.into_bytes()));
+        ary.push_bulk_strings(Bytes::from(xgroup.group.into_bytes()));
+        match xgroup.subcommand {
+            XGroupSubcommand::Create { id, mkstream } => {
+                ary.push_bulk_strings(Bytes::from(id.into_bytes()));
+                if mkstream {
+                    ary.push_bulk_strings(Bytes::from("MKSTREAM"));
+                }
+            }
+            XGroupSubcommand::SetId { id } => ary.push_bulk_strings(Bytes::from(id.into_bytes())),
+            XGroupSubcommand::CreateConsumer { consumer }
+            | XGroupSubcommand::DelConsumer { consumer } => {
+                ary.push_bulk_strings(Bytes::from(consumer.into_bytes()))
+            }
+            XGroupSubcommand::Destroy => {}
+        }
+        ary
+    }
+}
+
+#[derive(Debug)]
+pub struct XAck {
+    key: String,
+    group: String,
+    ids: Vec<StreamId>,
+}
+
+impl XAck {
+    pub fn new(key: impl ToString, group: impl ToString, ids: &[StreamId]) -> Self {
+        XAck {
+            key: key.to_string(),
+            group: group.to_string(),
+            ids: ids.to_vec(),
+        }
+    }
+}
+
+impl From<XAck> for Resp {
+    fn from(xack: XAck) -> Self {
+        let mut ary = Resp::array();
+        ary.push_bulk_strings(Bytes::from("XACK"));
+        ary.push_bulk_strings(Bytes::from(xack.key.into_bytes()));
+        ary.push_bulk_strings(Bytes::from(xack.group.into_bytes()));
+        for id in xack.ids {
+            ary.push_bulk_strings(Bytes::from(id.to_string()));
+        }
+        ary
+    }
+}
+
+#[derive(Debug)]
+struct XPendingRange {
+    idle: Option<Duration>,
+    start: String,
+    end: String,
+    count: u64,
+    consumer: Option<String>,
+}
+
+// XPENDING in the summary form, or the extended form when range is given
+#[derive(Debug)]
+pub struct XPending {
+    key: String,
+    group: String,
+    range: Option<XPendingRange>,
+}
+
+impl XPending {
+    pub fn new(key: impl ToString, group: impl ToString) -> Self {
+        XPending {
+            key: key.to_string(),
+            group: group.to_string(),
+            range: None,
+        }
+    }
+
+    pub fn new_range(
+        key: impl ToString,
+        group: impl ToString,
+        idle: Option<Duration>,
+        start: &str,
+        end: &str,
+        count: u64,
+        consumer: Option<&str>,
+    ) -> Self {
+        XPending {
+            range: Some(XPendingRange {
+                idle,
+                start: start.to_string(),
+                end: end.to_string(),
+                count,
+                consumer: consumer.map(|c| c.to_string()),
+            }),
+            ..XPending::new(key, group)
+        }
+    }
+}
+
+impl From<XPending> for Resp {
+    fn from(xpending: XPending) -> Self {
+        let mut ary = Resp::array();
+        ary.push_bulk_strings(Bytes::from("XPENDING"));
+        ary.push_bulk_strings(Bytes::from(xpending.key.into_bytes()));
+        ary.push_bulk_strings(Bytes::from(xpending.group.into_bytes()));
+        if let Some(range) = xpending.range {
+            if let Some(idle) = range.idle {
+                ary.push_bulk_strings(Bytes::from("IDLE"));
+                ary.push_bulk_strings(Bytes::from(idle.as_millis().to_string()));
+            }
+            ary.push_bulk_strings(Bytes::from(range.start.into_bytes()));
+            ary.push_bulk_strings(Bytes::from(range.end.into_bytes()));
+            ary.push_bulk_strings(Bytes::from(range.count.to_string()));
+            if let Some(consumer) = range.consumer {
+                ary.push_bulk_strings(Bytes::from(consumer.into_bytes()));
+            }
+        }
+        ary
+    }
+}
+
+#[derive(Debug)]
+pub struct XClaim {
+    key: String,
+    group: String,
+    consumer: String,
+    min_idle: Duration,
+    ids: Vec<StreamId>,
+}
+
+impl XClaim {
+    pub fn new(
+        key: impl ToString,
+        group: impl ToString,
+        consumer: impl ToString,
+        min_idle: Duration,
+        ids: &[StreamId],
+    ) -> Self {
+        XClaim {
+            key: key.to_string(),
+            group: group.to_string(),
+            consumer: consumer.to_string(),
+            min_idle,
+            ids: ids.to_vec(),
+        }
+    }
+}
+
+impl From<XClaim> for Resp {
+    fn from(xclaim: XClaim) -> Self {
+        let mut ary = Resp::array();
+        ary.push_bulk_strings(Bytes::from("XCLAIM"));
+        ary.push_bulk_strings(Bytes::from(xclaim.key.into_bytes()));
+        ary.push_bulk_strings(Bytes::from(xclaim.group.into_bytes()));
+        ary.push_bulk_strings(Bytes::from(xclaim.consumer.into_bytes()));
+        ary.push_bulk_strings(Bytes::from(xclaim.min_idle.as_millis().to_string()));
+        for id in xclaim.ids {
+            ary.push_bulk_strings(Bytes::from(id.to_string()));
+        }
+        ary
+    }
+}
+
+#[derive(Debug)]
+pub struct XAutoClaim {
+    key: String,
+    group: String,
+    consumer: String,
+    min_idle: Duration,
+    start: StreamId,
+    count: Option<u64>,
+}
+
+impl XAutoClaim {
+    pub fn new(
+        key: impl ToString,
+        group: impl ToString,
+        consumer: impl ToString,
+        min_idle: Duration,
+        start: StreamId,
+        count: Option<u64>,
+    ) -> Self {
+        XAutoClaim {
+            key: key.to_string(),
+            group: group.to_string(),
+            consumer: consumer.to_string(),
+            min_idle,
+            start,
+            count,
+        }
+    }
+}
+
+impl From<XAutoClaim> for Resp {
+    fn from(xautoclaim: XAutoClaim) -> Self {
+        let mut ary = Resp::array();
+        ary.push_bulk_strings(Bytes::from("XAUTOCLAIM"));
+        ary.push_bulk_strings(Bytes::from(xautoclaim.key.into_bytes()));
+        ary.push_bulk_strings(Bytes::from(xautoclaim.group.into_bytes()));
+        ary.push_bulk_strings(Bytes::from(xautoclaim.consumer.into_bytes()));
+        ary.push_bulk_strings(Bytes::from(xautoclaim.min_idle.as_millis().to_string()));
+        ary.push_bulk_strings(Bytes::from(xautoclaim.start.to_string()));
+        if let Some(count) = xautoclaim.count {
+            ary.push_bulk_strings(Bytes::from("COUNT"));
+            ary.push_bulk_strings(Bytes::from(count.to_string()));
+        }
+        ary
+    }
+}
+
+// XINFO STREAM, XINFO GROUPS and XINFO CONSUMERS
+#[derive(Debug)]
+pub struct XInfo {
+    subcommand: &'static str,
+    key: String,
+    group: Option<String>,
+}
+
+impl XInfo {
+    pub fn stream(key: impl ToString) -> Self {
+        XInfo {
+            subcommand: "STREAM",
+            key: key.to_string(),
+            group: None,
+        }
+    }
+
+    pub fn groups(key: impl ToString) -> Self {
+        XInfo {
+            subcommand: "GROUPS",
+            key: key.to_string(),
+            group: None,
+        }
+    }
+
+    pub fn consumers(key: impl ToString, group: impl ToString) -> Self {
+        XInfo {
+            subcommand: "CONSUMERS",
+            key: key.to_string(),
+            group: Some(group.to_string()),
+        }
+    }
+}
+
+impl From<XInfo> for Resp {
+    fn from(xinfo: XInfo) -> Self {
+        let mut ary = Resp::array();
+        ary.push_bulk_strings(Bytes::from("XINFO"));
+        ary.push_bulk_strings(Bytes::from(xinfo.subcommand));
+        ary.push_bulk_strings(Bytes::from(xinfo.key.into_bytes()));
+        if let Some(group) = xinfo.group {
+            ary.push_bulk_strings(Bytes::from(group.into_bytes()));
+        }
+        ary
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cmd;
+
+    #[test]
+    fn xread_block() {
+        let xread = XRead::new(&[("s", "$")], None, Some(Duration::from_micros(500)));
+        assert_eq!(
+            cmd!["XREAD", "BLOCK", "1", "STREAMS", "s", "$"],
+            Resp::from(xread)
+        );
+
+        let xread = XRead::new(&[("s", "0")], Some(10), Some(Duration::ZERO));
+        assert_eq!(
+            cmd!["XREAD", "COUNT", "10", "BLOCK", "0", "STREAMS", "s", "0"],
+            Resp::from(xread)
+        );
+    }
+}
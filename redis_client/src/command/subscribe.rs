@@ -4,12 +4,21 @@ use bytes::Bytes;
 #[derive(Debug)]
 pub struct Subscribe {
     channels: Vec<String>,
+    pattern: bool, // PSUBSCRIBE if true
 }
 
 impl Subscribe {
     pub fn new(channels: &[String]) -> Self {
         Subscribe {
             channels: channels.to_vec(),
+            pattern: false,
+        }
+    }
+
+    pub fn new_patterns(patterns: &[String]) -> Self {
+        Subscribe {
+            channels: patterns.to_vec(),
+            pattern: true,
         }
     }
 }
@@ -17,7 +26,11 @@ impl Subscribe {
 impl From<Subscribe> for Resp {
     fn from(sub: Subscribe) -> Self {
         let mut ary = Resp::array();
-        ary.push_bulk_strings(Bytes::from("Subscribe".as_bytes()));
+        if sub.pattern {
+            ary.push_bulk_strings(Bytes::from("PSUBSCRIBE"));
+        } else {
+            ary.push_bulk_strings(Bytes::from("Subscribe".as_bytes()));
+        }
         for ch in sub.channels {
             ary.push_bulk_strings(Bytes::from(ch.into_bytes()));
         }
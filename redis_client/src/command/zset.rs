@@ -0,0 +1,563 @@
+use super::timeout_arg;
+use crate::resp::Resp;
+use bytes::Bytes;
+use std::time::Duration;
+
+/// Options of ZADD. `Default` adds and updates members unconditionally.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ZAddOptions {
+    /// NX: only add new members
+    pub nx: bool,
+    /// XX: only update existing members
+    pub xx: bool,
+    /// GT: only update when the new score is greater
+    pub gt: bool,
+    /// LT: only update when the new score is less
+    pub lt: bool,
+    /// CH: count changed members as well as added ones
+    pub ch: bool,
+}
+
+#[derive(Debug)]
+pub struct ZAdd {
+    key: String,
+    options: ZAddOptions,
+    incr: bool,
+    members: Vec<(f64, Bytes)>,
+}
+
+impl ZAdd {
+    pub fn new(key: impl ToString, options: ZAddOptions, members: &[(f64, Bytes)]) -> Self {
+        ZAdd {
+            key: key.to_string(),
+            options,
+            incr: false,
+            members: members.to_vec(),
+        }
+    }
+
+    pub fn new_incr(key: impl ToString, options: ZAddOptions, score: f64, member: Bytes) -> Self {
+        ZAdd {
+            key: key.to_string(),
+            options,
+            incr: true,
+            members: vec![(score, member)],
+        }
+    }
+}
+
+impl From<ZAdd> for Resp {
+    fn from(zadd: ZAdd) -> Self {
+        let mut ary = Resp::array();
+        ary.push_bulk_strings(Bytes::from("ZADD"));
+        ary.push_bulk_strings(Bytes::from(zadd.key.into_bytes()));
+        for (enabled, flag) in [
+            (zadd.options.nx, "NX"),
+            (zadd.options.xx, "XX"),
+            (zadd.options.gt, "GT"),
+            (zadd.options.lt, "LT"),
+            (zadd.options.ch, "CH"),
+            (zadd.incr, "INCR"),
+        ] {
+            if enabled {
+                ary.push_bulk_strings(Bytes::from(flag));
+            }
+        }
+        for (score, member) in zadd.members {
+            ary.push_bulk_strings(Bytes::from(score.to_string()));
+            ary.push_bulk_strings(member);
+        }
+        ary
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ZRangeBy {
+    Index,
+    Score,
+    Lex,
+}
+
+/// Range specification of ZRANGE.
+///
+/// `start` and `stop` are passed as-is, so they can be exclusive scores
+/// like `"(1.5"`, `"-inf"`/`"+inf"`, or lex ranges like `"[a"`/`"-"`/`"+"`.
+#[derive(Debug, Clone)]
+pub struct ZRange {
+    start: String,
+    stop: String,
+    by: ZRangeBy,
+    rev: bool,
+    limit: Option<(i64, i64)>,
+}
+
+impl ZRange {
+    /// Range by rank, like `ZRANGE key 0 -1`.
+    pub fn index(start: i64, stop: i64) -> Self {
+        ZRange::new(ZRangeBy::Index, start.to_string(), stop.to_string())
+    }
+
+    /// Range by score (BYSCORE).
+    pub fn score(min: impl ToString, max: impl ToString) -> Self {
+        ZRange::new(ZRangeBy::Score, min.to_string(), max.to_string())
+    }
+
+    /// Range by lexicographical order (BYLEX).
+    pub fn lex(min: impl ToString, max: impl ToString) -> Self {
+        ZRange::new(ZRangeBy::Lex, min.to_string(), max.to_string())
+    }
+
+    /// Reverses the order (REV). Note that `start` and `stop` must be given
+    /// from high to low then, as Redis expects.
+    pub fn rev(mut self) -> Self {
+        self.rev = true;
+        self
+    }
+
+    /// LIMIT offset count. Only valid with BYSCORE and BYLEX.
+    pub fn limit(mut self, offset: i64, count: i64) -> Self {
+        self.limit = Some((offset, count));
+        self
+    }
+
+    fn new(by: ZRangeBy, start: String, stop: String) -> Self {
+        ZRange {
+            start,
+            stop,
+            by,
+            rev: false,
+            limit: None,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct ZRangeCommand {
+    key: String,
+    range: ZRange,
+    withscores: bool,
+}
+
+impl ZRangeCommand {
+    pub fn new(key: impl ToString, range: ZRange, withscores: bool) -> Self {
+        ZRangeCommand {
+            key: key.to_string(),
+            range,
+            withscores,
+        }
+    }
+}
+
+impl From<ZRangeCommand> for Resp {
+    fn from(zrange: ZRangeCommand) -> Self {
+        let range = zrange.range;
+        let mut ary = Resp::array();
+        ary.push_bulk_strings(Bytes::from("ZRANGE"));
+        ary.push_bulk_strings(Bytes::from(zrange.key.into_bytes()));
+        ary.push_bulk_strings(Bytes::from(range.start.into_bytes()));
+        ary.push_bulk_strings(Bytes::from(range.stop.into_bytes()));
+        match range.by {
+            ZRangeBy::Index => {}
+            ZRangeBy::Score => ary.push_bulk_strings(Bytes::from("BYSCORE")),
+            ZRangeBy::Lex => ary.push_bulk_strings(Bytes::from("BYLEX")),
+        }
+        if range.rev {
+            ary.push_bulk_strings(Bytes::from("REV"));
+        }
+        if let Some((offset, count)) = range.limit {
+            ary.push_bulk_strings(Bytes::from("LIMIT"));
+            ary.push_bulk_strings(Bytes::from(offset.to_string()));
+            ary.push_bulk_strings(Bytes::from(count.to_string()));
+        }
+        if zrange.withscores {
+            ary.push_bulk_strings(Bytes::from("WITHSCORES"));
+        }
+        ary
+    }
+}
+
+// ZRANK, ZREVRANK, ZSCORE and ZMSCORE
+#[derive(Debug)]
+pub struct ZMember {
+    command: &'static str,
+    key: String,
+    members: Vec<Bytes>,
+}
+
+impl ZMember {
+    pub fn rank(key: impl ToString, member: Bytes) -> Self {
+        ZMember::new("ZRANK", key, &[member])
+    }
+
+    pub fn revrank(key: impl ToString, member: Bytes) -> Self {
+        ZMember::new("ZREVRANK", key, &[member])
+    }
+
+    pub fn score(key: impl ToString, member: Bytes) -> Self {
+        ZMember::new("ZSCORE", key, &[member])
+    }
+
+    pub fn mscore(key: impl ToString, members: &[Bytes]) -> Self {
+        ZMember::new("ZMSCORE", key, members)
+    }
+
+    pub fn rem(key: impl ToString, members: &[Bytes]) -> Self {
+        ZMember::new("ZREM", key, members)
+    }
+
+    fn new(command: &'static str, key: impl ToString, members: &[Bytes]) -> Self {
+        ZMember {
+            command,
+            key: key.to_string(),
+            members: members.to_vec(),
+        }
+    }
+}
+
+impl From<ZMember> for Resp {
+    fn from(zmember: ZMember) -> Self {
+        let mut ary = Resp::array();
+        ary.push_bulk_strings(Bytes::from(zmember.command));
+        ary.push_bulk_strings(Bytes::from(zmember.key.into_bytes()));
+        for member in zmember.members {
+            ary.push_bulk_strings(member);
+        }
+        ary
+    }
+}
+
+#[derive(Debug)]
+pub struct ZIncrBy {
+    key: String,
+    increment: f64,
+    member: Bytes,
+}
+
+impl ZIncrBy {
+    pub fn new(key: impl ToString, increment: f64, member: Bytes) -> Self {
+        ZIncrBy {
+            key: key.to_string(),
+            increment,
+            member,
+        }
+    }
+}
+
+impl From<ZIncrBy> for Resp {
+    fn from(zincrby: ZIncrBy) -> Self {
+        let mut ary = Resp::array();
+        ary.push_bulk_strings(Bytes::from("ZINCRBY"));
+        ary.push_bulk_strings(Bytes::from(zincrby.key.into_bytes()));
+        ary.push_bulk_strings(Bytes::from(zincrby.increment.to_string()));
+        ary.push_bulk_strings(zincrby.member);
+        ary
+    }
+}
+
+// ZPOPMIN and ZPOPMAX
+#[derive(Debug)]
+pub struct ZPop {
+    max: bool,
+    key: String,
+    count: Option<u64>,
+}
+
+impl ZPop {
+    pub fn new(max: bool, key: impl ToString, count: Option<u64>) -> Self {
+        ZPop {
+            max,
+            key: key.to_string(),
+            count,
+        }
+    }
+}
+
+impl From<ZPop> for Resp {
+    fn from(zpop: ZPop) -> Self {
+        let mut ary = Resp::array();
+        if zpop.max {
+            ary.push_bulk_strings(Bytes::from("ZPOPMAX"));
+        } else {
+            ary.push_bulk_strings(Bytes::from("ZPOPMIN"));
+        }
+        ary.push_bulk_strings(Bytes::from(zpop.key.into_bytes()));
+        if let Some(count) = zpop.count {
+            ary.push_bulk_strings(Bytes::from(count.to_string()));
+        }
+        ary
+    }
+}
+
+// BZPOPMIN and BZPOPMAX
+#[derive(Debug)]
+pub struct BZPop {
+    max: bool,
+    keys: Vec<String>,
+    timeout: Duration,
+}
+
+impl BZPop {
+    pub fn new(max: bool, keys: &[&str], timeout: Duration) -> Self {
+        BZPop {
+            max,
+            keys: keys.iter().map(|k| k.to_string()).collect(),
+            timeout,
+        }
+    }
+}
+
+impl From<BZPop> for Resp {
+    fn from(bzpop: BZPop) -> Self {
+        let mut ary = Resp::array();
+        if bzpop.max {
+            ary.push_bulk_strings(Bytes::from("BZPOPMAX"));
+        } else {
+            ary.push_bulk_strings(Bytes::from("BZPOPMIN"));
+        }
+        for key in bzpop.keys {
+            ary.push_bulk_strings(Bytes::from(key.into_bytes()));
+        }
+        ary.push_bulk_strings(timeout_arg(bzpop.timeout));
+        ary
+    }
+}
+
+#[derive(Debug)]
+pub struct ZCount {
+    key: String,
+    min: String,
+    max: String,
+}
+
+impl ZCount {
+    pub fn new(key: impl ToString, min: impl ToString, max: impl ToString) -> Self {
+        ZCount {
+            key: key.to_string(),
+            min: min.to_string(),
+            max: max.to_string(),
+        }
+    }
+}
+
+impl From<ZCount> for Resp {
+    fn from(zcount: ZCount) -> Self {
+        let mut ary = Resp::array();
+        ary.push_bulk_strings(Bytes::from("ZCOUNT"));
+        ary.push_bulk_strings(Bytes::from(zcount.key.into_bytes()));
+        ary.push_bulk_strings(Bytes::from(zcount.min.into_bytes()));
+        ary.push_bulk_strings(Bytes::from(zcount.max.into_bytes()));
+        ary
+    }
+}
+
+/// AGGREGATE option of ZUNIONSTORE and ZINTERSTORE.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Aggregate {
+    Sum,
+    Min,
+    Max,
+}
+
+// ZUNIONSTORE and ZINTERSTORE
+#[derive(Debug)]
+pub struct ZStore {
+    union: bool,
+    destination: String,
+    keys: Vec<String>,
+    weights: Option<Vec<f64>>,
+    aggregate: Option<Aggregate>,
+}
+
+impl ZStore {
+    pub fn new(
+        union: bool,
+        destination: impl ToString,
+        keys: &[&str],
+        weights: Option<&[f64]>,
+        aggregate: Option<Aggregate>,
+    ) -> Self {
+        ZStore {
+            union,
+            destination: destination.to_string(),
+            keys: keys.iter().map(|k| k.to_string()).collect(),
+            weights: weights.map(|w| w.to_vec()),
+            aggregate,
+        }
+    }
+}
+
+impl From<ZStore> for Resp {
+    fn from(zstore: ZStore) -> Self {
+        let mut ary = Resp::array();
+        if zstore.union {
+            ary.push_bulk_strings(Bytes::from("ZUNIONSTORE"));
+        } else {
+            ary.push_bulk_strings(Bytes::from("ZINTERSTORE"));
+        }
+        ary.push_bulk_strings(Bytes::from(zstore.destination.into_bytes()));
+        ary.push_bulk_strings(Bytes::from(zstore.keys.len().to_string()));
+        for key in zstore.keys {
+            ary.push_bulk_strings(Bytes::from(key.into_bytes()));
+        }
+        if let Some(weights) = zstore.weights {
+            ary.push_bulk_strings(Bytes::from("WEIGHTS"));
+            for weight in weights {
+                ary.push_bulk_strings(Bytes::from(weight.to_string()));
+            }
+        }
+        if let Some(aggregate) = zstore.aggregate {
+            ary.push_bulk_strings(Bytes::from("AGGREGATE"));
+            match aggregate {
+                Aggregate::Sum => ary.push_bulk_strings(Bytes::from("SUM")),
+                Aggregate::Min => ary.push_bulk_strings(Bytes::from("MIN")),
+                Aggregate::Max => ary.push_bulk_strings(Bytes::from("MAX")),
+            }
+        }
+        ary
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cmd;
+    use crate::resp::RespWriter;
+
+    async fn encode(resp: Resp) -> Vec<u8> {
+        let mut buf = vec![];
+        RespWriter::new(&mut buf).write(&resp).await.unwrap();
+        buf
+    }
+
+    #[tokio::test]
+    async fn zrange_by_score() {
+        let range = ZRange::score("(1", "+inf").rev().limit(0, 10);
+        let buf = encode(Resp::from(ZRangeCommand::new("z", range, true))).await;
+
+        assert_eq!(
+            b"*10\r\n$6\r\nZRANGE\r\n$1\r\nz\r\n$2\r\n(1\r\n$4\r\n+inf\r\n$7\r\nBYSCORE\r\n$3\r\nREV\r\n$5\r\nLIMIT\r\n$1\r\n0\r\n$2\r\n10\r\n$10\r\nWITHSCORES\r\n".to_vec(),
+            buf
+        );
+    }
+
+    #[tokio::test]
+    async fn zadd_with_flags() {
+        let options = ZAddOptions {
+            xx: true,
+            gt: true,
+            ch: true,
+            ..Default::default()
+        };
+        let buf = encode(Resp::from(ZAdd::new("z", options, &[(1.5, "m".into())]))).await;
+
+        assert_eq!(
+            b"*7\r\n$4\r\nZADD\r\n$1\r\nz\r\n$2\r\nXX\r\n$2\r\nGT\r\n$2\r\nCH\r\n$3\r\n1.5\r\n$1\r\nm\r\n".to_vec(),
+            buf
+        );
+    }
+
+    #[tokio::test]
+    async fn bzpop_timeout_in_seconds() {
+        let bzpop = BZPop::new(true, &["z"], Duration::from_millis(1500));
+        let buf = encode(Resp::from(bzpop)).await;
+
+        assert_eq!(
+            b"*3\r\n$8\r\nBZPOPMAX\r\n$1\r\nz\r\n$3\r\n1.5\r\n".to_vec(),
+            buf
+        );
+    }
+
+    #[test]
+    fn zadd_incr() {
+        let options = ZAddOptions {
+            nx: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            cmd!["ZADD", "z", "NX", "INCR", "-2", "m"],
+            Resp::from(ZAdd::new_incr("z", options, -2.0, "m".into()))
+        );
+    }
+
+    #[test]
+    fn zrange_by_index_and_lex() {
+        let zrange = ZRangeCommand::new("z", ZRange::index(0, -1), false);
+        assert_eq!(cmd!["ZRANGE", "z", "0", "-1"], Resp::from(zrange));
+
+        let range = ZRange::lex("[a", "(c").limit(1, 2);
+        assert_eq!(
+            cmd!["ZRANGE", "z", "[a", "(c", "BYLEX", "LIMIT", "1", "2"],
+            Resp::from(ZRangeCommand::new("z", range, false))
+        );
+    }
+
+    #[test]
+    fn members_and_pops() {
+        let m = Bytes::from("m");
+        assert_eq!(
+            cmd!["ZRANK", "z", "m"],
+            Resp::from(ZMember::rank("z", m.clone()))
+        );
+        assert_eq!(
+            cmd!["ZREVRANK", "z", "m"],
+            Resp::from(ZMember::revrank("z", m.clone()))
+        );
+        assert_eq!(
+            cmd!["ZSCORE", "z", "m"],
+            Resp::from(ZMember::score("z", m.clone()))
+        );
+        assert_eq!(
+            cmd!["ZMSCORE", "z", "m", "n"],
+            Resp::from(ZMember::mscore("z", &[m.clone(), "n".into()]))
+        );
+        assert_eq!(
+            cmd!["ZREM", "z", "m"],
+            Resp::from(ZMember::rem("z", &["m".into()]))
+        );
+        assert_eq!(
+            cmd!["ZINCRBY", "z", "0.5", "m"],
+            Resp::from(ZIncrBy::new("z", 0.5, m))
+        );
+        assert_eq!(
+            cmd!["ZPOPMIN", "z"],
+            Resp::from(ZPop::new(false, "z", None))
+        );
+        assert_eq!(
+            cmd!["ZPOPMAX", "z", "2"],
+            Resp::from(ZPop::new(true, "z", Some(2)))
+        );
+        assert_eq!(
+            cmd!["ZCOUNT", "z", "-inf", "(1.5"],
+            Resp::from(ZCount::new("z", "-inf", "(1.5"))
+        );
+    }
+
+    #[test]
+    fn zstore_weights_and_aggregate() {
+        assert_eq!(
+            cmd!["ZINTERSTORE", "d", "2", "a", "b"],
+            Resp::from(ZStore::new(false, "d", &["a", "b"], None, None))
+        );
+        assert_eq!(
+            cmd![
+                "ZUNIONSTORE",
+                "d",
+                "2",
+                "a",
+                "b",
+                "WEIGHTS",
+                "1",
+                "0.5",
+                "AGGREGATE",
+                "MAX"
+            ],
+            Resp::from(ZStore::new(
+                true,
+                "d",
+                &["a", "b"],
+                Some(&[1.0, 0.5]),
+                Some(Aggregate::Max)
+            ))
+        );
+    }
+}
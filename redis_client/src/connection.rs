@@ -1,40 +1,127 @@
 use crate::error::Error;
-use crate::resp::{ParseErr, Resp, RespWriter};
-use bytes::{Buf, BytesMut};
-use std::io::{Cursor, Error as IError, ErrorKind};
+use crate::resp::{ParseErr, Resp, RespDecoder};
+use bytes::BytesMut;
+use std::io::{Error as IError, ErrorKind};
+use std::net::SocketAddr;
+use std::time::Duration;
 use tokio::io::{AsyncReadExt, AsyncWriteExt, BufWriter};
 use tokio::net::TcpStream;
 
+pub const DEFAULT_READ_BUFFER_CAPACITY: usize = 4 * 1024;
+
 #[derive(Debug)]
 pub struct Connection {
     stream: BufWriter<TcpStream>,
 
     // the buffer for reciving data
     buffer: BytesMut,
+
+    decoder: RespDecoder,
+
+    write_timeout: Option<Duration>,
+
+    // totals of the frames written and read, for metrics
+    bytes_written: u64,
+    bytes_read: u64,
+
+    // set once a write or a read timed out, or an invalid frame was read: the
+    // replies no longer match the commands sent, so the connection must not
+    // be used anymore
+    poisoned: bool,
 }
 
 impl Connection {
-    pub fn new(stream: TcpStream) -> Self {
+    pub fn with_capacity(stream: TcpStream, capacity: usize) -> Self {
         Connection {
             stream: BufWriter::new(stream),
-
-            // TODO: capacity must be configurable
-            buffer: BytesMut::with_capacity(4 * 1024),
+            buffer: BytesMut::with_capacity(capacity),
+            decoder: RespDecoder::new(),
+            write_timeout: None,
+            bytes_written: 0,
+            bytes_read: 0,
+            poisoned: false,
         }
     }
 
+    pub fn set_write_timeout(&mut self, timeout: Option<Duration>) {
+        self.write_timeout = timeout;
+    }
+
+    pub fn is_poisoned(&self) -> bool {
+        self.poisoned
+    }
+
+    pub fn bytes_written(&self) -> u64 {
+        self.bytes_written
+    }
+
+    pub fn bytes_read(&self) -> u64 {
+        self.bytes_read
+    }
+
+    pub fn peer_addr(&self) -> Result<SocketAddr, Error> {
+        Ok(self.stream.get_ref().peer_addr()?)
+    }
+
     pub async fn write_data(&mut self, data: &Resp) -> Result<(), Error> {
-        RespWriter::new(&mut self.stream)
-            .write(data)
-            .await
-            .map_err(|e| e.into())?;
+        self.check_poisoned()?;
+
+        match self.write_timeout {
+            Some(timeout) => {
+                match tokio::time::timeout(timeout, self.write_and_flush(data)).await {
+                    Ok(r) => r,
+                    Err(_) => Err(self.poison("write timed out")),
+                }
+            }
+            None => self.write_and_flush(data).await,
+        }
+    }
 
-        self.stream.flush().await.map_err(|e| e.into())
+    async fn write_and_flush(&mut self, data: &Resp) -> Result<(), Error> {
+        let mut buf = BytesMut::new();
+        data.encode(&mut buf);
+        self.write_data2(&buf).await
     }
 
+    /// Writes bytes already encoded, such as several commands at once.
     pub async fn write_data2(&mut self, data: &[u8]) -> Result<(), Error> {
-        self.stream.write(data).await.map_err(|e| e.into())?;
-        self.stream.flush().await.map_err(|e| e.into())
+        self.check_poisoned()?;
+        self.stream.write_all(data).await?;
+        self.bytes_written += data.len() as u64;
+
+        self.stream.flush().await.map_err(Error::from)
+    }
+
+    /// Same as `read_data` but gives up after `timeout`, poisoning the connection.
+    pub async fn read_data_timeout(
+        &mut self,
+        timeout: Option<Duration>,
+    ) -> Result<Option<Resp>, Error> {
+        self.check_poisoned()?;
+
+        match timeout {
+            Some(timeout) => match tokio::time::timeout(timeout, self.read_data()).await {
+                Ok(r) => r,
+                Err(_) => Err(self.poison("response timed out")),
+            },
+            None => self.read_data().await,
+        }
+    }
+
+    pub(crate) fn poison(&mut self, msg: &str) -> Error {
+        self.poisoned = true;
+        Error::IO(IError::new(ErrorKind::TimedOut, msg))
+    }
+
+    fn check_poisoned(&self) -> Result<(), Error> {
+        if self.poisoned {
+            Err(Error::IO(IError::new(
+                ErrorKind::NotConnected,
+                "connection poisoned by an earlier timeout or protocol error",
+            )))
+        } else {
+            Ok(())
+        }
     }
 
     pub async fn read_data(&mut self) -> Result<Option<Resp>, Error> {
@@ -43,11 +130,7 @@ impl Connection {
                 return Ok(Some(v));
             }
 
-            let v = self
-                .stream
-                .read_buf(&mut self.buffer)
-                .await
-                .map_err(|e| e.into())?;
+            let v = self.stream.read_buf(&mut self.buffer).await?;
 
             if v == 0 {
                 if self.buffer.is_empty() {
@@ -63,22 +146,45 @@ impl Connection {
     }
 
     fn parse_data(&mut self) -> Result<Option<Resp>, Error> {
-        let mut buf = Cursor::new(&self.buffer[..]);
-
-        if buf.has_remaining() {
-            let len = buf.remaining();
-            match Resp::parse(&mut buf) {
-                Ok(resp) => {
-                    // dicard already parased data
-                    self.buffer.advance(len as usize);
-                    return Ok(Some(resp));
-                }
-                // still waiting for data
-                Err(ParseErr::Imcomplete) => Ok(None),
-                Err(e) => Err(Error::Serialization(format!("{:?}", e).into())), // TODO
+        let len = self.buffer.len();
+        match self.decoder.decode(&mut self.buffer) {
+            Ok(resp) => {
+                self.bytes_read += (len - self.buffer.len()) as u64;
+                Ok(resp)
+            }
+            Err(ParseErr::Imcomplete) => Ok(None),
+            Err(ParseErr::Invalid(e)) => {
+                self.poisoned = true;
+                Err(Error::protocol(e))
             }
-        } else {
-            return Ok(None);
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::client;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    #[tokio::test]
+    async fn invalid_frame_poisons_connection() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let server = tokio::spawn(async move {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let mut buf = [0; 64];
+            let _ = socket.read(&mut buf).await.unwrap();
+            socket.write_all(b"?PONG\r\n+PONG\r\n").await.unwrap();
+            socket
+        });
+
+        let mut client = client::connect(addr).await.unwrap();
+        let err = client.ping().await.unwrap_err();
+        assert!(err.is_connection_error());
+        assert!(!err.is_retryable());
+        assert!(client.is_poisoned());
+        assert!(client.ping().await.is_err());
+        drop(server.await.unwrap());
+    }
+}
@@ -0,0 +1,421 @@
+//! Deserializes replies into Rust types with serde, behind the `serde` feature.
+//!
+//! - arrays are read as sequences and tuples
+//! - flat field/value arrays, such as HGETALL replies, as structs and maps
+//! - nil as `None`, and as a missing value otherwise
+//! - integers and strings holding numbers as numbers; `1`/`0` and
+//!   `true`/`false` as bool
+//! - strings as unit enum variants
+//!
+//! Error replies are returned as errors. RESP3 maps are not supported since
+//! `Resp` only holds RESP2 values.
+//!
+//! ```
+//! use redis_client::{de, resp::Resp};
+//! use serde::Deserialize;
+//!
+//! #[derive(Deserialize)]
+//! struct User {
+//!     name: String,
+//!     age: u32,
+//! }
+//!
+//! let reply = Resp::Array(vec![
+//!     Resp::BulkString("name".into()),
+//!     Resp::BulkString("alice".into()),
+//!     Resp::BulkString("age".into()),
+//!     Resp::BulkString("42".into()),
+//! ]);
+//! let user: User = de::from_resp(reply).unwrap();
+//! assert_eq!(42, user.age);
+//! ```
+
+use crate::{error::Error, resp::Resp};
+use serde::de::{
+    self, DeserializeOwned, DeserializeSeed, IntoDeserializer, MapAccess, SeqAccess, Unexpected,
+    Visitor,
+};
+use std::fmt;
+
+pub fn from_resp<T: DeserializeOwned>(resp: Resp) -> Result<T, Error> {
+    T::deserialize(Deserializer::new(resp))
+}
+
+impl de::Error for Error {
+    fn custom<T: fmt::Display>(msg: T) -> Self {
+        Error::Serialization(msg.to_string())
+    }
+}
+
+#[derive(Debug)]
+pub struct Deserializer {
+    resp: Resp,
+}
+
+impl Deserializer {
+    pub fn new(resp: Resp) -> Deserializer {
+        Deserializer { resp }
+    }
+
+    // Strings, including the text of integers
+    fn into_string(self, expected: &dyn de::Expected) -> Result<String, Error> {
+        match self.resp {
+            Resp::SimpleString(s) => Ok(s),
+            Resp::BulkString(b) => String::from_utf8(b.to_vec())
+                .map_err(|_| <Error as de::Error>::invalid_value(Unexpected::Bytes(&b), expected)),
+            Resp::Integer(n) => Ok(n.to_string()),
+            Resp::Error(msg) => Err(Error::from_server_message(&msg)),
+            others => Err(invalid_type(&others, expected)),
+        }
+    }
+}
+
+fn invalid_type(resp: &Resp, expected: &dyn de::Expected) -> Error {
+    let unexpected = match resp {
+        Resp::SimpleString(s) => Unexpected::Str(s),
+        Resp::BulkString(b) => Unexpected::Bytes(b),
+        Resp::Integer(n) => Unexpected::Signed(*n),
+        Resp::Null => Unexpected::Option,
+        Resp::Array(_) => Unexpected::Seq,
+        Resp::Error(msg) => return Error::from_server_message(msg),
+    };
+    <Error as de::Error>::invalid_type(unexpected, expected)
+}
+
+// Numbers are parsed from strings since Redis stores them as such
+macro_rules! deserialize_number {
+    ($($method:ident => $ty:ty, $visit:ident;)*) => {
+        $(
+            fn $method<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+                match self.resp {
+                    Resp::Integer(n) => visitor.visit_i64(n),
+                    _ => {
+                        let s = self.into_string(&visitor)?;
+                        let n = s.parse::<$ty>().map_err(|_| {
+                            <Error as de::Error>::invalid_value(Unexpected::Str(&s), &visitor)
+                        })?;
+                        visitor.$visit(n)
+                    }
+                }
+            }
+        )*
+    };
+}
+
+impl<'de> de::Deserializer<'de> for Deserializer {
+    type Error = Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        match self.resp {
+            Resp::SimpleString(s) => visitor.visit_string(s),
+            Resp::BulkString(b) => match String::from_utf8(b.to_vec()) {
+                Ok(s) => visitor.visit_string(s),
+                Err(e) => visitor.visit_byte_buf(e.into_bytes()),
+            },
+            Resp::Integer(n) => visitor.visit_i64(n),
+            Resp::Null => visitor.visit_none(),
+            Resp::Array(items) => visitor.visit_seq(SeqDeserializer::new(items)),
+            Resp::Error(msg) => Err(Error::from_server_message(&msg)),
+        }
+    }
+
+    deserialize_number! {
+        deserialize_i8 => i8, visit_i8;
+        deserialize_i16 => i16, visit_i16;
+        deserialize_i32 => i32, visit_i32;
+        deserialize_i64 => i64, visit_i64;
+        deserialize_u8 => u8, visit_u8;
+        deserialize_u16 => u16, visit_u16;
+        deserialize_u32 => u32, visit_u32;
+        deserialize_u64 => u64, visit_u64;
+        deserialize_f32 => f32, visit_f32;
+        deserialize_f64 => f64, visit_f64;
+    }
+
+    fn deserialize_bool<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        if let Resp::Integer(n) = self.resp {
+            return visitor.visit_bool(n != 0);
+        }
+        let s = self.into_string(&visitor)?;
+        match s.as_str() {
+            "1" | "true" => visitor.visit_bool(true),
+            "0" | "false" => visitor.visit_bool(false),
+            _ => Err(<Error as de::Error>::invalid_value(
+                Unexpected::Str(&s),
+                &visitor,
+            )),
+        }
+    }
+
+    fn deserialize_str<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        let s = self.into_string(&visitor)?;
+        visitor.visit_string(s)
+    }
+
+    fn deserialize_string<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        self.deserialize_str(visitor)
+    }
+
+    fn deserialize_char<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        self.deserialize_str(visitor)
+    }
+
+    fn deserialize_identifier<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        self.deserialize_str(visitor)
+    }
+
+    fn deserialize_bytes<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        match self.resp {
+            Resp::BulkString(b) => visitor.visit_byte_buf(b.to_vec()),
+            Resp::SimpleString(s) => visitor.visit_byte_buf(s.into_bytes()),
+            others => Deserializer::new(others).deserialize_any(visitor),
+        }
+    }
+
+    fn deserialize_byte_buf<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        self.deserialize_bytes(visitor)
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        match self.resp {
+            Resp::Null => visitor.visit_none(),
+            Resp::Error(msg) => Err(Error::from_server_message(&msg)),
+            resp => visitor.visit_some(Deserializer::new(resp)),
+        }
+    }
+
+    // nil, or the OK of commands replying nothing else
+    fn deserialize_unit<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        match self.resp {
+            Resp::Null => visitor.visit_unit(),
+            Resp::SimpleString(s) if s == "OK" => visitor.visit_unit(),
+            others => Err(invalid_type(&others, &visitor)),
+        }
+    }
+
+    fn deserialize_unit_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        self.deserialize_unit(visitor)
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_seq<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        match self.resp {
+            Resp::Array(items) => visitor.visit_seq(SeqDeserializer::new(items)),
+            others => Err(invalid_type(&others, &visitor)),
+        }
+    }
+
+    fn deserialize_tuple<V: Visitor<'de>>(
+        self,
+        _len: usize,
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        self.deserialize_seq(visitor)
+    }
+
+    fn deserialize_tuple_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _len: usize,
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        self.deserialize_seq(visitor)
+    }
+
+    fn deserialize_map<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        match self.resp {
+            Resp::Array(items) if items.len().is_multiple_of(2) => {
+                visitor.visit_map(MapDeserializer::new(items))
+            }
+            Resp::Array(items) => Err(<Error as de::Error>::invalid_length(items.len(), &visitor)),
+            others => Err(invalid_type(&others, &visitor)),
+        }
+    }
+
+    fn deserialize_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        self.deserialize_map(visitor)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        let variant: de::value::StringDeserializer<Error> =
+            self.into_string(&visitor)?.into_deserializer();
+        visitor.visit_enum(variant)
+    }
+
+    fn deserialize_ignored_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_unit()
+    }
+}
+
+struct SeqDeserializer {
+    iter: std::vec::IntoIter<Resp>,
+}
+
+impl SeqDeserializer {
+    fn new(items: Vec<Resp>) -> SeqDeserializer {
+        SeqDeserializer {
+            iter: items.into_iter(),
+        }
+    }
+}
+
+impl<'de> SeqAccess<'de> for SeqDeserializer {
+    type Error = Error;
+
+    fn next_element_seed<T: DeserializeSeed<'de>>(
+        &mut self,
+        seed: T,
+    ) -> Result<Option<T::Value>, Error> {
+        match self.iter.next() {
+            Some(item) => seed.deserialize(Deserializer::new(item)).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.iter.len())
+    }
+}
+
+// [field1, value1, field2, value2, ...]
+struct MapDeserializer {
+    iter: std::vec::IntoIter<Resp>,
+    value: Option<Resp>,
+}
+
+impl MapDeserializer {
+    fn new(items: Vec<Resp>) -> MapDeserializer {
+        MapDeserializer {
+            iter: items.into_iter(),
+            value: None,
+        }
+    }
+}
+
+impl<'de> MapAccess<'de> for MapDeserializer {
+    type Error = Error;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(
+        &mut self,
+        seed: K,
+    ) -> Result<Option<K::Value>, Error> {
+        match self.iter.next() {
+            Some(key) => {
+                self.value = self.iter.next();
+                seed.deserialize(Deserializer::new(key)).map(Some)
+            }
+            None => Ok(None),
+        }
+    }
+
+    fn next_value_seed<V: DeserializeSeed<'de>>(&mut self, seed: V) -> Result<V::Value, Error> {
+        match self.value.take() {
+            Some(value) => seed.deserialize(Deserializer::new(value)),
+            None => Err(<Error as de::Error>::custom("value is missing")),
+        }
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.iter.len() / 2)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::bulk;
+    use serde::Deserialize;
+    use std::collections::HashMap;
+
+    #[derive(Debug, PartialEq, Deserialize)]
+    #[serde(rename_all = "lowercase")]
+    enum Status {
+        Active,
+        Banned,
+    }
+
+    #[derive(Debug, PartialEq, Deserialize)]
+    struct User {
+        name: String,
+        age: u32,
+        admin: bool,
+        status: Status,
+        email: Option<String>,
+    }
+
+    #[test]
+    fn struct_from_fields() {
+        let resp = Resp::Array(vec![
+            bulk("name"),
+            bulk("alice"),
+            bulk("age"),
+            bulk("42"),
+            bulk("admin"),
+            bulk("1"),
+            bulk("status"),
+            bulk("active"),
+            bulk("ignored"),
+            bulk("x"),
+        ]);
+        let user: User = from_resp(resp).unwrap();
+        assert_eq!(
+            User {
+                name: "alice".to_string(),
+                age: 42,
+                admin: true,
+                status: Status::Active,
+                email: None,
+            },
+            user
+        );
+
+        let resp = Resp::Array(vec![bulk("name"), bulk("bob"), bulk("age"), bulk("x")]);
+        assert!(matches!(
+            from_resp::<User>(resp),
+            Err(Error::Serialization(_))
+        ));
+    }
+
+    #[test]
+    fn sequences_and_maps() {
+        // XRANGE entries
+        let resp = Resp::Array(vec![Resp::Array(vec![
+            bulk("1-0"),
+            Resp::Array(vec![bulk("temp"), bulk("21.5")]),
+        ])]);
+        let entries: Vec<(String, HashMap<String, f64>)> = from_resp(resp).unwrap();
+        assert_eq!("1-0", entries[0].0);
+        assert_eq!(Some(&21.5), entries[0].1.get("temp"));
+
+        let resp = Resp::Array(vec![Resp::Integer(1), Resp::Null, bulk("3")]);
+        let values: Vec<Option<i64>> = from_resp(resp).unwrap();
+        assert_eq!(vec![Some(1), None, Some(3)], values);
+
+        let resp = Resp::Error("WRONGTYPE Operation against a key".to_string());
+        assert!(matches!(
+            from_resp::<Vec<String>>(resp),
+            Err(Error::WrongType(_))
+        ));
+    }
+}
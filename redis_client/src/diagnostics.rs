@@ -0,0 +1,316 @@
+use crate::{error::Error, resp::Resp};
+use bytes::Bytes;
+use std::collections::{BTreeMap, HashMap};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Reply of INFO. Fields are kept as strings since their set and types vary
+/// between server versions; `get` parses the ones needed.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Info {
+    pub server: HashMap<String, String>,
+    pub clients: HashMap<String, String>,
+    pub memory: HashMap<String, String>,
+    pub replication: HashMap<String, String>,
+    /// Keys of each non-empty database, by index
+    pub keyspace: BTreeMap<u64, KeyspaceInfo>,
+    /// The other sections (stats, persistence, cpu, ...) by lowercase name
+    pub sections: HashMap<String, HashMap<String, String>>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct KeyspaceInfo {
+    pub keys: u64,
+    pub expires: u64,
+    pub avg_ttl: Duration,
+}
+
+impl Info {
+    pub fn parse(text: &str) -> Result<Info, Error> {
+        let mut info = Info::default();
+        let mut section = String::new();
+
+        for line in text.lines().map(str::trim).filter(|l| !l.is_empty()) {
+            if let Some(name) = line.strip_prefix('#') {
+                section = name.trim().to_ascii_lowercase();
+                continue;
+            }
+            let (name, value) = line
+                .split_once(':')
+                .ok_or_else(|| Error::Invalid(format!("invalid INFO line: {:?}", line)))?;
+
+            let fields = match section.as_str() {
+                "keyspace" => {
+                    if let Some(db) = name.strip_prefix("db").and_then(|db| db.parse().ok()) {
+                        info.keyspace.insert(db, KeyspaceInfo::parse(value)?);
+                    }
+                    continue;
+                }
+                "server" => &mut info.server,
+                "clients" => &mut info.clients,
+                "memory" => &mut info.memory,
+                "replication" => &mut info.replication,
+                other => info.sections.entry(other.to_string()).or_default(),
+            };
+            fields.insert(name.to_string(), value.to_string());
+        }
+        Ok(info)
+    }
+
+    /// Looks up a field in every section and parses it, such as
+    /// `info.get::<u64>("connected_clients")`.
+    pub fn get<T: std::str::FromStr>(&self, field: &str) -> Option<T> {
+        [&self.server, &self.clients, &self.memory, &self.replication]
+            .into_iter()
+            .chain(self.sections.values())
+            .find_map(|fields| fields.get(field))
+            .and_then(|value| value.parse().ok())
+    }
+}
+
+impl KeyspaceInfo {
+    // keys=1,expires=0,avg_ttl=0
+    fn parse(value: &str) -> Result<KeyspaceInfo, Error> {
+        let mut info = KeyspaceInfo::default();
+        for pair in value.split(',') {
+            let (name, n) = pair
+                .split_once('=')
+                .ok_or_else(|| Error::Invalid(format!("invalid keyspace info: {:?}", value)))?;
+            let n = n
+                .parse()
+                .map_err(|_| Error::Invalid(format!("invalid keyspace info: {:?}", value)))?;
+            match name {
+                "keys" => info.keys = n,
+                "expires" => info.expires = n,
+                "avg_ttl" => info.avg_ttl = Duration::from_millis(n),
+                _ => {} // fields added by newer versions
+            }
+        }
+        Ok(info)
+    }
+}
+
+/// An entry of SLOWLOG GET.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SlowlogEntry {
+    pub id: u64,
+    pub timestamp: SystemTime,
+    pub duration: Duration,
+    pub args: Vec<Bytes>,
+    /// Not reported by servers before 4.0
+    pub client_addr: Option<String>,
+    pub client_name: Option<String>,
+}
+
+impl SlowlogEntry {
+    // [id, timestamp, microseconds, [arg...], client address, client name]
+    pub(crate) fn from_resp(resp: Resp) -> Result<SlowlogEntry, Error> {
+        let mut iter = resp.into_array()?.into_iter();
+        match (iter.next(), iter.next(), iter.next(), iter.next()) {
+            (Some(id), Some(timestamp), Some(micros), Some(args)) => Ok(SlowlogEntry {
+                id: id.into_integer()? as u64,
+                timestamp: UNIX_EPOCH + Duration::from_secs(timestamp.into_integer()? as u64),
+                duration: Duration::from_micros(micros.into_integer()? as u64),
+                args: args
+                    .into_array()?
+                    .into_iter()
+                    .map(Resp::into_bulk)
+                    .collect::<Result<_, _>>()?,
+                client_addr: iter.next().map(Resp::into_string).transpose()?,
+                client_name: iter.next().map(Resp::into_string).transpose()?,
+            }),
+            _ => Err(Error::Invalid(
+                "[id, timestamp, duration, args, ...] is expected".to_string(),
+            )),
+        }
+    }
+}
+
+/// The latest latency spike of an event, from LATENCY LATEST.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LatencyEvent {
+    pub event: String,
+    pub timestamp: SystemTime,
+    pub latest: Duration,
+    pub max: Duration,
+}
+
+impl LatencyEvent {
+    // [event, timestamp, latest ms, max ms]
+    pub(crate) fn from_resp(resp: Resp) -> Result<LatencyEvent, Error> {
+        let mut iter = resp.into_array()?.into_iter();
+        match (iter.next(), iter.next(), iter.next(), iter.next()) {
+            (Some(event), Some(timestamp), Some(latest), Some(max)) => Ok(LatencyEvent {
+                event: event.into_string()?,
+                timestamp: UNIX_EPOCH + Duration::from_secs(timestamp.into_integer()? as u64),
+                latest: Duration::from_millis(latest.into_integer()? as u64),
+                max: Duration::from_millis(max.into_integer()? as u64),
+            }),
+            _ => Err(Error::Invalid(
+                "[event, timestamp, latest, max] is expected".to_string(),
+            )),
+        }
+    }
+}
+
+/// A latency spike from LATENCY HISTORY.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LatencySample {
+    pub timestamp: SystemTime,
+    pub latency: Duration,
+}
+
+impl LatencySample {
+    // [timestamp, latency ms]
+    pub(crate) fn from_resp(resp: Resp) -> Result<LatencySample, Error> {
+        let mut iter = resp.into_array()?.into_iter();
+        match (iter.next(), iter.next()) {
+            (Some(timestamp), Some(latency)) => Ok(LatencySample {
+                timestamp: UNIX_EPOCH + Duration::from_secs(timestamp.into_integer()? as u64),
+                latency: Duration::from_millis(latency.into_integer()? as u64),
+            }),
+            _ => Err(Error::Invalid(
+                "[timestamp, latency] is expected".to_string(),
+            )),
+        }
+    }
+}
+
+/// Reply of MEMORY STATS. Sizes are in bytes.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MemoryStats {
+    pub peak_allocated: u64,
+    pub total_allocated: u64,
+    pub startup_allocated: u64,
+    pub overhead_total: u64,
+    pub keys_count: u64,
+    pub dataset_bytes: u64,
+    pub dataset_percentage: f64,
+    pub fragmentation: f64,
+    /// Overhead of the main and expires hash tables of each database
+    pub dbs: BTreeMap<u64, DbMemory>,
+    /// The other statistics, which vary between server versions
+    pub other: HashMap<String, Resp>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DbMemory {
+    pub overhead_hashtable_main: u64,
+    pub overhead_hashtable_expires: u64,
+}
+
+impl MemoryStats {
+    pub(crate) fn from_resp(resp: Resp) -> Result<MemoryStats, Error> {
+        let mut stats = MemoryStats::default();
+        for (name, value) in resp.into_fields()? {
+            match name.as_str() {
+                "peak.allocated" => stats.peak_allocated = value.into_integer()? as u64,
+                "total.allocated" => stats.total_allocated = value.into_integer()? as u64,
+                "startup.allocated" => stats.startup_allocated = value.into_integer()? as u64,
+                "overhead.total" => stats.overhead_total = value.into_integer()? as u64,
+                "keys.count" => stats.keys_count = value.into_integer()? as u64,
+                "dataset.bytes" => stats.dataset_bytes = value.into_integer()? as u64,
+                "dataset.percentage" => stats.dataset_percentage = value.into_float()?,
+                "fragmentation" => stats.fragmentation = value.into_float()?,
+                name => match name.strip_prefix("db.").and_then(|db| db.parse().ok()) {
+                    Some(db) => {
+                        stats.dbs.insert(db, DbMemory::from_resp(value)?);
+                    }
+                    None => {
+                        stats.other.insert(name.to_string(), value);
+                    }
+                },
+            }
+        }
+        Ok(stats)
+    }
+}
+
+impl DbMemory {
+    fn from_resp(resp: Resp) -> Result<DbMemory, Error> {
+        let mut db = DbMemory::default();
+        for (name, value) in resp.into_fields()? {
+            match name.as_str() {
+                "overhead.hashtable.main" => {
+                    db.overhead_hashtable_main = value.into_integer()? as u64
+                }
+                "overhead.hashtable.expires" => {
+                    db.overhead_hashtable_expires = value.into_integer()? as u64
+                }
+                _ => {}
+            }
+        }
+        Ok(db)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_info() {
+        let text = "# Server\r\nredis_version:7.2.4\r\nuptime_in_seconds:42\r\n\r\n\
+                    # Clients\r\nconnected_clients:3\r\n\r\n\
+                    # Stats\r\ntotal_commands_processed:100\r\n\r\n\
+                    # Keyspace\r\ndb0:keys=5,expires=1,avg_ttl=2000\r\ndb3:keys=1,expires=0,avg_ttl=0\r\n";
+        let info = Info::parse(text).unwrap();
+
+        assert_eq!(
+            Some("7.2.4"),
+            info.server.get("redis_version").map(String::as_str)
+        );
+        assert_eq!(Some(3), info.get::<u64>("connected_clients"));
+        assert_eq!(Some(100), info.get::<u64>("total_commands_processed"));
+        assert_eq!(None, info.get::<u64>("missing"));
+        assert_eq!(
+            KeyspaceInfo {
+                keys: 5,
+                expires: 1,
+                avg_ttl: Duration::from_secs(2)
+            },
+            info.keyspace[&0]
+        );
+        assert_eq!(1, info.keyspace[&3].keys);
+    }
+
+    #[test]
+    fn parse_slowlog_entry() {
+        let resp = Resp::Array(vec![
+            Resp::Integer(14),
+            Resp::Integer(1309448221),
+            Resp::Integer(15),
+            Resp::Array(vec![Resp::BulkString(Bytes::from("ping"))]),
+            Resp::BulkString(Bytes::from("127.0.0.1:58217")),
+            Resp::BulkString(Bytes::from("worker-123")),
+        ]);
+        let entry = SlowlogEntry::from_resp(resp).unwrap();
+        assert_eq!(14, entry.id);
+        assert_eq!(Duration::from_micros(15), entry.duration);
+        assert_eq!(vec![Bytes::from("ping")], entry.args);
+        assert_eq!(Some("worker-123".to_string()), entry.client_name);
+    }
+
+    #[test]
+    fn parse_memory_stats() {
+        let resp = Resp::Array(vec![
+            Resp::BulkString(Bytes::from("peak.allocated")),
+            Resp::Integer(1000),
+            Resp::BulkString(Bytes::from("db.0")),
+            Resp::Array(vec![
+                Resp::BulkString(Bytes::from("overhead.hashtable.main")),
+                Resp::Integer(72),
+                Resp::BulkString(Bytes::from("overhead.hashtable.expires")),
+                Resp::Integer(0),
+            ]),
+            Resp::BulkString(Bytes::from("fragmentation")),
+            Resp::BulkString(Bytes::from("1.5")),
+            Resp::BulkString(Bytes::from("aof.buffer")),
+            Resp::Integer(0),
+        ]);
+        let stats = MemoryStats::from_resp(resp).unwrap();
+        assert_eq!(1000, stats.peak_allocated);
+        assert_eq!(1.5, stats.fragmentation);
+        assert_eq!(72, stats.dbs[&0].overhead_hashtable_main);
+        assert_eq!(Some(&Resp::Integer(0)), stats.other.get("aof.buffer"));
+    }
+}
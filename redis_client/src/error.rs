@@ -1,16 +1,218 @@
-use std::io;
+use std::{fmt, io};
 
 #[derive(Debug)]
 pub enum Error {
     IO(io::Error),
     Serialization(String),
     Invalid(String),
-    RedisError(String),
+
+    // Error replies from the server. Each variant keeps the message without the code.
+    /// WRONGTYPE: operation against a key holding the wrong kind of value
+    WrongType(String),
+    /// MOVED: the slot is served by another node of the cluster
+    Moved {
+        slot: u16,
+        addr: String,
+    },
+    /// ASK: the slot is being migrated and the key should be asked to another node once
+    Ask {
+        slot: u16,
+        addr: String,
+    },
+    /// NOSCRIPT: no script matches the SHA1 digest
+    NoScript(String),
+    /// LOADING: the server is loading the dataset in memory
+    Loading(String),
+    /// BUSY: the server is busy running a script or function
+    Busy(String),
+    /// READONLY: write against a read only replica
+    ReadOnly(String),
+    /// NOAUTH: authentication is required
+    NoAuth(String),
+    /// EXECABORT: the transaction was discarded because of previous errors
+    ExecAbort(String),
+    /// Any other error reply, such as `ERR unknown command`
+    Generic {
+        code: String,
+        message: String,
+    },
+
     Other(String),
 }
 
-impl Into<Error> for io::Error {
-    fn into(self) -> Error {
-        Error::IO(self)
+impl Error {
+    /// Parses an error reply (without the leading `-`) by its code, the first
+    /// word of the message.
+    pub fn from_server_message(msg: &str) -> Error {
+        let (code, message) = msg.split_once(' ').unwrap_or((msg, ""));
+        let message = message.to_string();
+        match code {
+            "WRONGTYPE" => Error::WrongType(message),
+            "MOVED" | "ASK" => match parse_redirection(&message) {
+                Some((slot, addr)) if code == "MOVED" => Error::Moved { slot, addr },
+                Some((slot, addr)) => Error::Ask { slot, addr },
+                None => Error::Generic {
+                    code: code.to_string(),
+                    message,
+                },
+            },
+            "NOSCRIPT" => Error::NoScript(message),
+            "LOADING" => Error::Loading(message),
+            "BUSY" => Error::Busy(message),
+            "READONLY" => Error::ReadOnly(message),
+            "NOAUTH" => Error::NoAuth(message),
+            "EXECABORT" => Error::ExecAbort(message),
+            code => Error::Generic {
+                code: code.to_string(),
+                message,
+            },
+        }
+    }
+
+    // the server sent an invalid frame: the replies that follow cannot be
+    // delimited, so the connection must not be used anymore
+    pub(crate) fn protocol(msg: String) -> Error {
+        Error::IO(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("protocol error: {}", msg),
+        ))
+    }
+
+    // the server closed the connection while a reply was expected
+    pub(crate) fn closed() -> Error {
+        Error::IO(io::Error::new(
+            io::ErrorKind::UnexpectedEof,
+            "connection closed by server",
+        ))
+    }
+
+    /// Returns true for errors replied by the server (as opposed to I/O and protocol errors).
+    pub fn is_server_error(&self) -> bool {
+        matches!(
+            self,
+            Error::WrongType(_)
+                | Error::Moved { .. }
+                | Error::Ask { .. }
+                | Error::NoScript(_)
+                | Error::Loading(_)
+                | Error::Busy(_)
+                | Error::ReadOnly(_)
+                | Error::NoAuth(_)
+                | Error::ExecAbort(_)
+                | Error::Generic { .. }
+        )
+    }
+
+    /// Returns true if the connection is unusable and should be reestablished,
+    /// after an I/O error, a timeout or an invalid frame from the server.
+    pub fn is_connection_error(&self) -> bool {
+        matches!(self, Error::IO(_))
+    }
+
+    /// Returns true if the same command may succeed when retried, possibly
+    /// on a new connection or after a while.
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            Error::IO(e) => matches!(
+                e.kind(),
+                io::ErrorKind::ConnectionRefused
+                    | io::ErrorKind::ConnectionReset
+                    | io::ErrorKind::ConnectionAborted
+                    | io::ErrorKind::BrokenPipe
+                    | io::ErrorKind::TimedOut
+                    | io::ErrorKind::Interrupted
+                    | io::ErrorKind::UnexpectedEof
+            ),
+            Error::Loading(_) | Error::Busy(_) | Error::ReadOnly(_) => true,
+            Error::Moved { .. } | Error::Ask { .. } => true,
+            Error::Generic { code, .. } => {
+                matches!(code.as_str(), "TRYAGAIN" | "CLUSTERDOWN" | "MASTERDOWN")
+            }
+            _ => false,
+        }
+    }
+}
+
+// "3999 127.0.0.1:6381"
+fn parse_redirection(message: &str) -> Option<(u16, String)> {
+    let (slot, addr) = message.split_once(' ')?;
+    Some((slot.parse().ok()?, addr.to_string()))
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::IO(e) => write!(f, "io error: {}", e),
+            Error::Serialization(msg) => write!(f, "protocol error: {}", msg),
+            Error::Invalid(msg) => write!(f, "invalid response: {}", msg),
+            Error::WrongType(msg) => write!(f, "WRONGTYPE {}", msg),
+            Error::Moved { slot, addr } => write!(f, "MOVED {} {}", slot, addr),
+            Error::Ask { slot, addr } => write!(f, "ASK {} {}", slot, addr),
+            Error::NoScript(msg) => write!(f, "NOSCRIPT {}", msg),
+            Error::Loading(msg) => write!(f, "LOADING {}", msg),
+            Error::Busy(msg) => write!(f, "BUSY {}", msg),
+            Error::ReadOnly(msg) => write!(f, "READONLY {}", msg),
+            Error::NoAuth(msg) => write!(f, "NOAUTH {}", msg),
+            Error::ExecAbort(msg) => write!(f, "EXECABORT {}", msg),
+            Error::Generic { code, message } => write!(f, "{} {}", code, message),
+            Error::Other(msg) => write!(f, "{}", msg),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::IO(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(e: io::Error) -> Error {
+        Error::IO(e)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_server_error() {
+        assert!(matches!(
+            Error::from_server_message("WRONGTYPE Operation against a key holding the wrong kind of value"),
+            Error::WrongType(msg) if msg == "Operation against a key holding the wrong kind of value"
+        ));
+        assert!(matches!(
+            Error::from_server_message("MOVED 3999 127.0.0.1:6381"),
+            Error::Moved { slot: 3999, addr } if addr == "127.0.0.1:6381"
+        ));
+        assert!(matches!(
+            Error::from_server_message("ASK 3999 127.0.0.1:6381"),
+            Error::Ask { slot: 3999, .. }
+        ));
+        assert!(matches!(
+            Error::from_server_message("ERR unknown command 'FOO'"),
+            Error::Generic { code, message } if code == "ERR" && message == "unknown command 'FOO'"
+        ));
+        assert!(matches!(
+            Error::from_server_message("BUSYGROUP Consumer Group name already exists"),
+            Error::Generic { code, .. } if code == "BUSYGROUP"
+        ));
+    }
+
+    #[test]
+    fn classify() {
+        assert!(Error::from_server_message("LOADING Redis is loading").is_retryable());
+        assert!(Error::from_server_message("TRYAGAIN Multiple keys request").is_retryable());
+        assert!(!Error::from_server_message("ERR syntax error").is_retryable());
+        assert!(Error::from_server_message("ERR syntax error").is_server_error());
+
+        let e = Error::from(io::Error::new(io::ErrorKind::ConnectionReset, "reset"));
+        assert!(e.is_retryable());
+        assert!(e.is_connection_error());
+        assert!(!e.is_server_error());
     }
 }
@@ -0,0 +1,150 @@
+use crate::{error::Error, resp::Resp};
+
+/// How FUNCTION RESTORE deals with existing libraries.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RestorePolicy {
+    /// Delete all existing libraries before restoring
+    Flush,
+    /// Fail if a library already exists (the default of Redis)
+    Append,
+    /// Replace existing libraries with the same names
+    Replace,
+}
+
+/// An element of FUNCTION LIST.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LibraryInfo {
+    pub name: String,
+    pub engine: String,
+    pub functions: Vec<FunctionInfo>,
+    /// Only set with WITHCODE
+    pub code: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FunctionInfo {
+    pub name: String,
+    pub description: Option<String>,
+    pub flags: Vec<String>,
+}
+
+impl LibraryInfo {
+    pub(crate) fn from_resp(resp: Resp) -> Result<LibraryInfo, Error> {
+        let mut info = LibraryInfo {
+            name: String::new(),
+            engine: String::new(),
+            functions: vec![],
+            code: None,
+        };
+
+        for (name, value) in resp.into_fields()? {
+            match name.as_str() {
+                "library_name" => info.name = value.into_string()?,
+                "engine" => info.engine = value.into_string()?,
+                "functions" => {
+                    info.functions = value
+                        .into_array()?
+                        .into_iter()
+                        .map(FunctionInfo::from_resp)
+                        .collect::<Result<_, _>>()?
+                }
+                "library_code" => info.code = Some(value.into_string()?),
+                _ => {}
+            }
+        }
+        Ok(info)
+    }
+}
+
+impl FunctionInfo {
+    fn from_resp(resp: Resp) -> Result<FunctionInfo, Error> {
+        let mut info = FunctionInfo {
+            name: String::new(),
+            description: None,
+            flags: vec![],
+        };
+
+        for (name, value) in resp.into_fields()? {
+            match name.as_str() {
+                "name" => info.name = value.into_string()?,
+                "description" => {
+                    info.description = match value {
+                        Resp::Null => None,
+                        value => Some(value.into_string()?),
+                    }
+                }
+                "flags" => {
+                    info.flags = value
+                        .into_array()?
+                        .into_iter()
+                        .map(Resp::into_string)
+                        .collect::<Result<_, _>>()?
+                }
+                _ => {}
+            }
+        }
+        Ok(info)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::bulk;
+
+    fn function(name: &str, description: Resp, flags: &[&str]) -> Resp {
+        Resp::Array(vec![
+            bulk("name"),
+            bulk(name),
+            bulk("description"),
+            description,
+            bulk("flags"),
+            Resp::Array(flags.iter().map(bulk).collect()),
+        ])
+    }
+
+    #[test]
+    fn parse_library_info() {
+        // an element of FUNCTION LIST WITHCODE
+        let resp = Resp::Array(vec![
+            bulk("library_name"),
+            bulk("mylib"),
+            bulk("engine"),
+            bulk("LUA"),
+            bulk("functions"),
+            Resp::Array(vec![
+                function("myfunc", Resp::Null, &[]),
+                function("myget", bulk("reads a key"), &["no-writes", "allow-stale"]),
+            ]),
+            bulk("library_code"),
+            bulk("#!lua name=mylib\n..."),
+        ]);
+
+        assert_eq!(
+            LibraryInfo {
+                name: "mylib".to_string(),
+                engine: "LUA".to_string(),
+                functions: vec![
+                    FunctionInfo {
+                        name: "myfunc".to_string(),
+                        description: None,
+                        flags: vec![],
+                    },
+                    FunctionInfo {
+                        name: "myget".to_string(),
+                        description: Some("reads a key".to_string()),
+                        flags: vec!["no-writes".to_string(), "allow-stale".to_string()],
+                    },
+                ],
+                code: Some("#!lua name=mylib\n...".to_string()),
+            },
+            LibraryInfo::from_resp(resp).unwrap()
+        );
+    }
+
+    #[test]
+    fn parse_invalid_library_info() {
+        let resp = Resp::Array(vec![bulk("functions"), bulk("not an array")]);
+        assert!(LibraryInfo::from_resp(resp).is_err());
+    }
+}
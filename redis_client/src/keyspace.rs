@@ -0,0 +1,252 @@
+use crate::{
+    client::Client,
+    error::Error,
+    subscriber::{Message, Subscriber},
+};
+use bytes::Bytes;
+
+/// A keyspace notification. Keys are kept as bytes since they need not be UTF-8.
+///
+/// See https://redis.io/docs/manual/keyspace-notifications/ for the events
+/// each command generates.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum KeyspaceEvent {
+    Set {
+        key: Bytes,
+    },
+    Del {
+        key: Bytes,
+    },
+    Expire {
+        key: Bytes,
+    },
+    Expired {
+        key: Bytes,
+    },
+    Evicted {
+        key: Bytes,
+    },
+    New {
+        key: Bytes,
+    },
+    RenameFrom {
+        key: Bytes,
+    },
+    RenameTo {
+        key: Bytes,
+    },
+    /// Any other event, such as `hset` or `lpush`
+    Other {
+        event: String,
+        key: Bytes,
+    },
+}
+
+impl KeyspaceEvent {
+    fn new(event: &str, key: Bytes) -> KeyspaceEvent {
+        match event {
+            "set" => KeyspaceEvent::Set { key },
+            "del" => KeyspaceEvent::Del { key },
+            "expire" => KeyspaceEvent::Expire { key },
+            "expired" => KeyspaceEvent::Expired { key },
+            "evicted" => KeyspaceEvent::Evicted { key },
+            "new" => KeyspaceEvent::New { key },
+            "rename_from" => KeyspaceEvent::RenameFrom { key },
+            "rename_to" => KeyspaceEvent::RenameTo { key },
+            event => KeyspaceEvent::Other {
+                event: event.to_string(),
+                key,
+            },
+        }
+    }
+
+    pub fn key(&self) -> &Bytes {
+        match self {
+            KeyspaceEvent::Set { key }
+            | KeyspaceEvent::Del { key }
+            | KeyspaceEvent::Expire { key }
+            | KeyspaceEvent::Expired { key }
+            | KeyspaceEvent::Evicted { key }
+            | KeyspaceEvent::New { key }
+            | KeyspaceEvent::RenameFrom { key }
+            | KeyspaceEvent::RenameTo { key }
+            | KeyspaceEvent::Other { key, .. } => key,
+        }
+    }
+
+    /// Decodes a message of `__keyspace@<db>__:<key>` (whose payload is the
+    /// event) or `__keyevent@<db>__:<event>` (whose payload is the key).
+    /// Returns None for other channels.
+    pub fn parse(channel: &[u8], payload: &[u8]) -> Option<KeyspaceEvent> {
+        let rest = channel.strip_prefix(b"__")?;
+        let at = rest.iter().position(|&b| b == b'@')?;
+        let (kind, rest) = (&rest[..at], &rest[at + 1..]);
+        let name = rest.windows(3).position(|w| w == b"__:")?;
+        let name = &rest[name + 3..];
+
+        match kind {
+            b"keyspace" => Some(KeyspaceEvent::new(
+                &String::from_utf8_lossy(payload),
+                Bytes::copy_from_slice(name),
+            )),
+            b"keyevent" => Some(KeyspaceEvent::new(
+                &String::from_utf8_lossy(name),
+                Bytes::copy_from_slice(payload),
+            )),
+            _ => None,
+        }
+    }
+}
+
+/// Which channels notifications are received on.
+///
+/// Each event is published on both kinds of channels if enabled, so only one
+/// of them is subscribed to avoid receiving events twice.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum KeyspaceChannel {
+    /// `__keyspace@<db>__:<key pattern>`, to watch keys matching the pattern
+    Keyspace(String),
+    /// `__keyevent@<db>__:<event pattern>`, to watch events matching the pattern
+    Keyevent(String),
+}
+
+#[derive(Debug, Clone)]
+pub struct KeyspaceOptions {
+    pub db: u64,
+    pub channel: KeyspaceChannel,
+    /// If set, `notify-keyspace-events` is set to this value with CONFIG SET
+    /// before subscribing (e.g. "Kx" for expiry events on keyspace channels)
+    pub notify_keyspace_events: Option<String>,
+}
+
+impl Default for KeyspaceOptions {
+    fn default() -> Self {
+        KeyspaceOptions {
+            db: 0,
+            channel: KeyspaceChannel::Keyevent("*".to_string()),
+            notify_keyspace_events: None,
+        }
+    }
+}
+
+/// Yields keyspace notifications as typed events.
+pub struct KeyspaceSubscriber<'a> {
+    subscriber: Subscriber<'a>,
+}
+
+impl<'a> KeyspaceSubscriber<'a> {
+    pub async fn new(
+        client: &'a mut Client,
+        options: KeyspaceOptions,
+    ) -> Result<KeyspaceSubscriber<'a>, Error> {
+        if let Some(flags) = &options.notify_keyspace_events {
+            client.config_set("notify-keyspace-events", flags).await?;
+        }
+
+        let pattern = match &options.channel {
+            KeyspaceChannel::Keyspace(keys) => format!("__keyspace@{}__:{}", options.db, keys),
+            KeyspaceChannel::Keyevent(events) => {
+                format!("__keyevent@{}__:{}", options.db, events)
+            }
+        };
+        let subscriber = client.psubscribe(vec![pattern]).await?;
+        Ok(KeyspaceSubscriber { subscriber })
+    }
+
+    pub async fn next_event(&mut self) -> Result<KeyspaceEvent, Error> {
+        loop {
+            match self.subscriber.next_message().await? {
+                Message::PMessage(_, channel, payload) => {
+                    if let Some(event) = KeyspaceEvent::parse(&channel, &payload) {
+                        return Ok(event);
+                    }
+                }
+                others => {
+                    return Err(Error::Other(format!(
+                        "pmessage is expected, but {:?}",
+                        others
+                    )))
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{cmd, resp::Resp, testing::bulk, testing::MockServer};
+
+    #[test]
+    fn parse_keyspace_event() {
+        assert_eq!(
+            Some(KeyspaceEvent::Expired {
+                key: Bytes::from("session:1")
+            }),
+            KeyspaceEvent::parse(b"__keyspace@0__:session:1", b"expired")
+        );
+        assert_eq!(
+            Some(KeyspaceEvent::Set {
+                key: Bytes::from("session:1")
+            }),
+            KeyspaceEvent::parse(b"__keyevent@3__:set", b"session:1")
+        );
+        assert_eq!(
+            Some(KeyspaceEvent::Other {
+                event: "hset".to_string(),
+                key: Bytes::from("h")
+            }),
+            KeyspaceEvent::parse(b"__keyevent@0__:hset", b"h")
+        );
+        assert_eq!(None, KeyspaceEvent::parse(b"news", b"hello"));
+    }
+
+    #[tokio::test]
+    async fn next_event_with_binary_key() {
+        let server = MockServer::start().await;
+        server
+            .expect(cmd!["PSUBSCRIBE", "__keyspace@0__:*"])
+            .replies(vec![
+                Resp::Array(vec![
+                    bulk("psubscribe"),
+                    bulk("__keyspace@0__:*"),
+                    Resp::Integer(1),
+                ]),
+                Resp::Array(vec![
+                    bulk("pmessage"),
+                    bulk("__keyspace@0__:*"),
+                    bulk(b"__keyspace@0__:img\xff\xd8"),
+                    bulk("set"),
+                ]),
+                Resp::Array(vec![
+                    bulk("pmessage"),
+                    bulk("__keyspace@0__:*"),
+                    bulk("__keyspace@0__:session"),
+                    bulk("expired"),
+                ]),
+            ]);
+
+        let mut client = crate::client::connect(server.addr()).await.unwrap();
+        let options = KeyspaceOptions {
+            channel: KeyspaceChannel::Keyspace("*".to_string()),
+            ..KeyspaceOptions::default()
+        };
+        let mut subscriber = KeyspaceSubscriber::new(&mut client, options).await.unwrap();
+        // the key can be used as is in later commands
+        let event = subscriber.next_event().await.unwrap();
+        assert_eq!(
+            KeyspaceEvent::Set {
+                key: Bytes::from_static(b"img\xff\xd8")
+            },
+            event
+        );
+        assert_eq!(&b"img\xff\xd8"[..], event.key());
+        assert_eq!(
+            KeyspaceEvent::Expired {
+                key: Bytes::from("session")
+            },
+            subscriber.next_event().await.unwrap()
+        );
+        server.assert_done();
+    }
+}
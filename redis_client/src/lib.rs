@@ -1,8 +1,28 @@
+mod cache;
 mod command;
 mod connection;
 mod pubsub;
-mod resp;
+mod scan;
 
+pub mod blocking;
 pub mod client;
+pub mod codec;
+#[cfg(feature = "serde")]
+pub mod de;
+pub mod diagnostics;
 pub mod error;
+pub mod functions;
+pub mod keyspace;
+pub mod lock;
+pub mod metrics;
+pub mod middleware;
+pub mod monitor;
+pub mod resp;
+pub mod script;
+#[cfg(feature = "serde")]
+pub mod ser;
+pub mod stream_consumer;
+pub mod streams;
 pub mod subscriber;
+#[cfg(any(test, feature = "testing"))]
+pub mod testing;
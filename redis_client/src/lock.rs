@@ -0,0 +1,619 @@
+use crate::{
+    client::{Client, SetOptions},
+    error::Error,
+    script::Script,
+};
+use bytes::Bytes;
+use futures_util::future::join_all;
+use rand::Rng;
+use std::time::Duration;
+use tokio::time::{self, Instant};
+
+// Deletes the key only if it still holds our token
+const RELEASE_SCRIPT: &str = r#"
+if redis.call("GET", KEYS[1]) == ARGV[1] then
+    return redis.call("DEL", KEYS[1])
+else
+    return 0
+end
+"#;
+
+// Resets the expiry only if the key still holds our token
+const EXTEND_SCRIPT: &str = r#"
+if redis.call("GET", KEYS[1]) == ARGV[1] then
+    return redis.call("PEXPIRE", KEYS[1], ARGV[2])
+else
+    return 0
+end
+"#;
+
+fn new_token() -> String {
+    rand::thread_rng()
+        .sample_iter(&rand::distributions::Alphanumeric)
+        .take(20)
+        .map(char::from)
+        .collect()
+}
+
+// SET with PX 0 is rejected by the server
+fn check_ttl(ttl: Duration) -> Result<(), Error> {
+    if ttl.as_millis() == 0 {
+        return Err(Error::Invalid(
+            "lock ttl must be at least 1 millisecond".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// A lock held on a single Redis instance.
+///
+/// The lock is a key set with `SET key token NX PX ttl`, where the token is a
+/// random value only this holder knows. `release` and `extend` check the token
+/// atomically with a script, so an expired lock that has been taken by someone
+/// else is never released or extended by mistake.
+///
+/// The lock is not released on drop; it expires after its ttl if `release` is
+/// never called.
+#[derive(Debug, Clone)]
+pub struct Lock {
+    key: String,
+    token: String,
+}
+
+impl Lock {
+    /// Tries to acquire the lock once. Returns None if it is held by someone else.
+    pub async fn acquire(
+        client: &mut Client,
+        key: &str,
+        ttl: Duration,
+    ) -> Result<Option<Lock>, Error> {
+        check_ttl(ttl)?;
+        let token = new_token();
+        if try_lock(client, key, &token, ttl).await? {
+            Ok(Some(Lock {
+                key: key.to_string(),
+                token,
+            }))
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// Retries `acquire` every `retry_delay` until `timeout` elapses.
+    pub async fn acquire_with_timeout(
+        client: &mut Client,
+        key: &str,
+        ttl: Duration,
+        timeout: Duration,
+        retry_delay: Duration,
+    ) -> Result<Option<Lock>, Error> {
+        let deadline = Instant::now() + timeout;
+        loop {
+            if let Some(lock) = Lock::acquire(client, key, ttl).await? {
+                return Ok(Some(lock));
+            }
+
+            if Instant::now() + retry_delay > deadline {
+                return Ok(None);
+            }
+            time::sleep(retry_delay).await;
+        }
+    }
+
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+
+    pub fn token(&self) -> &str {
+        &self.token
+    }
+
+    /// Returns false if the lock had already expired (and might be held by someone else).
+    pub async fn release(&self, client: &mut Client) -> Result<bool, Error> {
+        unlock(client, &self.key, &self.token).await
+    }
+
+    /// Resets the ttl of the lock. Returns false if the lock had already expired.
+    pub async fn extend(&self, client: &mut Client, ttl: Duration) -> Result<bool, Error> {
+        // PEXPIRE 0 would delete the lock
+        check_ttl(ttl)?;
+        let args = [
+            Bytes::from(self.token.clone()),
+            Bytes::from(ttl.as_millis().to_string()),
+        ];
+        Script::new(EXTEND_SCRIPT)
+            .invoke(client, &[&self.key], &args)
+            .await
+    }
+}
+
+async fn try_lock(
+    client: &mut Client,
+    key: &str,
+    token: &str,
+    ttl: Duration,
+) -> Result<bool, Error> {
+    let options = SetOptions {
+        nx: true,
+        expire: Some(ttl),
+        ..Default::default()
+    };
+    client
+        .set_with_options(key, Bytes::from(token.to_string()), options)
+        .await
+}
+
+async fn unlock(client: &mut Client, key: &str, token: &str) -> Result<bool, Error> {
+    Script::new(RELEASE_SCRIPT)
+        .invoke(client, &[key], &[Bytes::from(token.to_string())])
+        .await
+}
+
+// A request to a Redlock instance taking longer than this fraction of the ttl
+// counts as a failure, so that a slow instance cannot eat up the validity
+const INSTANCE_TIMEOUT_DIVISOR: u32 = 10;
+
+// The connection is poisoned on timeout since the reply may still arrive
+async fn try_lock_timeout(
+    client: &mut Client,
+    key: &str,
+    token: &str,
+    ttl: Duration,
+) -> Result<bool, Error> {
+    let timeout = ttl / INSTANCE_TIMEOUT_DIVISOR;
+    match time::timeout(timeout, try_lock(client, key, token, ttl)).await {
+        Ok(result) => result,
+        Err(_) => Err(client.poison("lock request timed out")),
+    }
+}
+
+async fn unlock_timeout(
+    client: &mut Client,
+    key: &str,
+    token: &str,
+    ttl: Duration,
+) -> Result<bool, Error> {
+    let timeout = ttl / INSTANCE_TIMEOUT_DIVISOR;
+    match time::timeout(timeout, unlock(client, key, token)).await {
+        Ok(result) => result,
+        Err(_) => Err(client.poison("unlock request timed out")),
+    }
+}
+
+/// The Redlock algorithm over independent Redis masters.
+///
+/// A lock is acquired when a majority of the instances accepted it within its
+/// ttl, and it is valid for the ttl minus the time spent acquiring it and an
+/// allowance for clock drift between the instances.
+/// Each request to an instance times out after a tenth of the ttl and counts
+/// as a failure. The connection of that instance is then replaced by a new one
+/// before the next request, since the late reply may still arrive on it.
+/// See https://redis.io/docs/manual/patterns/distributed-locks/
+pub struct Redlock {
+    clients: Vec<Client>,
+    retry_count: u32,
+    retry_delay: Duration,
+    // clock drift factor of the ttl
+    drift_factor: f64,
+}
+
+/// A lock acquired by `Redlock`.
+#[derive(Debug, Clone)]
+pub struct RedlockGuard {
+    key: String,
+    token: String,
+    validity: Duration,
+    ttl: Duration,
+}
+
+impl RedlockGuard {
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+
+    /// How long the lock is valid since it was acquired.
+    pub fn validity(&self) -> Duration {
+        self.validity
+    }
+}
+
+impl Redlock {
+    pub fn new(clients: Vec<Client>) -> Redlock {
+        Redlock {
+            clients,
+            retry_count: 3,
+            retry_delay: Duration::from_millis(200),
+            drift_factor: 0.01,
+        }
+    }
+
+    /// Number of attempts after the first one failed (3 by default).
+    pub fn retry_count(mut self, count: u32) -> Redlock {
+        self.retry_count = count;
+        self
+    }
+
+    /// Base delay between attempts (200ms by default). A random jitter of up
+    /// to the same length is added to avoid clients retrying in lockstep.
+    pub fn retry_delay(mut self, delay: Duration) -> Redlock {
+        self.retry_delay = delay;
+        self
+    }
+
+    /// Clock drift factor of the ttl (0.01 by default). `lock` fails with
+    /// `Error::Invalid` unless it is finite and not negative.
+    pub fn drift_factor(mut self, factor: f64) -> Redlock {
+        self.drift_factor = factor;
+        self
+    }
+
+    pub fn quorum(&self) -> usize {
+        self.clients.len() / 2 + 1
+    }
+
+    /// Tries to acquire the lock on a majority of the instances. Returns None
+    /// if it could not be acquired within the retries.
+    pub async fn lock(&mut self, key: &str, ttl: Duration) -> Result<Option<RedlockGuard>, Error> {
+        check_ttl(ttl)?;
+        if self.drift_factor < 0.0 || !self.drift_factor.is_finite() {
+            return Err(Error::Invalid(format!(
+                "invalid drift factor {}",
+                self.drift_factor
+            )));
+        }
+
+        for attempt in 0..=self.retry_count {
+            if attempt > 0 {
+                let jitter = rand::thread_rng().gen_range(0..=self.retry_delay.as_millis() as u64);
+                time::sleep(self.retry_delay + Duration::from_millis(jitter)).await;
+            }
+            self.reconnect_poisoned(ttl).await;
+
+            let token = new_token();
+            let start = Instant::now();
+            let results = join_all(
+                self.clients
+                    .iter_mut()
+                    .map(|client| try_lock_timeout(client, key, &token, ttl)),
+            )
+            .await;
+            let acquired = results.iter().filter(|r| matches!(r, Ok(true))).count();
+
+            // 2ms for the expiry precision of Redis. A drift too large to be a
+            // Duration leaves no validity
+            let drift = Duration::try_from_secs_f64(ttl.as_secs_f64() * self.drift_factor);
+            let validity = drift.ok().and_then(|drift| {
+                ttl.checked_sub(start.elapsed() + drift + Duration::from_millis(2))
+            });
+            match validity {
+                Some(validity) if acquired >= self.quorum() => {
+                    return Ok(Some(RedlockGuard {
+                        key: key.to_string(),
+                        token,
+                        validity,
+                        ttl,
+                    }))
+                }
+                // release the instances which accepted the lock, so other
+                // clients don't have to wait for the ttl
+                _ => self.release(key, &token, ttl).await,
+            }
+        }
+        Ok(None)
+    }
+
+    /// Releases the lock on all the instances.
+    pub async fn unlock(&mut self, guard: &RedlockGuard) {
+        self.release(&guard.key, &guard.token, guard.ttl).await
+    }
+
+    // errors are ignored since the instance may be the one that is down
+    async fn release(&mut self, key: &str, token: &str, ttl: Duration) {
+        self.reconnect_poisoned(ttl).await;
+        join_all(
+            self.clients
+                .iter_mut()
+                .map(|client| unlock_timeout(client, key, token, ttl)),
+        )
+        .await;
+    }
+
+    pub fn into_inner(self) -> Vec<Client> {
+        self.clients
+    }
+
+    // Replaces the connections poisoned by a timeout, so that a transient
+    // slowdown does not lose the instance for good. An instance which cannot
+    // be reconnected fails its next request.
+    async fn reconnect_poisoned(&mut self, ttl: Duration) {
+        let timeout = ttl / INSTANCE_TIMEOUT_DIVISOR;
+        join_all(
+            self.clients
+                .iter_mut()
+                .filter(|client| client.is_poisoned())
+                .map(|client| async move {
+                    match time::timeout(timeout, client.reconnect()).await {
+                        Ok(Ok(())) => {}
+                        Ok(Err(e)) => tracing::debug!(error = %e, "failed to reconnect"),
+                        Err(_) => tracing::debug!("reconnect timed out"),
+                    }
+                }),
+        )
+        .await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{client, cmd, resp::Resp, testing::MockServer};
+
+    fn ok() -> Resp {
+        Resp::SimpleString("OK".into())
+    }
+
+    fn release_sha() -> String {
+        Script::new(RELEASE_SCRIPT).sha().to_string()
+    }
+
+    // expects the SET of `Lock::acquire` or `Redlock::lock` with a 1s ttl and
+    // any token
+    fn expect_set(server: &MockServer, reply: Resp) {
+        server
+            .expect(cmd!["SET", "lock", "", "NX", "PX", "1000"])
+            .any_arg(2)
+            .reply(reply);
+    }
+
+    // expects the release of `token`, or of any token if None
+    fn expect_release(server: &MockServer, token: Option<&str>, released: i64) {
+        let expectation = server.expect(cmd![
+            "EVALSHA",
+            release_sha(),
+            "1",
+            "lock",
+            token.unwrap_or_default()
+        ]);
+        let expectation = match token {
+            Some(_) => expectation,
+            None => expectation.any_arg(4),
+        };
+        expectation.reply(Resp::Integer(released));
+    }
+
+    // the tokens sent to a server, in SET and release commands
+    fn tokens(server: &MockServer) -> Vec<Bytes> {
+        server
+            .received()
+            .into_iter()
+            .map(|command| match command {
+                Resp::Array(args) if args.len() == 6 => args[2].clone(),
+                Resp::Array(args) => args[4].clone(),
+                others => panic!("unexpected command {:?}", others),
+            })
+            .map(|token| match token {
+                Resp::BulkString(token) => token,
+                others => panic!("unexpected token {:?}", others),
+            })
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn contention() {
+        let server = MockServer::start().await;
+        expect_set(&server, ok());
+        expect_set(&server, Resp::Null);
+        expect_set(&server, ok());
+
+        let mut client = client::connect(server.addr()).await.unwrap();
+        let ttl = Duration::from_secs(1);
+        let lock = Lock::acquire(&mut client, "lock", ttl).await.unwrap();
+        let lock = lock.unwrap();
+        assert_eq!("lock", lock.key());
+        assert_eq!(20, lock.token().len());
+        assert!(lock.token().bytes().all(|b| b.is_ascii_alphanumeric()));
+        assert!(Lock::acquire(&mut client, "lock", ttl)
+            .await
+            .unwrap()
+            .is_none());
+
+        // each acquisition gets its own token
+        let other = Lock::acquire(&mut client, "lock", ttl).await.unwrap();
+        assert_ne!(lock.token(), other.unwrap().token());
+
+        expect_release(&server, Some(lock.token()), 1);
+        assert!(lock.release(&mut client).await.unwrap());
+        server.assert_done();
+    }
+
+    #[tokio::test]
+    async fn mismatched_token() {
+        let extend_sha = Script::new(EXTEND_SCRIPT).sha().to_string();
+        let server = MockServer::start().await;
+        expect_release(&server, Some("expired"), 0);
+        server
+            .expect(cmd!["EVALSHA", extend_sha, "1", "lock", "expired", "2000"])
+            .reply(Resp::Integer(0));
+
+        // a lock whose key expired and was taken by another holder
+        let lock = Lock {
+            key: "lock".to_string(),
+            token: "expired".to_string(),
+        };
+        let mut client = client::connect(server.addr()).await.unwrap();
+        assert!(!lock.release(&mut client).await.unwrap());
+        assert!(!lock
+            .extend(&mut client, Duration::from_secs(2))
+            .await
+            .unwrap());
+        server.assert_done();
+    }
+
+    #[tokio::test]
+    async fn acquire_with_timeout() {
+        let server = MockServer::start().await;
+        expect_set(&server, Resp::Null);
+        expect_set(&server, Resp::Null);
+        expect_set(&server, ok());
+        // gives up without retrying when the delay exceeds the timeout
+        expect_set(&server, Resp::Null);
+
+        let mut client = client::connect(server.addr()).await.unwrap();
+        let ttl = Duration::from_secs(1);
+        let retry_delay = Duration::from_millis(10);
+        let lock = Lock::acquire_with_timeout(&mut client, "lock", ttl, ttl, retry_delay);
+        assert!(lock.await.unwrap().is_some());
+        let lock =
+            Lock::acquire_with_timeout(&mut client, "lock", ttl, retry_delay / 2, retry_delay);
+        assert!(lock.await.unwrap().is_none());
+        server.assert_done();
+    }
+
+    #[tokio::test]
+    async fn zero_ttl() {
+        let server = MockServer::start().await;
+        let mut client = client::connect(server.addr()).await.unwrap();
+        let lock = Lock::acquire(&mut client, "lock", Duration::from_micros(500)).await;
+        assert!(matches!(lock, Err(Error::Invalid(_))));
+
+        // nothing is sent, as PEXPIRE 0 would delete the lock
+        let lock = Lock {
+            key: "lock".to_string(),
+            token: "t".to_string(),
+        };
+        for ttl in [Duration::ZERO, Duration::from_micros(999)] {
+            let extended = lock.extend(&mut client, ttl).await;
+            assert!(matches!(extended, Err(Error::Invalid(_))));
+        }
+
+        let mut redlock = Redlock::new(vec![client]);
+        let guard = redlock.lock("lock", Duration::ZERO).await;
+        assert!(matches!(guard, Err(Error::Invalid(_))));
+        server.assert_done();
+    }
+
+    async fn redlock(servers: &[MockServer]) -> Redlock {
+        let mut clients = vec![];
+        for server in servers {
+            clients.push(client::connect(server.addr()).await.unwrap());
+        }
+        Redlock::new(clients).retry_count(0)
+    }
+
+    async fn servers(n: usize) -> Vec<MockServer> {
+        let mut servers = vec![];
+        for _ in 0..n {
+            servers.push(MockServer::start().await);
+        }
+        servers
+    }
+
+    #[tokio::test]
+    async fn redlock_quorum_and_validity() {
+        let servers = servers(3).await;
+        expect_set(&servers[0], ok());
+        expect_set(&servers[1], ok());
+        expect_set(&servers[2], Resp::Null);
+        for server in &servers {
+            expect_release(server, None, 1);
+        }
+
+        let mut redlock = redlock(&servers).await.drift_factor(0.1);
+        assert_eq!(2, redlock.quorum());
+        let start = Instant::now();
+        let guard = redlock.lock("lock", Duration::from_secs(1)).await.unwrap();
+        let guard = guard.unwrap();
+
+        // the ttl minus the drift (100ms + 2ms) and the time spent acquiring
+        let elapsed = start.elapsed();
+        assert!(guard.validity() <= Duration::from_millis(898));
+        assert!(guard.validity() >= Duration::from_millis(898) - elapsed);
+        redlock.unlock(&guard).await;
+
+        // the same token is set and released on every instance
+        let token = tokens(&servers[0])[0].clone();
+        for server in &servers {
+            assert_eq!(vec![token.clone(), token.clone()], tokens(server));
+            server.assert_done();
+        }
+    }
+
+    #[tokio::test]
+    async fn redlock_releases_after_failed_quorum() {
+        let servers = servers(3).await;
+        expect_set(&servers[0], ok());
+        expect_set(&servers[1], Resp::Null);
+        servers[2]
+            .expect(cmd!["SET", "lock", "", "NX", "PX", "1000"])
+            .any_arg(2)
+            .reply_error("ERR connection refused by test");
+        for server in &servers {
+            expect_release(server, None, 0);
+        }
+
+        let mut redlock = redlock(&servers).await;
+        let guard = redlock.lock("lock", Duration::from_secs(1)).await.unwrap();
+        assert!(guard.is_none());
+        for server in &servers {
+            let tokens = tokens(server);
+            assert_eq!(tokens[0], tokens[1]);
+            server.assert_done();
+        }
+    }
+
+    #[tokio::test]
+    async fn redlock_drift_exceeding_ttl() {
+        let servers = servers(1).await;
+        expect_set(&servers[0], ok());
+        expect_release(&servers[0], None, 1);
+
+        // the drift allowance alone consumes the whole ttl
+        let mut redlock = redlock(&servers).await.drift_factor(1.0);
+        let guard = redlock.lock("lock", Duration::from_secs(1)).await.unwrap();
+        assert!(guard.is_none());
+        servers[0].assert_done();
+    }
+
+    #[tokio::test]
+    async fn redlock_invalid_drift_factor() {
+        let servers = servers(1).await;
+        let mut redlock = redlock(&servers).await;
+        for factor in [-0.1, f64::NAN, f64::INFINITY] {
+            redlock = redlock.drift_factor(factor);
+            let guard = redlock.lock("lock", Duration::from_secs(1)).await;
+            assert!(matches!(guard, Err(Error::Invalid(_))));
+        }
+        servers[0].assert_done();
+    }
+
+    #[tokio::test]
+    async fn redlock_instance_timeout() {
+        let servers = servers(3).await;
+        expect_set(&servers[0], ok());
+        expect_set(&servers[1], ok());
+        servers[2]
+            .expect(cmd!["SET", "lock", "", "NX", "PX", "1000"])
+            .any_arg(2)
+            .delay(Duration::from_millis(300))
+            .reply(ok());
+        for server in &servers {
+            expect_release(server, None, 1);
+        }
+
+        let mut redlock = redlock(&servers).await;
+        let start = Instant::now();
+        let guard = redlock.lock("lock", Duration::from_secs(1)).await.unwrap();
+        let guard = guard.unwrap();
+        // the slow instance was given up after a tenth of the ttl
+        assert!(start.elapsed() < Duration::from_millis(300));
+        assert!(guard.validity() > Duration::from_millis(800));
+
+        // its poisoned connection is replaced before the release
+        redlock.unlock(&guard).await;
+        let clients = redlock.into_inner();
+        assert!(!clients[2].is_poisoned());
+        for server in &servers {
+            server.assert_done();
+        }
+    }
+}
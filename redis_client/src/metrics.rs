@@ -0,0 +1,241 @@
+//! Hooks to observe the commands sent by a `Client`.
+//!
+//! Every request/reply command runs in a `redis_command` tracing span at the
+//! DEBUG level, with the `db.system`, `db.operation` and `db.statement` fields.
+//! Arguments in `db.statement` can be hidden with `ClientBuilder::redact_args`;
+//! those of commands which may hold credentials (AUTH, HELLO, ACL SETUSER,
+//! CONFIG SET and MIGRATE) are always hidden.
+//!
+//! A `Metrics` implementation set with `ClientBuilder::metrics` additionally
+//! receives a `CommandEvent` once each command completes, for example to
+//! export latency histograms:
+//!
+//! ```no_run
+//! use redis_client::client::Client;
+//! use redis_client::metrics::{CommandEvent, Metrics};
+//! use std::sync::Arc;
+//!
+//! #[derive(Debug)]
+//! struct Log;
+//!
+//! impl Metrics for Log {
+//!     fn record(&self, event: &CommandEvent<'_>) {
+//!         println!("{} {} {:?}", event.command, event.outcome.as_str(), event.latency);
+//!     }
+//! }
+//!
+//! # async fn run() -> Result<(), redis_client::error::Error> {
+//! let mut client = Client::builder()
+//!     .metrics(Arc::new(Log))
+//!     .connect("127.0.0.1:6379")
+//!     .await?;
+//! client.ping().await?;
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::{error::Error, resp::Resp};
+use std::fmt;
+use std::time::Duration;
+
+/// Receives an event for each command. It is called on the task running the
+/// command, so it should only update counters and histograms.
+pub trait Metrics: fmt::Debug + Send + Sync {
+    fn record(&self, event: &CommandEvent<'_>);
+}
+
+/// A completed command.
+#[derive(Debug, Clone)]
+pub struct CommandEvent<'a> {
+    /// The uppercase command name, with the subcommand of container commands
+    /// such as `CONFIG GET`
+    pub command: &'a str,
+    /// Time from sending the command to receiving its reply
+    pub latency: Duration,
+    pub bytes_out: u64,
+    pub bytes_in: u64,
+    pub outcome: Outcome,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Outcome {
+    Ok,
+    /// The server replied with an error
+    ServerError,
+    /// The command failed without a reply: IO error, timeout, invalid reply
+    Error,
+}
+
+impl Outcome {
+    pub(crate) fn of(result: &Result<Resp, Error>) -> Outcome {
+        match result {
+            Ok(_) => Outcome::Ok,
+            Err(e) if e.is_server_error() => Outcome::ServerError,
+            Err(_) => Outcome::Error,
+        }
+    }
+
+    /// A label for metrics: `ok`, `server_error` or `error`.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Outcome::Ok => "ok",
+            Outcome::ServerError => "server_error",
+            Outcome::Error => "error",
+        }
+    }
+}
+
+// Commands whose first argument is a subcommand
+const CONTAINER_COMMANDS: &[&str] = &[
+    "ACL", "CLIENT", "CLUSTER", "COMMAND", "CONFIG", "FUNCTION", "LATENCY", "MEMORY", "MODULE",
+    "OBJECT", "PUBSUB", "SCRIPT", "SLOWLOG", "XGROUP", "XINFO",
+];
+
+// Commands whose arguments may hold credentials, such as the passwords of
+// CONFIG SET requirepass/masterauth or MIGRATE ... AUTH, matched by full name
+const SECRET_COMMANDS: &[&str] = &["AUTH", "HELLO", "ACL SETUSER", "CONFIG SET", "MIGRATE"];
+
+/// The name of a command, such as `GET` or `CONFIG GET`.
+pub(crate) fn command_name(command: &Resp) -> String {
+    let mut args = match command {
+        Resp::Array(args) => args.iter().map(arg_str),
+        _ => return String::new(),
+    };
+    let mut name = args.next().unwrap_or_default().to_ascii_uppercase();
+    if CONTAINER_COMMANDS.contains(&name.as_str()) {
+        if let Some(sub) = args.next() {
+            name.push(' ');
+            name.push_str(&sub.to_ascii_uppercase());
+        }
+    }
+    name
+}
+
+/// The command line recorded in spans, arguments being replaced by `?` if redacted.
+pub(crate) fn statement(command: &Resp, name: &str, redact: bool) -> String {
+    let args = match command {
+        Resp::Array(args) => args,
+        _ => return String::new(),
+    };
+    let redact = redact || SECRET_COMMANDS.contains(&name);
+    let skip = name.split(' ').count();
+
+    let mut statement = name.to_string();
+    for arg in args.iter().skip(skip) {
+        statement.push(' ');
+        if redact {
+            statement.push('?');
+        } else {
+            statement.push_str(&arg_str(arg));
+        }
+    }
+    statement
+}
+
+fn arg_str(arg: &Resp) -> String {
+    match arg {
+        Resp::BulkString(b) => String::from_utf8_lossy(b).into_owned(),
+        Resp::SimpleString(s) => s.clone(),
+        Resp::Integer(n) => n.to_string(),
+        others => format!("{:?}", others),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{cmd, testing::MockServer};
+    use bytes::Bytes;
+    use std::sync::{Arc, Mutex};
+
+    #[derive(Debug, Default)]
+    struct Recorder(Mutex<Vec<(String, Outcome, u64, u64)>>);
+
+    impl Metrics for Recorder {
+        fn record(&self, event: &CommandEvent<'_>) {
+            self.0.lock().unwrap().push((
+                event.command.to_string(),
+                event.outcome,
+                event.bytes_out,
+                event.bytes_in,
+            ));
+        }
+    }
+
+    fn command(args: &[&str]) -> Resp {
+        Resp::Array(
+            args.iter()
+                .map(|arg| Resp::BulkString(Bytes::copy_from_slice(arg.as_bytes())))
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn names_and_statements() {
+        let set = command(&["set", "k", "v"]);
+        assert_eq!("SET", command_name(&set));
+        assert_eq!("SET k v", statement(&set, "SET", false));
+        assert_eq!("SET ? ?", statement(&set, "SET", true));
+
+        let config = command(&["config", "get", "maxmemory"]);
+        assert_eq!("CONFIG GET", command_name(&config));
+        assert_eq!(
+            "CONFIG GET maxmemory",
+            statement(&config, "CONFIG GET", false)
+        );
+
+        let auth = command(&["AUTH", "user", "secret"]);
+        assert_eq!("AUTH ? ?", statement(&auth, "AUTH", false));
+
+        let setuser = command(&["acl", "setuser", "app", "on", ">secret"]);
+        assert_eq!("ACL SETUSER", command_name(&setuser));
+        assert_eq!(
+            "ACL SETUSER ? ? ?",
+            statement(&setuser, "ACL SETUSER", false)
+        );
+        let getuser = command(&["ACL", "GETUSER", "app"]);
+        assert_eq!("ACL GETUSER app", statement(&getuser, "ACL GETUSER", false));
+
+        let config = command(&["CONFIG", "SET", "requirepass", "secret"]);
+        assert_eq!("CONFIG SET ? ?", statement(&config, "CONFIG SET", false));
+
+        let migrate = command(&[
+            "MIGRATE", "host", "6379", "k", "0", "1000", "AUTH2", "user", "secret",
+        ]);
+        assert_eq!("MIGRATE", command_name(&migrate));
+        assert_eq!(
+            "MIGRATE ? ? ? ? ? ? ? ?",
+            statement(&migrate, "MIGRATE", false)
+        );
+    }
+
+    #[tokio::test]
+    async fn record_commands() {
+        let server = MockServer::start().await;
+        server
+            .expect(cmd!["SET", "k", "v"])
+            .reply(Resp::SimpleString("OK".into()));
+        server
+            .expect(cmd!["INCR", "k"])
+            .reply_error("ERR not an integer");
+
+        let recorder = Arc::new(Recorder::default());
+        let mut client = crate::client::Client::builder()
+            .metrics(recorder.clone())
+            .connect(server.addr())
+            .await
+            .unwrap();
+        client.set("k", Bytes::from("v")).await.unwrap();
+        assert!(client.incr("k").await.is_err());
+        server.assert_done();
+
+        let events = recorder.0.lock().unwrap();
+        assert_eq!(
+            vec![
+                ("SET".to_string(), Outcome::Ok, 27, 5),
+                ("INCR".to_string(), Outcome::ServerError, 21, 21),
+            ],
+            *events
+        );
+    }
+}
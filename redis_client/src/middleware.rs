@@ -0,0 +1,216 @@
+//! Middlewares see every request/reply command of a `Client` and its result,
+//! to add behavior such as key prefixing, logging, fault injection or retries
+//! without wrapping each method.
+//!
+//! Middlewares are added with `ClientBuilder::middleware` and form a stack: the
+//! first one added receives the command first and the result last. Each one
+//! calls `Next::run` to pass the command down the stack, zero or more times, or
+//! returns a result itself. Pushed messages (SUBSCRIBE, MONITOR) bypass them.
+//!
+//! ```no_run
+//! use futures_util::future::BoxFuture;
+//! use redis_client::client::Client;
+//! use redis_client::error::Error;
+//! use redis_client::middleware::{Middleware, Next};
+//! use redis_client::resp::Resp;
+//! use std::sync::Arc;
+//!
+//! #[derive(Debug)]
+//! struct Log;
+//!
+//! impl Middleware for Log {
+//!     fn handle<'a>(&'a self, command: Resp, mut next: Next<'a>) -> BoxFuture<'a, Result<Resp, Error>> {
+//!         Box::pin(async move {
+//!             println!("> {:?}", command);
+//!             let result = next.run(command).await;
+//!             println!("< {:?}", result);
+//!             result
+//!         })
+//!     }
+//! }
+//!
+//! # async fn run() -> Result<(), Error> {
+//! let mut client = Client::builder()
+//!     .middleware(Arc::new(Log))
+//!     .connect("127.0.0.1:6379")
+//!     .await?;
+//! client.ping().await?;
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::{client::Client, error::Error, resp::Resp};
+use futures_util::future::BoxFuture;
+use std::fmt;
+use std::sync::Arc;
+use std::time::Duration;
+
+pub trait Middleware: fmt::Debug + Send + Sync {
+    /// Handles a command, an array of bulk strings such as `["GET", "key"]`.
+    /// Error replies are passed as `Err`.
+    fn handle<'a>(&'a self, command: Resp, next: Next<'a>) -> BoxFuture<'a, Result<Resp, Error>>;
+}
+
+/// The rest of the stack below a middleware, ending with the connection.
+pub struct Next<'a> {
+    stack: &'a [Arc<dyn Middleware>],
+    client: &'a mut Client,
+    timeout: Option<Duration>,
+    cached: bool,
+}
+
+impl<'a> Next<'a> {
+    pub(crate) fn new(
+        stack: &'a [Arc<dyn Middleware>],
+        client: &'a mut Client,
+        timeout: Option<Duration>,
+        cached: bool,
+    ) -> Next<'a> {
+        Next {
+            stack,
+            client,
+            timeout,
+            cached,
+        }
+    }
+
+    /// Passes the command to the next middleware, or sends it to the server.
+    pub fn run(&mut self, command: Resp) -> BoxFuture<'_, Result<Resp, Error>> {
+        match self.stack.split_first() {
+            Some((middleware, rest)) => {
+                let next = Next::new(rest, self.client, self.timeout, self.cached);
+                middleware.handle(command, next)
+            }
+            None => Box::pin(self.client.execute(command, self.timeout, self.cached)),
+        }
+    }
+}
+
+impl fmt::Debug for Next<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Next")
+            .field("stack", &self.stack)
+            .field("timeout", &self.timeout)
+            .finish()
+    }
+}
+
+/// Retries commands failing with a transient error reply (LOADING, BUSY,
+/// TRYAGAIN, ...), waiting `backoff` then twice as long between attempts.
+///
+/// Connection errors are not retried: the command may have been executed and
+/// the connection cannot be used anymore.
+#[derive(Debug, Clone)]
+pub struct Retry {
+    attempts: u32,
+    backoff: Duration,
+}
+
+impl Retry {
+    /// Tries each command up to `attempts` times.
+    pub fn new(attempts: u32, backoff: Duration) -> Retry {
+        Retry { attempts, backoff }
+    }
+}
+
+impl Middleware for Retry {
+    fn handle<'a>(
+        &'a self,
+        command: Resp,
+        mut next: Next<'a>,
+    ) -> BoxFuture<'a, Result<Resp, Error>> {
+        Box::pin(async move {
+            let mut backoff = self.backoff;
+            for _ in 1..self.attempts {
+                match next.run(command.clone()).await {
+                    Err(e) if can_retry(&e) => {
+                        tracing::debug!(error = %e, ?backoff, "retrying command");
+                        tokio::time::sleep(backoff).await;
+                        backoff *= 2;
+                    }
+                    result => return result,
+                }
+            }
+            next.run(command).await
+        })
+    }
+}
+
+// Redirections are left to the caller, as they need another connection
+fn can_retry(e: &Error) -> bool {
+    let redirected = matches!(e, Error::Moved { .. } | Error::Ask { .. });
+    e.is_server_error() && e.is_retryable() && !redirected
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{cmd, testing::MockServer};
+    use bytes::Bytes;
+
+    // Prefixes the first argument, the key of simple commands
+    #[derive(Debug)]
+    struct Prefix(&'static str);
+
+    impl Middleware for Prefix {
+        fn handle<'a>(
+            &'a self,
+            command: Resp,
+            mut next: Next<'a>,
+        ) -> BoxFuture<'a, Result<Resp, Error>> {
+            let command = match command {
+                Resp::Array(mut args) => {
+                    if let Some(Resp::BulkString(key)) = args.get_mut(1) {
+                        *key = Bytes::from([self.0.as_bytes(), key].concat());
+                    }
+                    Resp::Array(args)
+                }
+                others => others,
+            };
+            Box::pin(async move { next.run(command).await })
+        }
+    }
+
+    // Fails every command without sending it
+    #[derive(Debug)]
+    struct Fail;
+
+    impl Middleware for Fail {
+        fn handle<'a>(&'a self, _: Resp, _: Next<'a>) -> BoxFuture<'a, Result<Resp, Error>> {
+            Box::pin(async { Err(Error::from_server_message("READONLY injected")) })
+        }
+    }
+
+    #[tokio::test]
+    async fn stack_order() {
+        let server = MockServer::start().await;
+        server
+            .expect(cmd!["GET", "app:k"])
+            .reply_error("LOADING Redis is loading the dataset in memory");
+        server
+            .expect(cmd!["GET", "app:k"])
+            .reply(Resp::BulkString(Bytes::from("v")));
+
+        let mut client = Client::builder()
+            .middleware(Arc::new(Retry::new(3, Duration::from_millis(1))))
+            .middleware(Arc::new(Prefix("app:")))
+            .connect(server.addr())
+            .await
+            .unwrap();
+        assert_eq!(Some(Bytes::from("v")), client.get("k").await.unwrap());
+        server.assert_done();
+    }
+
+    #[tokio::test]
+    async fn fault_injection() {
+        let server = MockServer::start().await;
+        let mut client = Client::builder()
+            .middleware(Arc::new(Retry::new(2, Duration::from_millis(1))))
+            .middleware(Arc::new(Fail))
+            .connect(server.addr())
+            .await
+            .unwrap();
+        assert!(matches!(client.ping().await, Err(Error::ReadOnly(_))));
+        server.assert_done();
+    }
+}
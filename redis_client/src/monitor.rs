@@ -0,0 +1,151 @@
+use crate::error::Error;
+use bytes::Bytes;
+use std::fmt;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// A command processed by the server, as streamed by `Client::monitor`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MonitorEntry {
+    pub timestamp: SystemTime,
+    pub db: u64,
+    /// The address of the client (`127.0.0.1:52410`, `unix:/tmp/redis.sock`),
+    /// or `lua` for commands run by a script
+    pub client: String,
+    pub args: Vec<Bytes>,
+}
+
+impl MonitorEntry {
+    // 1339518083.107412 [0 127.0.0.1:60866] "keys" "*"
+    pub(crate) fn parse(line: &str) -> Result<MonitorEntry, Error> {
+        let invalid = || Error::Invalid(format!("invalid MONITOR line: {:?}", line));
+
+        let (timestamp, rest) = line.split_once(" [").ok_or_else(invalid)?;
+        let (source, args) = rest.split_once(']').ok_or_else(invalid)?;
+        let (db, client) = source.split_once(' ').ok_or_else(invalid)?;
+
+        let (secs, micros) = timestamp.split_once('.').ok_or_else(invalid)?;
+        let secs = secs.parse().map_err(|_| invalid())?;
+        let micros: u32 = micros.parse().map_err(|_| invalid())?;
+
+        Ok(MonitorEntry {
+            timestamp: UNIX_EPOCH + Duration::new(secs, micros * 1000),
+            db: db.parse().map_err(|_| invalid())?,
+            client: client.to_string(),
+            args: parse_args(args.as_bytes()).ok_or_else(invalid)?,
+        })
+    }
+}
+
+// Arguments are quoted and escaped like `"set" "k" "a\x00b"`
+fn parse_args(mut s: &[u8]) -> Option<Vec<Bytes>> {
+    let mut args = vec![];
+    loop {
+        s = s.trim_ascii_start();
+        if s.is_empty() {
+            return Some(args);
+        }
+        s = s.strip_prefix(b"\"")?;
+
+        let mut arg = vec![];
+        loop {
+            match s {
+                [b'"', rest @ ..] => {
+                    s = rest;
+                    break;
+                }
+                [b'\\', b'x', h, l, rest @ ..]
+                    if h.is_ascii_hexdigit() && l.is_ascii_hexdigit() =>
+                {
+                    let hex = [*h, *l];
+                    arg.push(u8::from_str_radix(std::str::from_utf8(&hex).ok()?, 16).ok()?);
+                    s = rest;
+                }
+                [b'\\', c, rest @ ..] => {
+                    arg.push(match c {
+                        b'n' => b'\n',
+                        b'r' => b'\r',
+                        b't' => b'\t',
+                        b'a' => 0x07,
+                        b'b' => 0x08,
+                        c => *c,
+                    });
+                    s = rest;
+                }
+                [c, rest @ ..] => {
+                    arg.push(*c);
+                    s = rest;
+                }
+                [] => return None,
+            }
+        }
+        args.push(Bytes::from(arg));
+    }
+}
+
+impl fmt::Display for MonitorEntry {
+    /// Formats the entry as the server does.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let since_epoch = self
+            .timestamp
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default();
+        write!(
+            f,
+            "{}.{:06} [{} {}]",
+            since_epoch.as_secs(),
+            since_epoch.subsec_micros(),
+            self.db,
+            self.client
+        )?;
+        for arg in &self.args {
+            write!(f, " \"")?;
+            for &c in arg.iter() {
+                match c {
+                    b'\\' => write!(f, "\\\\")?,
+                    b'"' => write!(f, "\\\"")?,
+                    b'\n' => write!(f, "\\n")?,
+                    b'\r' => write!(f, "\\r")?,
+                    b'\t' => write!(f, "\\t")?,
+                    0x07 => write!(f, "\\a")?,
+                    0x08 => write!(f, "\\b")?,
+                    c if c.is_ascii_graphic() || c == b' ' => write!(f, "{}", c as char)?,
+                    c => write!(f, "\\x{:02x}", c)?,
+                }
+            }
+            write!(f, "\"")?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_entry() {
+        let line = r#"1339518083.107412 [2 127.0.0.1:60866] "set" "k" "a \"b\"\x00\n""#;
+        let entry = MonitorEntry::parse(line).unwrap();
+        assert_eq!(
+            UNIX_EPOCH + Duration::new(1339518083, 107_412_000),
+            entry.timestamp
+        );
+        assert_eq!(2, entry.db);
+        assert_eq!("127.0.0.1:60866", entry.client);
+        assert_eq!(
+            vec![
+                Bytes::from("set"),
+                Bytes::from("k"),
+                Bytes::from("a \"b\"\0\n")
+            ],
+            entry.args
+        );
+        assert_eq!(line, entry.to_string());
+
+        let entry = MonitorEntry::parse(r#"1339518083.000001 [0 lua] "get" "k""#).unwrap();
+        assert_eq!("lua", entry.client);
+
+        assert!(MonitorEntry::parse("OK").is_err());
+        assert!(MonitorEntry::parse(r#"1.1 [0 lua] "unterminated"#).is_err());
+    }
+}
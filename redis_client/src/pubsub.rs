@@ -2,10 +2,13 @@ use crate::{error::Error, resp::Resp};
 use bytes::Bytes;
 
 #[derive(Debug)]
+#[allow(clippy::enum_variant_names)]
 pub enum Message {
     Subscribe(Bytes, u64), // ["subscribe", message ,num of subscriber]
     Unsubscribe(Bytes),
-    Message(String, Bytes),
+    Message(Bytes, Bytes),         // ["message", channel, message]
+    PSubscribe(Bytes, u64),        // ["psubscribe", pattern, num of subscriber]
+    PMessage(Bytes, Bytes, Bytes), // ["pmessage", pattern, channel, message]
 }
 
 pub(crate) fn parse(resp: &Resp) -> Result<Message, Error> {
@@ -14,28 +17,82 @@ pub(crate) fn parse(resp: &Resp) -> Result<Message, Error> {
             [Resp::BulkString(typ), Resp::BulkString(channel), Resp::BulkString(message)]
                 if typ == "message" =>
             {
-                let ch = String::from_utf8(channel.to_vec()).unwrap(); //  TODO
-                Ok(Message::Message(ch, message.clone()))
+                Ok(Message::Message(channel.clone(), message.clone()))
             }
             [Resp::BulkString(typ), Resp::BulkString(message), Resp::Integer(r)]
                 if typ == "subscribe" =>
             {
                 Ok(Message::Subscribe(message.clone(), *r as u64))
             }
-            others => Err(Error::Other(
-                format!(
-                    "unxpected value. arrays(size=3) is expected for pub/sub message: {:?}",
-                    others
-                )
-                .into(),
-            )),
-        },
-        others => Err(Error::Other(
-            format!(
+            [Resp::BulkString(typ), Resp::BulkString(pattern), Resp::Integer(r)]
+                if typ == "psubscribe" =>
+            {
+                Ok(Message::PSubscribe(pattern.clone(), *r as u64))
+            }
+            others => Err(Error::Other(format!(
                 "unxpected value. arrays(size=3) is expected for pub/sub message: {:?}",
                 others
-            )
-            .into(),
-        )),
+            ))),
+        },
+        Resp::Array(ary) if ary.len() == 4 => match ary.as_slice() {
+            [Resp::BulkString(typ), Resp::BulkString(pattern), Resp::BulkString(channel), Resp::BulkString(message)]
+                if typ == "pmessage" =>
+            {
+                // channels are kept as bytes, since keyspace notifications
+                // carry binary keys in them
+                Ok(Message::PMessage(
+                    pattern.clone(),
+                    channel.clone(),
+                    message.clone(),
+                ))
+            }
+            others => Err(Error::Other(format!(
+                "unxpected value. arrays(size=4) is expected for pub/sub pmessage: {:?}",
+                others
+            ))),
+        },
+        others => Err(Error::Other(format!(
+            "unxpected value. arrays(size=3) is expected for pub/sub message: {:?}",
+            others
+        ))),
+    }
+}
+
+// Checks the reply confirming the subscription to a channel, or to a pattern
+// for PSUBSCRIBE, and returns the number of subscriptions of the connection
+pub(crate) fn confirm(resp: &Resp, name: &str, pattern: bool) -> Result<u64, Error> {
+    match parse(resp)? {
+        Message::Subscribe(ch, n) if !pattern && ch == name => Ok(n),
+        Message::PSubscribe(p, n) if pattern && p == name => Ok(n),
+        others => Err(Error::Other(format!(
+            "message whose {} is {:?} is required, but {:?}",
+            if pattern { "pattern" } else { "channel" },
+            name,
+            others
+        ))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::bulk;
+
+    #[test]
+    fn binary_channel() {
+        let pmessage = Resp::Array(vec![
+            bulk("pmessage"),
+            bulk("__keyspace@0__:*"),
+            bulk(b"__keyspace@0__:k\xff"),
+            bulk("set"),
+        ]);
+        match parse(&pmessage).unwrap() {
+            Message::PMessage(pattern, channel, payload) => {
+                assert_eq!("__keyspace@0__:*", pattern);
+                assert_eq!(&b"__keyspace@0__:k\xff"[..], channel);
+                assert_eq!("set", payload);
+            }
+            others => panic!("unexpected message {:?}", others),
+        }
     }
 }
@@ -1,10 +1,10 @@
-use crate::error::{self, Error};
-use bytes::{Buf, Bytes};
-use std::io::{self, Cursor};
+use crate::error::Error;
+use bytes::{Buf, Bytes, BytesMut};
+use std::io;
 use tokio::io::AsyncWriteExt;
 
 // REdis Serialization Protocol
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum Resp {
     SimpleString(String),
     Error(String),
@@ -26,125 +26,172 @@ const INTEGER_CHAR: u8 = b':';
 const ERROR_CHAR: u8 = b'-';
 const ARRAY_CHAR: u8 = b'*';
 
-fn read_u8(buf: &mut Cursor<&[u8]>) -> Result<u8, ParseErr> {
-    let v = peek_u8(buf)?;
-    buf.advance(1);
-
-    Ok(v)
+// Returns the index of the first \r\n
+fn find_crlf(buf: &[u8]) -> Option<usize> {
+    buf.windows(2).position(|w| w == b"\r\n")
 }
 
-fn peek_u8(buf: &Cursor<&[u8]>) -> Result<u8, ParseErr> {
-    if !buf.has_remaining() {
-        return Err(ParseErr::Imcomplete);
-    }
+fn parse_int(line: &[u8]) -> Result<i64, ParseErr> {
+    use atoi::FromRadix10SignedChecked;
 
-    return Ok(buf.chunk()[0]);
+    match i64::from_radix_10_signed_checked(line) {
+        (Some(n), used) if used > 0 && used == line.len() => Ok(n),
+        _ => Err(ParseErr::Invalid(format!(
+            "invalid protocol. {:?} is not a number",
+            String::from_utf8_lossy(line)
+        ))),
+    }
 }
 
-fn read_num(buf: &mut Cursor<&[u8]>) -> Result<i64, ParseErr> {
-    use atoi::atoi;
-
-    let mut negtive = false;
-    if peek_u8(buf)? == b'-' {
-        skip(buf, 1)?; // skip '-'
+fn parse_string(line: Bytes) -> Result<String, ParseErr> {
+    String::from_utf8(line.to_vec()).map_err(|e| ParseErr::Invalid(e.to_string()))
+}
 
-        negtive = true;
+// Checks the element at the start of `buf` without decoding it. Returns its
+// length (for an array, only its header) and the number of elements nested
+// in it, or None if more bytes are needed.
+fn scan_element(buf: &[u8]) -> Result<Option<(usize, usize)>, ParseErr> {
+    let line_end = match find_crlf(buf) {
+        Some(i) => i,
+        None => return Ok(None),
+    };
+    if line_end == 0 {
+        return Err(ParseErr::Invalid("invalid protocol. empty line".into()));
     }
-
-    let line = read_line(buf)?;
-    let r = atoi::<i64>(line).ok_or_else(|| ParseErr::Invalid("invalid protocol".into()))?;
-
-    if negtive {
-        Ok(-1 * r)
-    } else {
-        Ok(r)
+    let line = &buf[1..line_end];
+    let header = line_end + 2;
+
+    match buf[0] {
+        SIMPLE_STRING_CHAR | ERROR_CHAR => Ok(Some((header, 0))),
+        INTEGER_CHAR => parse_int(line).map(|_| Some((header, 0))),
+        BULK_STRING_CHAR => match parse_int(line)? {
+            -1 => Ok(Some((header, 0))),
+            len if len < -1 => Err(ParseErr::Invalid(
+                "invalid protocol. negitive value is not allowed here".into(),
+            )),
+            len => {
+                // no buffer can hold more than isize::MAX bytes
+                let total = usize::try_from(len)
+                    .ok()
+                    .and_then(|len| header.checked_add(len)?.checked_add(2))
+                    .filter(|&total| total <= isize::MAX as usize)
+                    .ok_or_else(|| {
+                        ParseErr::Invalid(format!(
+                            "invalid protocol. bulk string length {} is too large",
+                            len
+                        ))
+                    })?;
+                if buf.len() < total {
+                    Ok(None)
+                } else if &buf[total - 2..total] != b"\r\n" {
+                    Err(ParseErr::Invalid(
+                        "invalid protocol. bulk string is not terminated by \\r\\n".into(),
+                    ))
+                } else {
+                    Ok(Some((total, 0)))
+                }
+            }
+        },
+        ARRAY_CHAR => match parse_int(line)? {
+            -1 => Ok(Some((header, 0))),
+            len if len < -1 => Err(ParseErr::Invalid("invalid protocol".into())),
+            len => Ok(Some((header, len as usize))),
+        },
+        actual => Err(ParseErr::Invalid(format!(
+            "invalid protocol. unknown type {:?}",
+            actual as char
+        ))),
     }
 }
 
-fn read_string(buf: &mut Cursor<&[u8]>) -> Result<String, ParseErr> {
-    let line = read_line(buf)?.to_vec();
-
-    String::from_utf8(line).map_err(|e| ParseErr::Invalid(e.to_string()))
-}
-
-fn skip(buf: &mut Cursor<&[u8]>, n: usize) -> Result<(), ParseErr> {
-    if buf.remaining() < n {
-        return Err(ParseErr::Imcomplete);
+// Decodes a frame already checked by `scan_element`. Bulk strings share the
+// memory of `frame`.
+fn decode_frame(frame: &mut Bytes) -> Result<Resp, ParseErr> {
+    let line_end = find_crlf(frame)
+        .ok_or_else(|| ParseErr::Invalid("invalid protocol. truncated frame".into()))?;
+    let mut line = frame.split_to(line_end);
+    frame.advance(2);
+    let typ = line.get_u8();
+
+    match typ {
+        SIMPLE_STRING_CHAR => Ok(Resp::SimpleString(parse_string(line)?)),
+        ERROR_CHAR => Ok(Resp::Error(parse_string(line)?)),
+        INTEGER_CHAR => Ok(Resp::Integer(parse_int(&line)?)),
+        BULK_STRING_CHAR => match parse_int(&line)? {
+            -1 => Ok(Resp::Null),
+            len => {
+                let data = frame.split_to(len as usize);
+                frame.advance(2);
+                Ok(Resp::BulkString(data))
+            }
+        },
+        ARRAY_CHAR => match parse_int(&line)? {
+            -1 => Ok(Resp::Null),
+            len => (0..len)
+                .map(|_| decode_frame(frame))
+                .collect::<Result<_, _>>()
+                .map(Resp::Array),
+        },
+        actual => Err(ParseErr::Invalid(format!(
+            "invalid protocol. unknown type {:?}",
+            actual as char
+        ))),
     }
-
-    buf.advance(n);
-    return Ok(());
 }
 
-fn read_line<'a>(buf: &mut Cursor<&'a [u8]>) -> Result<&'a [u8], ParseErr> {
-    let start = buf.position() as usize;
-    let end = buf.get_ref().len();
-
-    for i in start..(end - 1) {
-        if buf.get_ref()[i] == b'\r' && buf.get_ref()[i + 1] == b'\n' {
-            buf.set_position(std::cmp::min(i + 2, end) as u64);
+/// Decodes replies from a receive buffer as bytes arrive.
+///
+/// The decoder remembers how much of the pending frame was already checked, so
+/// a large reply received in many reads is scanned once. It consumes exactly
+/// the bytes of each decoded frame, leaving pipelined replies in the buffer,
+/// and bulk strings are slices of the buffer rather than copies.
+#[derive(Debug, Default)]
+pub struct RespDecoder {
+    // length of the complete elements already checked in the pending frame
+    scanned: usize,
+
+    // elements still expected by each array being checked, innermost last
+    pending: Vec<usize>,
+}
 
-            // without \r\n
-            return Ok(&buf.get_ref()[start..i]);
-        }
+impl RespDecoder {
+    pub fn new() -> RespDecoder {
+        RespDecoder::default()
     }
 
-    Err(ParseErr::Imcomplete)
-}
-
-impl Resp {
-    pub fn parse(buf: &mut Cursor<&[u8]>) -> Result<Resp, ParseErr> {
-        match read_u8(buf)? {
-            BULK_STRING_CHAR => {
-                match read_num(buf)? {
-                    -1 => Ok(Resp::Null),
-                    len if len < -1 => Err(ParseErr::Invalid(
-                        "invalid protocol. negitive value is not allowed here".into(),
-                    )),
-                    len => {
-                        let len = len as usize;
-                        if buf.remaining() < len {
-                            return Err(ParseErr::Imcomplete);
-                        }
-                        let r = Bytes::copy_from_slice(&buf.chunk()[..len]);
-                        skip(buf, len + 2)?; // expected \r\n
-
-                        Ok(Resp::BulkString(r))
-                    }
-                }
-            }
-            SIMPLE_STRING_CHAR => {
-                let sstring = read_string(buf)?;
-                Ok(Resp::SimpleString(sstring))
+    /// Returns the next reply, or None if the buffer does not hold a whole
+    /// frame yet. After an error the stream cannot be decoded anymore.
+    pub fn decode(&mut self, buf: &mut BytesMut) -> Result<Option<Resp>, ParseErr> {
+        loop {
+            let (len, nested) = match scan_element(&buf[self.scanned..])? {
+                Some(element) => element,
+                None => return Ok(None),
+            };
+            self.scanned += len;
+
+            if nested > 0 {
+                self.pending.push(nested);
+                continue;
             }
-            INTEGER_CHAR => {
-                let n = read_num(buf)?;
-                Ok(Resp::Integer(n))
-            }
-            ARRAY_CHAR => match read_num(buf)? {
-                -1 => Ok(Resp::Null),
-                len if len < -1 => Err(ParseErr::Invalid("invalid protocol".into())),
-                len => {
-                    let len = len as usize;
-                    let mut inner = Vec::with_capacity(len);
-                    for _ in 0..len {
-                        inner.push(Resp::parse(buf)?)
-                    }
-
-                    Ok(Resp::Array(inner))
+
+            // an element is complete, and so is every array it completes
+            while let Some(remaining) = self.pending.last_mut() {
+                *remaining -= 1;
+                if *remaining > 0 {
+                    break;
                 }
-            },
-            ERROR_CHAR => {
-                let sstring = read_string(buf)?;
-                Ok(Resp::Error(sstring))
+                self.pending.pop();
             }
-            actual => {
-                println!("{:?}", actual);
-                Err(ParseErr::Imcomplete)
+
+            if self.pending.is_empty() {
+                let mut frame = buf.split_to(std::mem::take(&mut self.scanned)).freeze();
+                return decode_frame(&mut frame).map(Some);
             }
         }
     }
+}
 
+impl Resp {
     pub fn array() -> Resp {
         Resp::Array(vec![])
     }
@@ -170,8 +217,180 @@ impl Resp {
         }
     }
 
+    pub(crate) fn into_integer(self) -> Result<i64, Error> {
+        match self {
+            Resp::Integer(v) => Ok(v),
+            others => Err(others.to_error()),
+        }
+    }
+
+    pub(crate) fn into_bool(self) -> Result<bool, Error> {
+        self.into_integer().map(|v| v == 1)
+    }
+
+    pub(crate) fn into_ok(self) -> Result<(), Error> {
+        match self {
+            Resp::SimpleString(resp) if resp == "OK" => Ok(()),
+            others => Err(others.to_error()),
+        }
+    }
+
+    pub(crate) fn into_string(self) -> Result<String, Error> {
+        match self {
+            Resp::SimpleString(resp) => Ok(resp),
+            Resp::BulkString(resp) => {
+                String::from_utf8(resp.to_vec()).map_err(|e| Error::Invalid(e.to_string()))
+            }
+            others => Err(others.to_error()),
+        }
+    }
+
+    pub(crate) fn into_bytes(self) -> Result<Option<Bytes>, Error> {
+        match self {
+            Resp::SimpleString(resp) => Ok(Some(resp.into())),
+            Resp::BulkString(resp) => Ok(Some(resp)),
+            Resp::Null => Ok(None),
+            others => Err(others.to_error()),
+        }
+    }
+
+    pub(crate) fn into_bulk(self) -> Result<Bytes, Error> {
+        match self {
+            Resp::BulkString(resp) => Ok(resp),
+            others => Err(others.to_error()),
+        }
+    }
+
+    pub(crate) fn into_float(self) -> Result<f64, Error> {
+        let v = self.into_string()?;
+        v.parse::<f64>()
+            .map_err(|e| Error::Invalid(format!("invalid float {:?}: {}", v, e)))
+    }
+
+    // [field1, value1, field2, value2, ...] into [(field1, value1), ...]
+    pub(crate) fn into_pairs(self) -> Result<Vec<(String, Bytes)>, Error> {
+        let ary = self.into_array()?;
+        if !ary.len().is_multiple_of(2) {
+            return Err(Error::Invalid(format!(
+                "even number of elements is expected, but {}",
+                ary.len()
+            )));
+        }
+
+        let mut pairs = Vec::with_capacity(ary.len() / 2);
+        let mut iter = ary.into_iter();
+        while let (Some(field), Some(value)) = (iter.next(), iter.next()) {
+            pairs.push((field.into_string()?, value.into_bulk()?));
+        }
+        Ok(pairs)
+    }
+
+    // [name1, value1, name2, value2, ...] with values of any type, as returned
+    // by XINFO, FUNCTION LIST and so on
+    pub(crate) fn into_fields(self) -> Result<Vec<(String, Resp)>, Error> {
+        let mut fields = vec![];
+        let mut iter = self.into_array()?.into_iter();
+        while let (Some(name), Some(value)) = (iter.next(), iter.next()) {
+            fields.push((name.into_string()?, value));
+        }
+        Ok(fields)
+    }
+
+    pub(crate) fn into_array(self) -> Result<Vec<Resp>, Error> {
+        match self {
+            Resp::Array(ary) => Ok(ary),
+            others => Err(others.to_error()),
+        }
+    }
+
     pub(crate) fn to_error(&self) -> Error {
-        Error::Other(format!("unexpected token: {:?}", self).into())
+        match self {
+            Resp::Error(msg) => Error::from_server_message(msg),
+            others => Error::Other(format!("unexpected token: {:?}", others)),
+        }
+    }
+}
+
+/// Conversion from a reply into a Rust type, used by commands whose reply type
+/// depends on the caller (e.g. scripts and functions).
+pub trait FromResp: Sized {
+    fn from_resp(resp: Resp) -> Result<Self, Error>;
+}
+
+impl FromResp for Resp {
+    fn from_resp(resp: Resp) -> Result<Self, Error> {
+        Ok(resp)
+    }
+}
+
+impl FromResp for () {
+    fn from_resp(_resp: Resp) -> Result<Self, Error> {
+        Ok(())
+    }
+}
+
+impl FromResp for i64 {
+    fn from_resp(resp: Resp) -> Result<Self, Error> {
+        resp.into_integer()
+    }
+}
+
+impl FromResp for u64 {
+    fn from_resp(resp: Resp) -> Result<Self, Error> {
+        match resp.into_integer()? {
+            v if v >= 0 => Ok(v as u64),
+            v => Err(Error::Invalid(format!("negative value {} for u64", v))),
+        }
+    }
+}
+
+impl FromResp for f64 {
+    fn from_resp(resp: Resp) -> Result<Self, Error> {
+        match resp {
+            Resp::Integer(v) => Ok(v as f64),
+            resp => resp.into_float(),
+        }
+    }
+}
+
+// Lua true is converted to 1 and false to nil
+impl FromResp for bool {
+    fn from_resp(resp: Resp) -> Result<Self, Error> {
+        match resp {
+            Resp::Integer(v) => Ok(v != 0),
+            Resp::Null => Ok(false),
+            others => Err(others.to_error()),
+        }
+    }
+}
+
+impl FromResp for String {
+    fn from_resp(resp: Resp) -> Result<Self, Error> {
+        resp.into_string()
+    }
+}
+
+impl FromResp for Bytes {
+    fn from_resp(resp: Resp) -> Result<Self, Error> {
+        match resp {
+            Resp::SimpleString(resp) => Ok(resp.into()),
+            resp => resp.into_bulk(),
+        }
+    }
+}
+
+impl<T: FromResp> FromResp for Option<T> {
+    fn from_resp(resp: Resp) -> Result<Self, Error> {
+        match resp {
+            Resp::Null => Ok(None),
+            resp => T::from_resp(resp).map(Some),
+        }
+    }
+}
+
+impl<T: FromResp> FromResp for Vec<T> {
+    fn from_resp(resp: Resp) -> Result<Self, Error> {
+        resp.into_array()?.into_iter().map(T::from_resp).collect()
     }
 }
 
@@ -187,48 +406,41 @@ impl<'a, T> RespWriter<'a, T> {
 
 impl<'a, T: AsyncWriteExt + std::marker::Unpin> RespWriter<'a, T> {
     pub async fn write(&mut self, data: &Resp) -> io::Result<()> {
-        match data {
-            Resp::Array(vals) => {
-                self.inner.write_u8(b'*').await?;
-                write_num(self.inner, vals.len()).await?;
-                for item in vals {
-                    self.write_value(item).await?;
-                }
-            }
-            _ => self.write_value(data).await?, // to avoid boxing in async fn
-        }
+        let mut buf = BytesMut::new();
+        data.encode(&mut buf);
 
-        Ok(())
+        self.inner.write_all(&buf).await
     }
+}
 
-    async fn write_value(&mut self, data: &Resp) -> io::Result<()> {
-        match data {
+impl Resp {
+    /// Appends the serialized value to `dst`. Null is written as a null bulk string.
+    pub fn encode(&self, dst: &mut BytesMut) {
+        match self {
+            Resp::SimpleString(val) => encode_line(dst, SIMPLE_STRING_CHAR, val.as_bytes()),
+            Resp::Error(val) => encode_line(dst, ERROR_CHAR, val.as_bytes()),
+            Resp::Integer(val) => encode_line(dst, INTEGER_CHAR, val.to_string().as_bytes()),
             Resp::BulkString(val) => {
-                self.inner.write_u8(BULK_STRING_CHAR).await?;
-                write_num(self.inner, val.len()).await?;
-                self.inner.write_all(val).await?;
-                self.inner.write_all(b"\r\n").await?;
+                encode_line(dst, BULK_STRING_CHAR, val.len().to_string().as_bytes());
+                dst.extend_from_slice(val);
+                dst.extend_from_slice(b"\r\n");
             }
-            Resp::SimpleString(val) => {
-                self.inner.write_u8(SIMPLE_STRING_CHAR).await?;
-                self.inner.write_all(val.as_bytes()).await?;
-                self.inner.write_all(b"\r\n").await?;
+            Resp::Array(vals) => {
+                encode_line(dst, ARRAY_CHAR, vals.len().to_string().as_bytes());
+                for item in vals {
+                    item.encode(dst);
+                }
             }
-
-            Resp::Array(_) => unreachable!(),
-            _ => todo!(),
+            Resp::Null => dst.extend_from_slice(b"$-1\r\n"),
         }
-        Ok(())
     }
 }
 
-async fn write_num<T: AsyncWriteExt + std::marker::Unpin>(
-    dst: &mut T,
-    val: usize,
-) -> io::Result<()> {
-    dst.write_all(val.to_string().as_bytes()).await?;
-    dst.write_all(b"\r\n").await?;
-    Ok(())
+fn encode_line(dst: &mut BytesMut, typ: u8, line: &[u8]) {
+    dst.reserve(line.len() + 3);
+    dst.extend_from_slice(&[typ]);
+    dst.extend_from_slice(line);
+    dst.extend_from_slice(b"\r\n");
 }
 
 #[cfg(test)]
@@ -261,12 +473,143 @@ mod tests {
     async fn write_array() {
         let mut buf = vec![];
 
-        let mut inner = vec![];
-        inner.push(Resp::SimpleString("OK1".into()));
-        inner.push(Resp::BulkString("OK2".into()));
+        let inner = vec![
+            Resp::SimpleString("OK1".into()),
+            Resp::BulkString("OK2".into()),
+        ];
         let resp = Resp::Array(inner);
         RespWriter::new(&mut buf).write(&resp).await.unwrap();
 
         assert_eq!(b"*2\r\n+OK1\r\n$3\r\nOK2\r\n".to_vec(), buf);
     }
+
+    #[test]
+    fn push_elements() {
+        let mut resp = Resp::array();
+        resp.push_simple_strings("OK".into());
+        resp.push_bulk_strings("v".into());
+        resp.push_integers(-1);
+        assert_eq!(
+            Resp::Array(vec![
+                Resp::SimpleString("OK".into()),
+                Resp::BulkString("v".into()),
+                Resp::Integer(-1),
+            ]),
+            resp
+        );
+    }
+
+    #[test]
+    fn into_pairs() {
+        let resp = Resp::Array(vec![
+            Resp::BulkString("f1".into()),
+            Resp::BulkString("v1".into()),
+            Resp::BulkString("f2".into()),
+            Resp::BulkString("v2".into()),
+        ]);
+        let pairs = resp.into_pairs().unwrap();
+        assert_eq!(
+            vec![
+                ("f1".to_string(), Bytes::from("v1")),
+                ("f2".to_string(), Bytes::from("v2"))
+            ],
+            pairs
+        );
+
+        let resp = Resp::Array(vec![Resp::BulkString("f1".into())]);
+        assert!(resp.into_pairs().is_err());
+    }
+
+    #[test]
+    fn from_resp() {
+        let resp = Resp::Array(vec![Resp::Integer(1), Resp::Integer(2)]);
+        assert_eq!(
+            Some(vec![1, 2]),
+            Option::<Vec<i64>>::from_resp(resp).unwrap()
+        );
+        assert_eq!(None, Option::<Vec<i64>>::from_resp(Resp::Null).unwrap());
+
+        let resp = Resp::BulkString("1.5".into());
+        assert_eq!(1.5, f64::from_resp(resp).unwrap());
+        assert!(u64::from_resp(Resp::Integer(-1)).is_err());
+        assert!(!bool::from_resp(Resp::Null).unwrap());
+    }
+
+    #[test]
+    fn decode_pipelined_frames() {
+        let mut buf = BytesMut::from(&b"+OK\r\n:42\r\n$3\r\nfoo\r\n*-1\r\n$-1\r\n-ERR bad\r\n"[..]);
+        let mut decoder = RespDecoder::new();
+
+        let mut frames = vec![];
+        while let Some(resp) = decoder.decode(&mut buf).unwrap() {
+            frames.push(resp);
+        }
+        assert_eq!(
+            vec![
+                Resp::SimpleString("OK".into()),
+                Resp::Integer(42),
+                Resp::BulkString(Bytes::from("foo")),
+                Resp::Null,
+                Resp::Null,
+                Resp::Error("ERR bad".into()),
+            ],
+            frames
+        );
+        assert!(buf.is_empty());
+    }
+
+    #[test]
+    fn decode_incrementally() {
+        let data = b"*3\r\n$5\r\nhello\r\n*2\r\n:-1\r\n*0\r\n+x\r\n:7\r\n";
+        let mut buf = BytesMut::new();
+        let mut decoder = RespDecoder::new();
+
+        let mut frames = vec![];
+        for b in data {
+            buf.extend_from_slice(&[*b]);
+            if let Some(resp) = decoder.decode(&mut buf).unwrap() {
+                frames.push(resp);
+            }
+        }
+        assert_eq!(
+            vec![
+                Resp::Array(vec![
+                    Resp::BulkString(Bytes::from("hello")),
+                    Resp::Array(vec![Resp::Integer(-1), Resp::Array(vec![])]),
+                    Resp::SimpleString("x".into()),
+                ]),
+                Resp::Integer(7),
+            ],
+            frames
+        );
+    }
+
+    #[test]
+    fn decode_bulk_string_without_copy() {
+        let mut buf = BytesMut::from(&b"$5\r\nhello\r\n"[..]);
+        let range = buf.as_ptr_range();
+
+        match RespDecoder::new().decode(&mut buf).unwrap() {
+            Some(Resp::BulkString(b)) => assert!(range.contains(&b.as_ptr())),
+            others => panic!("unexpected result {:?}", others),
+        }
+    }
+
+    #[test]
+    fn decode_invalid() {
+        let mut buf = BytesMut::from(&b"?foo\r\n"[..]);
+        assert!(RespDecoder::new().decode(&mut buf).is_err());
+
+        let mut buf = BytesMut::from(&b":12a\r\n"[..]);
+        assert!(RespDecoder::new().decode(&mut buf).is_err());
+    }
+
+    #[test]
+    fn decode_huge_bulk_length() {
+        let mut buf = BytesMut::from(format!("${}\r\nfoo\r\n", i64::MAX).as_bytes());
+        match RespDecoder::new().decode(&mut buf) {
+            Err(ParseErr::Invalid(msg)) => assert!(msg.contains("too large")),
+            others => panic!("unexpected result {:?}", others),
+        }
+    }
 }
@@ -0,0 +1,122 @@
+use crate::{client::Client, command::Scan, error::Error, resp::Resp};
+use futures_core::Stream;
+use futures_util::stream;
+use std::collections::VecDeque;
+
+struct State<'a, T> {
+    client: &'a mut Client,
+    command: Scan,
+    // None after the server returned cursor 0
+    cursor: Option<u64>,
+    buffer: VecDeque<T>,
+}
+
+// Drives SCAN-family commands until the cursor wraps to 0. Each reply is an
+// array of [cursor, [elements...]], and `convert` turns the elements of one
+// page into items.
+pub(crate) fn stream<'a, T: 'a>(
+    client: &'a mut Client,
+    command: Scan,
+    convert: fn(Vec<Resp>) -> Result<Vec<T>, Error>,
+) -> impl Stream<Item = Result<T, Error>> + 'a {
+    let state = State {
+        client,
+        command,
+        cursor: Some(0),
+        buffer: VecDeque::new(),
+    };
+
+    stream::unfold(state, move |mut state| async move {
+        loop {
+            if let Some(v) = state.buffer.pop_front() {
+                return Some((Ok(v), state));
+            }
+
+            let cursor = state.cursor?;
+            match next_page(&mut state, cursor, convert).await {
+                Ok(()) => {}
+                Err(e) => {
+                    state.cursor = None;
+                    return Some((Err(e), state));
+                }
+            }
+        }
+    })
+}
+
+async fn next_page<T>(
+    state: &mut State<'_, T>,
+    cursor: u64,
+    convert: fn(Vec<Resp>) -> Result<Vec<T>, Error>,
+) -> Result<(), Error> {
+    let mut command = state.command.clone();
+    command.set_cursor(cursor);
+
+    let resp = state.client.request(Resp::from(command)).await?;
+    let (next, elements) = match resp.into_array()?.as_mut_slice() {
+        [next, Resp::Array(elements)] => (
+            std::mem::replace(next, Resp::Null).into_string()?,
+            std::mem::take(elements),
+        ),
+        others => {
+            return Err(Error::Invalid(format!(
+                "invalid scan response {:?}",
+                others
+            )))
+        }
+    };
+
+    let next = next
+        .parse::<u64>()
+        .map_err(|e| Error::Invalid(format!("invalid cursor {:?}: {}", next, e)))?;
+    state.cursor = if next == 0 { None } else { Some(next) };
+    state.buffer.extend(convert(elements)?);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::client;
+    use futures_util::StreamExt;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    #[tokio::test]
+    async fn scan_until_cursor_wraps() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let server = tokio::spawn(async move {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let mut buf = [0; 1024];
+            let mut requests = vec![];
+            for reply in [
+                &b"*2\r\n$2\r\n17\r\n*2\r\n$2\r\nk1\r\n$2\r\nk2\r\n"[..],
+                &b"*2\r\n$1\r\n0\r\n*1\r\n$2\r\nk3\r\n"[..],
+            ] {
+                let n = socket.read(&mut buf).await.unwrap();
+                requests.push(buf[..n].to_vec());
+                socket.write_all(reply).await.unwrap();
+            }
+            requests
+        });
+
+        let mut client = client::connect(addr).await.unwrap();
+        let keys: Vec<_> = client
+            .scan(Some("k*"), Some(2), None)
+            .map(|v| v.unwrap())
+            .collect()
+            .await;
+        assert_eq!(vec!["k1", "k2", "k3"], keys);
+
+        let requests = server.await.unwrap();
+        assert_eq!(
+            b"*6\r\n$4\r\nSCAN\r\n$1\r\n0\r\n$5\r\nMATCH\r\n$2\r\nk*\r\n$5\r\nCOUNT\r\n$1\r\n2\r\n"
+                .to_vec(),
+            requests[0]
+        );
+        assert_eq!(
+            b"*6\r\n$4\r\nSCAN\r\n$2\r\n17\r\n$5\r\nMATCH\r\n$2\r\nk*\r\n$5\r\nCOUNT\r\n$1\r\n2\r\n".to_vec(),
+            requests[1]
+        );
+    }
+}
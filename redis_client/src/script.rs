@@ -0,0 +1,120 @@
+use crate::{client::Client, error::Error, resp::FromResp};
+use bytes::Bytes;
+
+/// A Lua script invoked by its SHA1 digest.
+///
+/// `invoke` sends EVALSHA, and if the server does not know the script yet
+/// (NOSCRIPT), loads it with SCRIPT LOAD and retries, so the script body is
+/// sent over the wire only once per server (or after SCRIPT FLUSH).
+#[derive(Debug, Clone)]
+pub struct Script {
+    source: String,
+    sha: String,
+}
+
+impl Script {
+    pub fn new(source: impl ToString) -> Script {
+        let source = source.to_string();
+        let sha = sha1_smol::Sha1::from(&source).digest().to_string();
+        Script { source, sha }
+    }
+
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+
+    /// Hex-encoded SHA1 digest of the source.
+    pub fn sha(&self) -> &str {
+        &self.sha
+    }
+
+    /// Runs the script and converts its reply into `T`.
+    pub async fn invoke<T: FromResp>(
+        &self,
+        client: &mut Client,
+        keys: &[&str],
+        args: &[Bytes],
+    ) -> Result<T, Error> {
+        match client.evalsha(&self.sha, keys, args).await {
+            Err(Error::NoScript(_)) => {
+                self.load(client).await?;
+                client.evalsha(&self.sha, keys, args).await
+            }
+            others => others,
+        }
+    }
+
+    /// Loads the script into the script cache of the server.
+    pub async fn load(&self, client: &mut Client) -> Result<(), Error> {
+        let sha = client.script_load(&self.source).await?;
+        if sha != self.sha {
+            return Err(Error::Invalid(format!(
+                "sha1 mismatched: expected {}, but {}",
+                self.sha, sha
+            )));
+        }
+        Ok(())
+    }
+
+    pub async fn exists(&self, client: &mut Client) -> Result<bool, Error> {
+        let exists = client.script_exists(&[&self.sha]).await?;
+        Ok(exists.first().copied().unwrap_or(false))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{client, cmd, resp::Resp, testing::MockServer};
+
+    #[test]
+    fn sha() {
+        // redis-cli SCRIPT LOAD "return 1"
+        let script = Script::new("return 1");
+        assert_eq!("e0e1f9fabfc9d4800c877a703b823ac0578ff8db", script.sha());
+    }
+
+    #[tokio::test]
+    async fn load_on_noscript() {
+        let script = Script::new("return redis.call('GET', KEYS[1])");
+        let server = MockServer::start().await;
+        server
+            .expect(cmd!["EVALSHA", script.sha(), "1", "k", "a"])
+            .reply_error("NOSCRIPT No matching script. Please use EVAL.");
+        server
+            .expect(cmd!["SCRIPT", "LOAD", script.source()])
+            .reply(Resp::BulkString(Bytes::from(script.sha().to_string())));
+        server
+            .expect(cmd!["EVALSHA", script.sha(), "1", "k", "a"])
+            .reply(Resp::BulkString(Bytes::from("v")));
+        // the script is known from then on
+        server
+            .expect(cmd!["EVALSHA", script.sha(), "1", "k", "a"])
+            .reply(Resp::BulkString(Bytes::from("v")));
+
+        let mut client = client::connect(server.addr()).await.unwrap();
+        let args = [Bytes::from("a")];
+        for _ in 0..2 {
+            let value: String = script.invoke(&mut client, &["k"], &args).await.unwrap();
+            assert_eq!("v", value);
+        }
+        server.assert_done();
+    }
+
+    #[tokio::test]
+    async fn load_with_mismatched_sha() {
+        let script = Script::new("return 1");
+        let server = MockServer::start().await;
+        server
+            .expect(cmd!["EVALSHA", script.sha(), "0"])
+            .reply_error("NOSCRIPT No matching script. Please use EVAL.");
+        server
+            .expect(cmd!["SCRIPT", "LOAD", "return 1"])
+            .reply(Resp::BulkString(Bytes::from("0000")));
+
+        let mut client = client::connect(server.addr()).await.unwrap();
+        let result: Result<i64, Error> = script.invoke(&mut client, &[], &[]).await;
+        assert!(matches!(result, Err(Error::Invalid(_))));
+        server.assert_done();
+    }
+}
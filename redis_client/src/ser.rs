@@ -0,0 +1,449 @@
+//! Flattens a struct or a map into the field/value arguments of HSET, behind
+//! the `serde` feature.
+//!
+//! Values must be scalars: numbers and strings are written as text, bool as
+//! `1`/`0`, unit enum variants as their name. `None` fields have no HSET
+//! argument: `to_hash_fields` lists them apart so that `Client::hset_struct`
+//! can remove them with HDEL. The `de` module reads the hash back.
+//!
+//! ```
+//! use redis_client::ser;
+//! use serde::Serialize;
+//!
+//! #[derive(Serialize)]
+//! struct User {
+//!     name: String,
+//!     age: u32,
+//!     email: Option<String>,
+//! }
+//!
+//! let user = User { name: "alice".to_string(), age: 42, email: None };
+//! let args = ser::to_hset_args(&user).unwrap();
+//! assert_eq!(("age".to_string(), "42".into()), args[1]);
+//! assert_eq!(2, args.len());
+//! ```
+
+use crate::error::Error;
+use bytes::Bytes;
+use serde::ser::{self, Impossible, Serialize, SerializeMap, SerializeStruct};
+use std::fmt;
+
+/// The fields of a struct or a map, split between those with a value and
+/// the `None` ones.
+#[derive(Debug, Default, PartialEq)]
+pub struct HashFields {
+    pub set: Vec<(String, Bytes)>,
+    pub cleared: Vec<String>,
+}
+
+pub fn to_hash_fields<T: Serialize + ?Sized>(value: &T) -> Result<HashFields, Error> {
+    value.serialize(FieldsSerializer)
+}
+
+/// The HSET arguments of `value`, `None` fields are left out.
+pub fn to_hset_args<T: Serialize + ?Sized>(value: &T) -> Result<Vec<(String, Bytes)>, Error> {
+    to_hash_fields(value).map(|fields| fields.set)
+}
+
+impl ser::Error for Error {
+    fn custom<T: fmt::Display>(msg: T) -> Self {
+        Error::Serialization(msg.to_string())
+    }
+}
+
+fn unsupported(what: &str) -> Error {
+    Error::Serialization(format!("{} cannot be flattened into HSET arguments", what))
+}
+
+// Serializes the top level struct or map
+struct FieldsSerializer;
+
+macro_rules! unsupported_top_level {
+    ($($method:ident($($arg:ty),*);)*) => {
+        $(
+            fn $method(self, $(_: $arg),*) -> Result<Self::Ok, Error> {
+                Err(unsupported("a value other than a struct or a map"))
+            }
+        )*
+    };
+}
+
+impl ser::Serializer for FieldsSerializer {
+    type Ok = HashFields;
+    type Error = Error;
+    type SerializeSeq = Impossible<Self::Ok, Error>;
+    type SerializeTuple = Impossible<Self::Ok, Error>;
+    type SerializeTupleStruct = Impossible<Self::Ok, Error>;
+    type SerializeTupleVariant = Impossible<Self::Ok, Error>;
+    type SerializeMap = Fields;
+    type SerializeStruct = Fields;
+    type SerializeStructVariant = Impossible<Self::Ok, Error>;
+
+    unsupported_top_level! {
+        serialize_bool(bool);
+        serialize_i8(i8);
+        serialize_i16(i16);
+        serialize_i32(i32);
+        serialize_i64(i64);
+        serialize_u8(u8);
+        serialize_u16(u16);
+        serialize_u32(u32);
+        serialize_u64(u64);
+        serialize_f32(f32);
+        serialize_f64(f64);
+        serialize_char(char);
+        serialize_str(&str);
+        serialize_bytes(&[u8]);
+        serialize_none();
+        serialize_unit();
+        serialize_unit_struct(&'static str);
+        serialize_unit_variant(&'static str, u32, &'static str);
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<Self::Ok, Error> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> Result<Self::Ok, Error> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        _index: u32,
+        _variant: &'static str,
+        _value: &T,
+    ) -> Result<Self::Ok, Error> {
+        Err(unsupported("an enum"))
+    }
+
+    fn serialize_seq(self, _len: Option<usize>) -> Result<Self::SerializeSeq, Error> {
+        Err(unsupported("a sequence"))
+    }
+
+    fn serialize_tuple(self, _len: usize) -> Result<Self::SerializeTuple, Error> {
+        Err(unsupported("a tuple"))
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleStruct, Error> {
+        Err(unsupported("a tuple struct"))
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleVariant, Error> {
+        Err(unsupported("an enum"))
+    }
+
+    fn serialize_map(self, len: Option<usize>) -> Result<Self::SerializeMap, Error> {
+        Ok(Fields::new(len.unwrap_or_default()))
+    }
+
+    fn serialize_struct(self, _name: &'static str, len: usize) -> Result<Fields, Error> {
+        Ok(Fields::new(len))
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStructVariant, Error> {
+        Err(unsupported("an enum"))
+    }
+}
+
+struct Fields {
+    fields: HashFields,
+    // the key of a map entry waiting for its value
+    key: Option<String>,
+}
+
+impl Fields {
+    fn new(len: usize) -> Fields {
+        Fields {
+            fields: HashFields {
+                set: Vec::with_capacity(len),
+                cleared: Vec::new(),
+            },
+            key: None,
+        }
+    }
+
+    fn push<T: Serialize + ?Sized>(&mut self, field: String, value: &T) -> Result<(), Error> {
+        match value.serialize(ValueSerializer)? {
+            Some(value) => self.fields.set.push((field, value)),
+            None => self.fields.cleared.push(field),
+        }
+        Ok(())
+    }
+}
+
+impl SerializeStruct for Fields {
+    type Ok = HashFields;
+    type Error = Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), Error> {
+        self.push(key.to_string(), value)
+    }
+
+    fn end(self) -> Result<Self::Ok, Error> {
+        Ok(self.fields)
+    }
+}
+
+impl SerializeMap for Fields {
+    type Ok = HashFields;
+    type Error = Error;
+
+    fn serialize_key<T: Serialize + ?Sized>(&mut self, key: &T) -> Result<(), Error> {
+        let key = key
+            .serialize(ValueSerializer)?
+            .ok_or_else(|| unsupported("a missing map key"))?;
+        let key = String::from_utf8(key.to_vec())
+            .map_err(|_| Error::Serialization("map keys must be UTF-8".to_string()))?;
+        self.key = Some(key);
+        Ok(())
+    }
+
+    fn serialize_value<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        let key = self
+            .key
+            .take()
+            .ok_or_else(|| Error::Serialization("map value without a key".to_string()))?;
+        self.push(key, value)
+    }
+
+    fn end(self) -> Result<Self::Ok, Error> {
+        Ok(self.fields)
+    }
+}
+
+// Serializes a field value, None if the field must be cleared
+struct ValueSerializer;
+
+macro_rules! serialize_display {
+    ($($method:ident($ty:ty);)*) => {
+        $(
+            fn $method(self, v: $ty) -> Result<Self::Ok, Error> {
+                Ok(Some(Bytes::from(v.to_string())))
+            }
+        )*
+    };
+}
+
+impl ser::Serializer for ValueSerializer {
+    type Ok = Option<Bytes>;
+    type Error = Error;
+    type SerializeSeq = Impossible<Self::Ok, Error>;
+    type SerializeTuple = Impossible<Self::Ok, Error>;
+    type SerializeTupleStruct = Impossible<Self::Ok, Error>;
+    type SerializeTupleVariant = Impossible<Self::Ok, Error>;
+    type SerializeMap = Impossible<Self::Ok, Error>;
+    type SerializeStruct = Impossible<Self::Ok, Error>;
+    type SerializeStructVariant = Impossible<Self::Ok, Error>;
+
+    serialize_display! {
+        serialize_i8(i8);
+        serialize_i16(i16);
+        serialize_i32(i32);
+        serialize_i64(i64);
+        serialize_u8(u8);
+        serialize_u16(u16);
+        serialize_u32(u32);
+        serialize_u64(u64);
+        serialize_f32(f32);
+        serialize_f64(f64);
+        serialize_char(char);
+    }
+
+    fn serialize_bool(self, v: bool) -> Result<Self::Ok, Error> {
+        Ok(Some(Bytes::from_static(if v { b"1" } else { b"0" })))
+    }
+
+    fn serialize_str(self, v: &str) -> Result<Self::Ok, Error> {
+        Ok(Some(Bytes::copy_from_slice(v.as_bytes())))
+    }
+
+    fn serialize_bytes(self, v: &[u8]) -> Result<Self::Ok, Error> {
+        Ok(Some(Bytes::copy_from_slice(v)))
+    }
+
+    fn serialize_none(self) -> Result<Self::Ok, Error> {
+        Ok(None)
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<Self::Ok, Error> {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<Self::Ok, Error> {
+        Ok(None)
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<Self::Ok, Error> {
+        Ok(None)
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+    ) -> Result<Self::Ok, Error> {
+        self.serialize_str(variant)
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> Result<Self::Ok, Error> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        _index: u32,
+        _variant: &'static str,
+        _value: &T,
+    ) -> Result<Self::Ok, Error> {
+        Err(unsupported("an enum variant holding data"))
+    }
+
+    fn serialize_seq(self, _len: Option<usize>) -> Result<Self::SerializeSeq, Error> {
+        Err(unsupported("a nested sequence"))
+    }
+
+    fn serialize_tuple(self, _len: usize) -> Result<Self::SerializeTuple, Error> {
+        Err(unsupported("a nested tuple"))
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleStruct, Error> {
+        Err(unsupported("a nested tuple struct"))
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleVariant, Error> {
+        Err(unsupported("an enum variant holding data"))
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<Self::SerializeMap, Error> {
+        Err(unsupported("a nested map"))
+    }
+
+    fn serialize_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStruct, Error> {
+        Err(unsupported("a nested struct"))
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStructVariant, Error> {
+        Err(unsupported("an enum variant holding data"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{de, resp::Resp};
+    use serde::{Deserialize, Serialize};
+    use std::collections::BTreeMap;
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    #[serde(rename_all = "lowercase")]
+    enum Status {
+        Active,
+    }
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct User {
+        name: String,
+        age: u32,
+        admin: bool,
+        status: Status,
+        email: Option<String>,
+    }
+
+    #[test]
+    fn round_trip() {
+        let user = User {
+            name: "alice".to_string(),
+            age: 42,
+            admin: false,
+            status: Status::Active,
+            email: None,
+        };
+        let fields = to_hash_fields(&user).unwrap();
+        assert_eq!(vec!["email".to_string()], fields.cleared);
+        let args = to_hset_args(&user).unwrap();
+        assert_eq!(
+            vec![
+                ("name".to_string(), Bytes::from("alice")),
+                ("age".to_string(), Bytes::from("42")),
+                ("admin".to_string(), Bytes::from("0")),
+                ("status".to_string(), Bytes::from("active")),
+            ],
+            args
+        );
+
+        // as HGETALL would return them
+        let reply = Resp::Array(
+            args.into_iter()
+                .flat_map(|(f, v)| [Resp::BulkString(Bytes::from(f)), Resp::BulkString(v)])
+                .collect(),
+        );
+        assert_eq!(user, de::from_resp::<User>(reply).unwrap());
+    }
+
+    #[test]
+    fn maps_and_unsupported_values() {
+        let map = BTreeMap::from([(1, 1.5), (2, 2.0)]);
+        assert_eq!(
+            vec![
+                ("1".to_string(), Bytes::from("1.5")),
+                ("2".to_string(), Bytes::from("2")),
+            ],
+            to_hset_args(&map).unwrap()
+        );
+
+        assert!(to_hset_args(&42).is_err());
+        assert!(to_hset_args(&BTreeMap::from([("tags", vec!["a"])])).is_err());
+    }
+}
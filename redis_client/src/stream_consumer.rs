@@ -0,0 +1,517 @@
+use crate::{
+    client::Client,
+    error::Error,
+    streams::{StreamEntry, StreamId},
+};
+use bytes::Bytes;
+use std::collections::HashMap;
+use std::fmt;
+use std::future::Future;
+use std::time::Duration;
+use tokio::time::Instant;
+
+/// Where entries go after being delivered too many times.
+#[derive(Debug, Clone)]
+pub struct DeadLetter {
+    /// Stream to which dead entries are added. Each entry keeps its fields and
+    /// gets `_source_id` and `_deliveries` fields in addition.
+    pub key: String,
+    /// Entries delivered more than this are moved to the dead-letter stream
+    pub max_deliveries: u64,
+}
+
+#[derive(Debug, Clone)]
+pub struct StreamConsumerOptions {
+    /// Maximum number of entries read at once
+    pub count: u64,
+    /// How long XREADGROUP blocks waiting for new entries
+    pub block: Duration,
+    /// How often pending entries of other consumers are checked
+    pub claim_interval: Duration,
+    /// Pending entries idle longer than this are claimed by this consumer
+    pub min_idle: Duration,
+    pub dead_letter: Option<DeadLetter>,
+}
+
+impl Default for StreamConsumerOptions {
+    fn default() -> Self {
+        StreamConsumerOptions {
+            count: 10,
+            block: Duration::from_secs(5),
+            claim_interval: Duration::from_secs(30),
+            min_idle: Duration::from_secs(60),
+            dead_letter: None,
+        }
+    }
+}
+
+/// A worker of a consumer group.
+///
+/// It reads entries with XREADGROUP, passes each of them to a handler, and
+/// acknowledges the ones the handler succeeded with. Entries the handler failed
+/// with are logged as warnings, stay pending and are retried once they have
+/// been idle for `min_idle`, which is also how entries of dead consumers are
+/// taken over.
+///
+/// ```no_run
+/// # use redis_client::{client, error::Error};
+/// # use redis_client::stream_consumer::{StreamConsumer, StreamConsumerOptions};
+/// # async fn run() -> Result<(), Error> {
+/// let client = client::connect("127.0.0.1:6379").await?;
+/// let options = StreamConsumerOptions::default();
+/// let mut consumer = StreamConsumer::new(client, "events", "workers", "worker-1", options).await?;
+/// consumer
+///     .run(|entry| async move {
+///         println!("{:?}", entry);
+///         Ok::<(), Error>(())
+///     })
+///     .await
+/// # }
+/// ```
+pub struct StreamConsumer {
+    client: Client,
+    key: String,
+    group: String,
+    consumer: String,
+    options: StreamConsumerOptions,
+    // the PEL of this consumer is processed before reading new entries
+    pending_start: Option<StreamId>,
+    claim_start: StreamId,
+    next_claim: Instant,
+}
+
+impl StreamConsumer {
+    /// Joins `group`, creating it (and the stream) if it does not exist yet.
+    /// A newly created group starts with new entries only.
+    pub async fn new(
+        mut client: Client,
+        key: &str,
+        group: &str,
+        consumer: &str,
+        options: StreamConsumerOptions,
+    ) -> Result<StreamConsumer, Error> {
+        match client.xgroup_create(key, group, "$", true).await {
+            Ok(()) => {}
+            Err(Error::Generic { code, .. }) if code == "BUSYGROUP" => {}
+            Err(e) => return Err(e),
+        }
+
+        Ok(StreamConsumer {
+            client,
+            key: key.to_string(),
+            group: group.to_string(),
+            consumer: consumer.to_string(),
+            pending_start: Some(StreamId::MIN),
+            claim_start: StreamId::MIN,
+            next_claim: Instant::now(),
+            options,
+        })
+    }
+
+    /// Processes entries until an error occurs on the connection.
+    pub async fn run<F, Fut, E>(&mut self, mut handler: F) -> Result<(), Error>
+    where
+        F: FnMut(StreamEntry) -> Fut,
+        Fut: Future<Output = Result<(), E>>,
+        E: fmt::Display,
+    {
+        loop {
+            self.run_once(&mut handler).await?;
+        }
+    }
+
+    /// Runs a single iteration: the PEL of this consumer (until it is drained),
+    /// idle entries of other consumers (every `claim_interval`), then a batch of
+    /// new entries. Returns the number of acknowledged entries.
+    pub async fn run_once<F, Fut, E>(&mut self, handler: &mut F) -> Result<u64, Error>
+    where
+        F: FnMut(StreamEntry) -> Fut,
+        Fut: Future<Output = Result<(), E>>,
+        E: fmt::Display,
+    {
+        let mut acked = 0;
+
+        if let Some(start) = self.pending_start {
+            let entries = self.read(&start.to_string(), None).await?;
+            // XREADGROUP returns pending entries with IDs greater than the given one
+            self.pending_start = entries.last().map(|e| e.id);
+            let entries = self.bury_dead(entries).await?;
+            acked += self.handle(entries, handler).await?;
+        }
+
+        if Instant::now() >= self.next_claim {
+            let entries = self.claim().await?;
+            let entries = self.bury_dead(entries).await?;
+            acked += self.handle(entries, handler).await?;
+        }
+
+        let entries = self.read(">", Some(self.options.block)).await?;
+        acked += self.handle(entries, handler).await?;
+        Ok(acked)
+    }
+
+    pub fn into_inner(self) -> Client {
+        self.client
+    }
+
+    async fn read(&mut self, id: &str, block: Option<Duration>) -> Result<Vec<StreamEntry>, Error> {
+        let streams = self
+            .client
+            .xreadgroup(
+                &self.group,
+                &self.consumer,
+                &[(&self.key, id)],
+                Some(self.options.count),
+                block,
+                false,
+            )
+            .await?;
+        Ok(streams
+            .into_iter()
+            .flat_map(|(_, entries)| entries)
+            .collect())
+    }
+
+    async fn claim(&mut self) -> Result<Vec<StreamEntry>, Error> {
+        let claimed = self
+            .client
+            .xautoclaim(
+                &self.key,
+                &self.group,
+                &self.consumer,
+                self.options.min_idle,
+                self.claim_start,
+                Some(self.options.count),
+            )
+            .await?;
+
+        // keep claiming from where we left off until the whole PEL is scanned
+        self.claim_start = claimed.next;
+        if claimed.next == StreamId::MIN {
+            self.next_claim = Instant::now() + self.options.claim_interval;
+        }
+        Ok(claimed.entries)
+    }
+
+    // Moves entries delivered too many times to the dead-letter stream and
+    // returns the rest
+    async fn bury_dead(&mut self, entries: Vec<StreamEntry>) -> Result<Vec<StreamEntry>, Error> {
+        let dead_letter = match &self.options.dead_letter {
+            Some(dead_letter) => dead_letter.clone(),
+            None => return Ok(entries),
+        };
+
+        let (first, last) = match (entries.first(), entries.last()) {
+            (Some(first), Some(last)) => (first.id.to_string(), last.id.to_string()),
+            _ => return Ok(entries),
+        };
+        // the delivery counts of the whole batch, which is sorted by ID
+        let deliveries: HashMap<StreamId, u64> = self
+            .client
+            .xpending_range(
+                &self.key,
+                &self.group,
+                None,
+                &first,
+                &last,
+                entries.len() as u64,
+                Some(&self.consumer),
+            )
+            .await?
+            .into_iter()
+            .map(|p| (p.id, p.deliveries))
+            .collect();
+
+        let mut alive = Vec::with_capacity(entries.len());
+        for entry in entries {
+            let deliveries = match deliveries.get(&entry.id) {
+                Some(&deliveries) => deliveries,
+                None => continue, // acknowledged by someone else in the meantime
+            };
+
+            if deliveries <= dead_letter.max_deliveries {
+                alive.push(entry);
+                continue;
+            }
+
+            let source_id = Bytes::from(entry.id.to_string());
+            let deliveries = Bytes::from(deliveries.to_string());
+            let mut fields: Vec<(&str, Bytes)> = entry
+                .fields
+                .iter()
+                .map(|(f, v)| (f.as_str(), v.clone()))
+                .collect();
+            fields.push(("_source_id", source_id));
+            fields.push(("_deliveries", deliveries));
+            self.client
+                .xadd(&dead_letter.key, None, None, &fields)
+                .await?;
+            self.client
+                .xack(&self.key, &self.group, &[entry.id])
+                .await?;
+        }
+        Ok(alive)
+    }
+
+    async fn handle<F, Fut, E>(
+        &mut self,
+        entries: Vec<StreamEntry>,
+        handler: &mut F,
+    ) -> Result<u64, Error>
+    where
+        F: FnMut(StreamEntry) -> Fut,
+        Fut: Future<Output = Result<(), E>>,
+        E: fmt::Display,
+    {
+        let mut acked = vec![];
+        for entry in entries {
+            let id = entry.id;
+            // entries deleted from the stream while pending have no fields
+            if entry.fields.is_empty() {
+                acked.push(id);
+                continue;
+            }
+            match handler(entry).await {
+                Ok(()) => acked.push(id),
+                Err(e) => tracing::warn!(id = %id, error = %e, "stream entry handler failed"),
+            }
+        }
+
+        if acked.is_empty() {
+            return Ok(0);
+        }
+        self.client.xack(&self.key, &self.group, &acked).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{client, cmd, resp::Resp, testing::bulk, testing::MockServer};
+
+    fn entry(id: &str, field: &str, value: &str) -> Resp {
+        Resp::Array(vec![bulk(id), Resp::Array(vec![bulk(field), bulk(value)])])
+    }
+
+    // reply of XREADGROUP on the "events" stream
+    fn read_reply(entries: Vec<Resp>) -> Resp {
+        Resp::Array(vec![Resp::Array(vec![
+            bulk("events"),
+            Resp::Array(entries),
+        ])])
+    }
+
+    fn expect_read(server: &MockServer, id: &str, reply: Resp) {
+        let read = if id == ">" {
+            cmd![
+                "XREADGROUP",
+                "GROUP",
+                "workers",
+                "w1",
+                "COUNT",
+                "10",
+                "BLOCK",
+                "100",
+                "STREAMS",
+                "events",
+                ">"
+            ]
+        } else {
+            cmd![
+                "XREADGROUP",
+                "GROUP",
+                "workers",
+                "w1",
+                "COUNT",
+                "10",
+                "STREAMS",
+                "events",
+                id
+            ]
+        };
+        server.expect(read).reply(reply);
+    }
+
+    fn expect_claim(server: &MockServer, start: &str, next: &str, entries: Vec<Resp>) {
+        server
+            .expect(cmd![
+                "XAUTOCLAIM",
+                "events",
+                "workers",
+                "w1",
+                "60000",
+                start,
+                "COUNT",
+                "10"
+            ])
+            .reply(Resp::Array(vec![
+                bulk(next),
+                Resp::Array(entries),
+                Resp::Array(vec![]),
+            ]));
+    }
+
+    fn expect_ack(server: &MockServer, ids: &[&str]) {
+        let mut ack = vec!["XACK", "events", "workers"];
+        ack.extend(ids);
+        server
+            .expect(Resp::Array(ack.into_iter().map(bulk).collect()))
+            .reply(Resp::Integer(ids.len() as i64));
+    }
+
+    async fn consumer(server: &MockServer, dead_letter: Option<DeadLetter>) -> StreamConsumer {
+        server
+            .expect(cmd![
+                "XGROUP", "CREATE", "events", "workers", "$", "MKSTREAM"
+            ])
+            .reply_error("BUSYGROUP Consumer Group name already exists");
+        let client = client::connect(server.addr()).await.unwrap();
+        let options = StreamConsumerOptions {
+            block: Duration::from_millis(100),
+            claim_interval: Duration::from_secs(3600),
+            dead_letter,
+            ..StreamConsumerOptions::default()
+        };
+        StreamConsumer::new(client, "events", "workers", "w1", options)
+            .await
+            .unwrap()
+    }
+
+    // fails the entries whose value is "fail"
+    async fn handler(entry: StreamEntry) -> Result<(), String> {
+        match entry.fields.get("v") {
+            Some(v) if v == "fail" => Err(format!("cannot handle {}", entry.id)),
+            _ => Ok(()),
+        }
+    }
+
+    #[tokio::test]
+    async fn create_group() {
+        let server = MockServer::start().await;
+        consumer(&server, None).await;
+        server
+            .expect(cmd![
+                "XGROUP", "CREATE", "events", "workers", "$", "MKSTREAM"
+            ])
+            .reply_error("WRONGTYPE Operation against a key holding the wrong kind of value");
+
+        let client = client::connect(server.addr()).await.unwrap();
+        let options = StreamConsumerOptions::default();
+        let consumer = StreamConsumer::new(client, "events", "workers", "w1", options).await;
+        assert!(matches!(consumer, Err(Error::WrongType(_))));
+        server.assert_done();
+    }
+
+    #[tokio::test]
+    async fn drain_own_pel() {
+        let server = MockServer::start().await;
+        let mut consumer = consumer(&server, None).await;
+
+        // the PEL left by a previous run, then new entries
+        expect_read(
+            &server,
+            "0-0",
+            read_reply(vec![entry("1-1", "v", "ok"), entry("2-1", "v", "fail")]),
+        );
+        expect_ack(&server, &["1-1"]);
+        expect_claim(&server, "0-0", "0-0", vec![]);
+        expect_read(&server, ">", Resp::Null);
+        assert_eq!(1, consumer.run_once(&mut handler).await.unwrap());
+
+        expect_read(&server, "2-1", read_reply(vec![]));
+        expect_read(&server, ">", read_reply(vec![entry("3-1", "v", "ok")]));
+        expect_ack(&server, &["3-1"]);
+        assert_eq!(1, consumer.run_once(&mut handler).await.unwrap());
+
+        // the PEL is drained and the next claim is not due
+        expect_read(&server, ">", Resp::Null);
+        assert_eq!(0, consumer.run_once(&mut handler).await.unwrap());
+        server.assert_done();
+    }
+
+    #[tokio::test]
+    async fn autoclaim_cursor_wrap() {
+        let server = MockServer::start().await;
+        let mut consumer = consumer(&server, None).await;
+
+        expect_read(&server, "0-0", read_reply(vec![]));
+        expect_claim(&server, "0-0", "5-0", vec![entry("4-1", "v", "ok")]);
+        expect_ack(&server, &["4-1"]);
+        expect_read(&server, ">", Resp::Null);
+        assert_eq!(1, consumer.run_once(&mut handler).await.unwrap());
+
+        // the scan continues from the cursor until it wraps to 0-0
+        expect_claim(&server, "5-0", "0-0", vec![]);
+        expect_read(&server, ">", Resp::Null);
+        assert_eq!(0, consumer.run_once(&mut handler).await.unwrap());
+
+        expect_read(&server, ">", Resp::Null);
+        assert_eq!(0, consumer.run_once(&mut handler).await.unwrap());
+        server.assert_done();
+    }
+
+    #[tokio::test]
+    async fn dead_letter() {
+        let server = MockServer::start().await;
+        let dead_letter = DeadLetter {
+            key: "dead".to_string(),
+            max_deliveries: 2,
+        };
+        let mut consumer = consumer(&server, Some(dead_letter)).await;
+
+        expect_read(
+            &server,
+            "0-0",
+            read_reply(vec![
+                entry("1-1", "v", "a"),
+                entry("2-1", "v", "b"),
+                entry("3-1", "v", "c"),
+            ]),
+        );
+        // a single XPENDING for the batch; 3-1 was acknowledged meanwhile
+        server
+            .expect(cmd![
+                "XPENDING", "events", "workers", "1-1", "3-1", "3", "w1"
+            ])
+            .reply(Resp::Array(vec![
+                Resp::Array(vec![
+                    bulk("1-1"),
+                    bulk("w1"),
+                    Resp::Integer(1000),
+                    Resp::Integer(3),
+                ]),
+                Resp::Array(vec![
+                    bulk("2-1"),
+                    bulk("w1"),
+                    Resp::Integer(1000),
+                    Resp::Integer(2),
+                ]),
+            ]));
+        server
+            .expect(cmd![
+                "XADD",
+                "dead",
+                "*",
+                "v",
+                "a",
+                "_source_id",
+                "1-1",
+                "_deliveries",
+                "3"
+            ])
+            .reply(bulk("9-0"));
+        expect_ack(&server, &["1-1"]);
+        expect_ack(&server, &["2-1"]);
+        expect_claim(&server, "0-0", "0-0", vec![]);
+        expect_read(&server, ">", Resp::Null);
+
+        let mut handled = vec![];
+        let mut handler = |entry: StreamEntry| {
+            handled.push(entry.id.to_string());
+            async { Ok::<(), Error>(()) }
+        };
+        assert_eq!(1, consumer.run_once(&mut handler).await.unwrap());
+        assert_eq!(vec!["2-1"], handled);
+        server.assert_done();
+    }
+}
@@ -0,0 +1,450 @@
+use crate::{error::Error, resp::Resp};
+use bytes::Bytes;
+use std::{collections::HashMap, fmt, str::FromStr, time::Duration};
+
+/// ID of a stream entry, `<milliseconds>-<sequence>`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct StreamId {
+    pub ms: u64,
+    pub seq: u64,
+}
+
+impl StreamId {
+    pub const MIN: StreamId = StreamId { ms: 0, seq: 0 };
+
+    pub fn new(ms: u64, seq: u64) -> Self {
+        StreamId { ms, seq }
+    }
+
+    pub(crate) fn from_resp(resp: Resp) -> Result<StreamId, Error> {
+        resp.into_string()?.parse()
+    }
+}
+
+impl fmt::Display for StreamId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}-{}", self.ms, self.seq)
+    }
+}
+
+impl FromStr for StreamId {
+    type Err = Error;
+
+    // a missing sequence part is treated as 0, as Redis does
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || Error::Invalid(format!("invalid stream id: {:?}", s));
+        let (ms, seq) = match s.split_once('-') {
+            Some((ms, seq)) => (ms, seq),
+            None => (s, "0"),
+        };
+
+        Ok(StreamId {
+            ms: ms.parse().map_err(|_| invalid())?,
+            seq: seq.parse().map_err(|_| invalid())?,
+        })
+    }
+}
+
+/// Trimming strategy of XADD and XTRIM.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StreamTrim {
+    /// MAXLEN: keep at most N entries
+    MaxLen(u64),
+    /// MINID: evict entries with IDs lower than the given one
+    MinId(StreamId),
+}
+
+/// Trimming options of XADD and XTRIM.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StreamTrimOptions {
+    pub strategy: StreamTrim,
+    /// `~`: trim only whole macro nodes, which is much more efficient
+    pub approximate: bool,
+    /// LIMIT: maximum number of entries evicted at once (requires `approximate`)
+    pub limit: Option<u64>,
+}
+
+impl StreamTrimOptions {
+    pub fn maxlen(len: u64) -> Self {
+        StreamTrimOptions {
+            strategy: StreamTrim::MaxLen(len),
+            approximate: false,
+            limit: None,
+        }
+    }
+
+    pub fn minid(id: StreamId) -> Self {
+        StreamTrimOptions {
+            strategy: StreamTrim::MinId(id),
+            approximate: false,
+            limit: None,
+        }
+    }
+
+    pub fn approximate(mut self) -> Self {
+        self.approximate = true;
+        self
+    }
+
+    pub fn limit(mut self, limit: u64) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+
+    pub(crate) fn push_to(&self, ary: &mut Resp) {
+        match self.strategy {
+            StreamTrim::MaxLen(len) => {
+                ary.push_bulk_strings(Bytes::from("MAXLEN"));
+                self.push_threshold(ary, len.to_string());
+            }
+            StreamTrim::MinId(id) => {
+                ary.push_bulk_strings(Bytes::from("MINID"));
+                self.push_threshold(ary, id.to_string());
+            }
+        }
+        if let Some(limit) = self.limit {
+            ary.push_bulk_strings(Bytes::from("LIMIT"));
+            ary.push_bulk_strings(Bytes::from(limit.to_string()));
+        }
+    }
+
+    fn push_threshold(&self, ary: &mut Resp, threshold: String) {
+        if self.approximate {
+            ary.push_bulk_strings(Bytes::from("~"));
+        }
+        ary.push_bulk_strings(Bytes::from(threshold));
+    }
+}
+
+/// An entry of a stream.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StreamEntry {
+    pub id: StreamId,
+    /// Empty if the entry was deleted while it was pending (XREADGROUP and XCLAIM)
+    pub fields: HashMap<String, Bytes>,
+}
+
+impl StreamEntry {
+    // [id, [field1, value1, ...]]
+    pub(crate) fn from_resp(resp: Resp) -> Result<StreamEntry, Error> {
+        let mut iter = resp.into_array()?.into_iter();
+        match (iter.next(), iter.next()) {
+            (Some(id), Some(Resp::Null)) => Ok(StreamEntry {
+                id: StreamId::from_resp(id)?,
+                fields: HashMap::new(),
+            }),
+            (Some(id), Some(fields)) => Ok(StreamEntry {
+                id: StreamId::from_resp(id)?,
+                fields: fields.into_pairs()?.into_iter().collect(),
+            }),
+            _ => Err(Error::Invalid("[id, fields] is expected".to_string())),
+        }
+    }
+
+    pub(crate) fn from_resp_array(resp: Resp) -> Result<Vec<StreamEntry>, Error> {
+        resp.into_array()?
+            .into_iter()
+            .map(StreamEntry::from_resp)
+            .collect()
+    }
+}
+
+// [[key, [entry...]], ...] or nil (on timeout)
+pub(crate) fn from_read_resp(resp: Resp) -> Result<Vec<(String, Vec<StreamEntry>)>, Error> {
+    if let Resp::Null = resp {
+        return Ok(vec![]);
+    }
+
+    resp.into_array()?
+        .into_iter()
+        .map(|stream| {
+            let mut iter = stream.into_array()?.into_iter();
+            match (iter.next(), iter.next()) {
+                (Some(key), Some(entries)) => {
+                    Ok((key.into_string()?, StreamEntry::from_resp_array(entries)?))
+                }
+                _ => Err(Error::Invalid("[key, entries] is expected".to_string())),
+            }
+        })
+        .collect()
+}
+
+/// Reply of XPENDING in the summary form.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PendingSummary {
+    pub count: u64,
+    /// None if there are no pending entries
+    pub min: Option<StreamId>,
+    pub max: Option<StreamId>,
+    /// Consumer names with the number of their pending entries
+    pub consumers: Vec<(String, u64)>,
+}
+
+impl PendingSummary {
+    pub(crate) fn from_resp(resp: Resp) -> Result<PendingSummary, Error> {
+        let mut iter = resp.into_array()?.into_iter();
+        let (count, min, max, consumers) =
+            match (iter.next(), iter.next(), iter.next(), iter.next()) {
+                (Some(count), Some(min), Some(max), Some(consumers)) => {
+                    (count, min, max, consumers)
+                }
+                _ => return Err(Error::Invalid("invalid xpending response".to_string())),
+            };
+
+        let optional_id = |resp: Resp| match resp {
+            Resp::Null => Ok(None),
+            resp => StreamId::from_resp(resp).map(Some),
+        };
+        let consumers = match consumers {
+            Resp::Null => vec![],
+            consumers => consumers
+                .into_array()?
+                .into_iter()
+                .map(|consumer| {
+                    let mut iter = consumer.into_array()?.into_iter();
+                    match (iter.next(), iter.next()) {
+                        // the count is sent as a bulk string
+                        (Some(name), Some(count)) => {
+                            let count = count.into_string()?;
+                            let count = count.parse::<u64>().map_err(|e| {
+                                Error::Invalid(format!("invalid count {:?}: {}", count, e))
+                            })?;
+                            Ok((name.into_string()?, count))
+                        }
+                        _ => Err(Error::Invalid("[name, count] is expected".to_string())),
+                    }
+                })
+                .collect::<Result<_, Error>>()?,
+        };
+
+        Ok(PendingSummary {
+            count: count.into_integer()? as u64,
+            min: optional_id(min)?,
+            max: optional_id(max)?,
+            consumers,
+        })
+    }
+}
+
+/// An entry of XPENDING in the extended form.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PendingEntry {
+    pub id: StreamId,
+    pub consumer: String,
+    /// Time since the entry was last delivered
+    pub idle: Duration,
+    pub deliveries: u64,
+}
+
+impl PendingEntry {
+    // [id, consumer, idle, deliveries]
+    pub(crate) fn from_resp(resp: Resp) -> Result<PendingEntry, Error> {
+        let mut iter = resp.into_array()?.into_iter();
+        match (iter.next(), iter.next(), iter.next(), iter.next()) {
+            (Some(id), Some(consumer), Some(idle), Some(deliveries)) => Ok(PendingEntry {
+                id: StreamId::from_resp(id)?,
+                consumer: consumer.into_string()?,
+                idle: Duration::from_millis(idle.into_integer()? as u64),
+                deliveries: deliveries.into_integer()? as u64,
+            }),
+            _ => Err(Error::Invalid(
+                "[id, consumer, idle, deliveries] is expected".to_string(),
+            )),
+        }
+    }
+}
+
+/// Reply of XAUTOCLAIM.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AutoClaim {
+    /// Start ID for the next call. `StreamId::MIN` means the whole PEL was scanned
+    pub next: StreamId,
+    pub entries: Vec<StreamEntry>,
+    /// IDs that were in the PEL but no longer exist in the stream (Redis 7.0 or later)
+    pub deleted: Vec<StreamId>,
+}
+
+impl AutoClaim {
+    pub(crate) fn from_resp(resp: Resp) -> Result<AutoClaim, Error> {
+        let mut iter = resp.into_array()?.into_iter();
+        let (next, entries) = match (iter.next(), iter.next()) {
+            (Some(next), Some(entries)) => (next, entries),
+            _ => return Err(Error::Invalid("invalid xautoclaim response".to_string())),
+        };
+
+        // entries deleted from the stream are reported as nil by Redis 6.2
+        let entries = entries
+            .into_array()?
+            .into_iter()
+            .filter(|v| !matches!(v, Resp::Null))
+            .map(StreamEntry::from_resp)
+            .collect::<Result<_, _>>()?;
+        let deleted = match iter.next() {
+            Some(deleted) => deleted
+                .into_array()?
+                .into_iter()
+                .map(StreamId::from_resp)
+                .collect::<Result<_, _>>()?,
+            None => vec![],
+        };
+
+        Ok(AutoClaim {
+            next: StreamId::from_resp(next)?,
+            entries,
+            deleted,
+        })
+    }
+}
+
+/// Reply of XINFO STREAM.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StreamInfo {
+    pub length: u64,
+    pub radix_tree_keys: u64,
+    pub radix_tree_nodes: u64,
+    pub groups: u64,
+    pub last_generated_id: StreamId,
+    pub first_entry: Option<StreamEntry>,
+    pub last_entry: Option<StreamEntry>,
+}
+
+impl StreamInfo {
+    pub(crate) fn from_resp(resp: Resp) -> Result<StreamInfo, Error> {
+        let mut info = StreamInfo {
+            length: 0,
+            radix_tree_keys: 0,
+            radix_tree_nodes: 0,
+            groups: 0,
+            last_generated_id: StreamId::MIN,
+            first_entry: None,
+            last_entry: None,
+        };
+
+        for (name, value) in resp.into_fields()? {
+            match name.as_str() {
+                "length" => info.length = value.into_integer()? as u64,
+                "radix-tree-keys" => info.radix_tree_keys = value.into_integer()? as u64,
+                "radix-tree-nodes" => info.radix_tree_nodes = value.into_integer()? as u64,
+                "groups" => info.groups = value.into_integer()? as u64,
+                "last-generated-id" => info.last_generated_id = StreamId::from_resp(value)?,
+                "first-entry" => info.first_entry = optional_entry(value)?,
+                "last-entry" => info.last_entry = optional_entry(value)?,
+                _ => {} // fields added by newer versions
+            }
+        }
+        Ok(info)
+    }
+}
+
+/// An element of XINFO GROUPS.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GroupInfo {
+    pub name: String,
+    pub consumers: u64,
+    pub pending: u64,
+    pub last_delivered_id: StreamId,
+}
+
+impl GroupInfo {
+    pub(crate) fn from_resp(resp: Resp) -> Result<GroupInfo, Error> {
+        let mut info = GroupInfo {
+            name: String::new(),
+            consumers: 0,
+            pending: 0,
+            last_delivered_id: StreamId::MIN,
+        };
+
+        for (name, value) in resp.into_fields()? {
+            match name.as_str() {
+                "name" => info.name = value.into_string()?,
+                "consumers" => info.consumers = value.into_integer()? as u64,
+                "pending" => info.pending = value.into_integer()? as u64,
+                "last-delivered-id" => info.last_delivered_id = StreamId::from_resp(value)?,
+                _ => {}
+            }
+        }
+        Ok(info)
+    }
+}
+
+/// An element of XINFO CONSUMERS.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConsumerInfo {
+    pub name: String,
+    pub pending: u64,
+    /// Time since the consumer last interacted with the server
+    pub idle: Duration,
+}
+
+impl ConsumerInfo {
+    pub(crate) fn from_resp(resp: Resp) -> Result<ConsumerInfo, Error> {
+        let mut info = ConsumerInfo {
+            name: String::new(),
+            pending: 0,
+            idle: Duration::ZERO,
+        };
+
+        for (name, value) in resp.into_fields()? {
+            match name.as_str() {
+                "name" => info.name = value.into_string()?,
+                "pending" => info.pending = value.into_integer()? as u64,
+                "idle" => info.idle = Duration::from_millis(value.into_integer()? as u64),
+                _ => {}
+            }
+        }
+        Ok(info)
+    }
+}
+
+fn optional_entry(resp: Resp) -> Result<Option<StreamEntry>, Error> {
+    match resp {
+        Resp::Null => Ok(None),
+        resp => StreamEntry::from_resp(resp).map(Some),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_stream_id() {
+        assert_eq!(
+            StreamId::new(1526919030474, 55),
+            "1526919030474-55".parse().unwrap()
+        );
+        assert_eq!(StreamId::new(10, 0), "10".parse().unwrap());
+        assert!("10-".parse::<StreamId>().is_err());
+        assert!("abc".parse::<StreamId>().is_err());
+        assert_eq!(
+            "1526919030474-55",
+            StreamId::new(1526919030474, 55).to_string()
+        );
+    }
+
+    #[test]
+    fn parse_read_reply() {
+        let resp = Resp::Array(vec![Resp::Array(vec![
+            Resp::BulkString("s1".into()),
+            Resp::Array(vec![
+                Resp::Array(vec![
+                    Resp::BulkString("1-0".into()),
+                    Resp::Array(vec![
+                        Resp::BulkString("f".into()),
+                        Resp::BulkString("v".into()),
+                    ]),
+                ]),
+                Resp::Array(vec![Resp::BulkString("1-1".into()), Resp::Null]),
+            ]),
+        ])]);
+
+        let streams = from_read_resp(resp).unwrap();
+        assert_eq!(1, streams.len());
+        assert_eq!("s1", streams[0].0);
+        assert_eq!(StreamId::new(1, 0), streams[0].1[0].id);
+        assert_eq!(Bytes::from("v"), streams[0].1[0].fields["f"]);
+        assert!(streams[0].1[1].fields.is_empty());
+
+        assert!(from_read_resp(Resp::Null).unwrap().is_empty());
+    }
+}
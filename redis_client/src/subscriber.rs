@@ -1,21 +1,78 @@
-use crate::{
-    client::Client,
-    error::Error,
-    pubsub::{self, Message},
-};
+use crate::{client::Client, error::Error, pubsub};
+
+pub use crate::pubsub::Message;
 
 pub struct Subscriber<'a> {
     client: &'a mut Client,
     channels: Vec<String>,
+    patterns: Vec<String>,
+    // subscription counts confirmed by the server
+    counts: Vec<u64>,
 }
 
 impl<'a> Subscriber<'a> {
-    pub fn new(client: &'a mut Client, channels: Vec<String>) -> Subscriber {
-        Subscriber { client, channels }
+    pub fn new(client: &'a mut Client, channels: Vec<String>) -> Subscriber<'a> {
+        Subscriber {
+            client,
+            channels,
+            patterns: vec![],
+            counts: vec![],
+        }
+    }
+
+    pub(crate) fn new_patterns(client: &'a mut Client, patterns: Vec<String>) -> Subscriber<'a> {
+        Subscriber {
+            client,
+            channels: vec![],
+            patterns,
+            counts: vec![],
+        }
+    }
+
+    pub(crate) fn with_counts(mut self, counts: Vec<u64>) -> Subscriber<'a> {
+        self.counts = counts;
+        self
+    }
+
+    pub fn channels(&self) -> &[String] {
+        &self.channels
+    }
+
+    pub fn patterns(&self) -> &[String] {
+        &self.patterns
+    }
+
+    /// The number of subscriptions of the connection confirmed by the server
+    /// after each channel or pattern, in order. Empty unless created by
+    /// `Client::subscribe` or `Client::psubscribe`.
+    pub fn subscription_counts(&self) -> &[u64] {
+        &self.counts
     }
 
-    pub async fn next_message(&'a mut self) -> Result<Message, Error> {
-        let resp = self.client.read_response().await?;
+    pub async fn next_message(&mut self) -> Result<Message, Error> {
+        let resp = self.client.read_message().await?;
         pubsub::parse(&resp)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::{client, cmd, resp::Resp, testing::bulk, testing::MockServer};
+
+    #[tokio::test]
+    async fn subscription_counts() {
+        let server = MockServer::start().await;
+        server.expect(cmd!["Subscribe", "a", "a"]).replies(vec![
+            Resp::Array(vec![bulk("subscribe"), bulk("a"), Resp::Integer(1)]),
+            Resp::Array(vec![bulk("subscribe"), bulk("a"), Resp::Integer(1)]),
+        ]);
+
+        let mut client = client::connect(server.addr()).await.unwrap();
+        let subscriber = client
+            .subscribe(vec!["a".to_string(), "a".to_string()])
+            .await
+            .unwrap();
+        assert_eq!(&[1, 1], subscriber.subscription_counts());
+        server.assert_done();
+    }
+}
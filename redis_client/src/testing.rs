@@ -0,0 +1,334 @@
+//! In-process mock server to test code using the client without a real Redis.
+//!
+//! ```ignore
+//! use redis_client::{client, cmd, resp::Resp, testing::MockServer};
+//!
+//! # #[tokio::main]
+//! # async fn main() {
+//! let server = MockServer::start().await;
+//! server.expect(cmd!["GET", "k"]).reply(Resp::Null);
+//!
+//! let mut client = client::connect(server.addr()).await.unwrap();
+//! assert_eq!(None, client.get("k").await.unwrap());
+//! server.assert_done();
+//! # }
+//! ```
+
+use crate::codec::RespCodec;
+use crate::resp::Resp;
+use bytes::Bytes;
+use futures_util::{SinkExt, StreamExt};
+use std::collections::VecDeque;
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::net::{TcpListener, TcpStream};
+use tokio::task::JoinHandle;
+use tokio_util::codec::Framed;
+
+/// Builds a command as sent by the client: `cmd!["SET", "k", "v"]`.
+#[macro_export]
+macro_rules! cmd {
+    ($($arg:expr),* $(,)?) => {
+        $crate::resp::Resp::Array(vec![$($crate::testing::bulk(&$arg)),*])
+    };
+}
+
+#[doc(hidden)]
+pub fn bulk<T: AsRef<[u8]> + ?Sized>(arg: &T) -> Resp {
+    Resp::BulkString(Bytes::copy_from_slice(arg.as_ref()))
+}
+
+#[derive(Debug)]
+enum Action {
+    Reply(Vec<Resp>),
+    Drop,
+}
+
+#[derive(Debug)]
+struct Scripted {
+    command: Resp,
+    // indexes of the arguments matching any value
+    any_args: Vec<usize>,
+    delay: Option<Duration>,
+    action: Action,
+}
+
+impl Scripted {
+    fn matches(&self, command: &Resp) -> bool {
+        match (&self.command, command) {
+            (Resp::Array(expected), Resp::Array(actual)) if expected.len() == actual.len() => {
+                expected
+                    .iter()
+                    .zip(actual)
+                    .enumerate()
+                    .all(|(i, (e, a))| self.any_args.contains(&i) || e == a)
+            }
+            (expected, actual) => expected == actual,
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct State {
+    expected: VecDeque<Scripted>,
+    received: Vec<Resp>,
+    failures: Vec<String>,
+}
+
+/// A server accepting any number of connections on an ephemeral local port.
+/// Commands received on any connection must match the expectations in the
+/// order they were scripted; a mismatch gets an error reply and is reported
+/// by `assert_done`.
+#[derive(Debug)]
+pub struct MockServer {
+    addr: SocketAddr,
+    state: Arc<Mutex<State>>,
+    task: JoinHandle<()>,
+}
+
+/// An expected command, registered by one of the methods choosing the reply.
+#[must_use = "the expectation is registered by reply, replies, reply_error or drop_connection"]
+#[derive(Debug)]
+pub struct Expectation<'a> {
+    server: &'a MockServer,
+    command: Resp,
+    any_args: Vec<usize>,
+    delay: Option<Duration>,
+}
+
+impl MockServer {
+    pub async fn start() -> MockServer {
+        let listener = TcpListener::bind("127.0.0.1:0")
+            .await
+            .expect("failed to bind the mock server");
+        let addr = listener
+            .local_addr()
+            .expect("failed to get the local address");
+        let state = Arc::new(Mutex::new(State::default()));
+
+        let task = tokio::spawn(accept(listener, state.clone()));
+
+        MockServer { addr, state, task }
+    }
+
+    pub fn addr(&self) -> SocketAddr {
+        self.addr
+    }
+
+    pub fn expect(&self, command: Resp) -> Expectation<'_> {
+        Expectation {
+            server: self,
+            command,
+            any_args: vec![],
+            delay: None,
+        }
+    }
+
+    /// Commands matched so far, in the order they were received, to check
+    /// arguments accepted by `Expectation::any_arg`.
+    pub fn received(&self) -> Vec<Resp> {
+        self.state.lock().unwrap().received.clone()
+    }
+
+    /// Panics if a command did not match or an expectation was not met.
+    pub fn assert_done(&self) {
+        let state = self.state.lock().unwrap();
+        if !state.failures.is_empty() {
+            panic!("unexpected commands: {:#?}", state.failures);
+        }
+        if !state.expected.is_empty() {
+            let commands: Vec<_> = state.expected.iter().map(|s| &s.command).collect();
+            panic!("expected commands not received: {:#?}", commands);
+        }
+    }
+
+    fn push(&self, scripted: Scripted) {
+        self.state.lock().unwrap().expected.push_back(scripted);
+    }
+}
+
+impl Drop for MockServer {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+impl Expectation<'_> {
+    /// Accepts any value for the argument at `index` (0 is the command name),
+    /// such as a random token.
+    pub fn any_arg(mut self, index: usize) -> Self {
+        self.any_args.push(index);
+        self
+    }
+
+    /// Waits before acting on the command, to trigger client timeouts.
+    pub fn delay(mut self, delay: Duration) -> Self {
+        self.delay = Some(delay);
+        self
+    }
+
+    pub fn reply(self, reply: Resp) {
+        self.register(Action::Reply(vec![reply]));
+    }
+
+    /// Replies with several frames in a row, such as the confirmation of a
+    /// SUBSCRIBE followed by the messages pushed on the channel.
+    pub fn replies(self, replies: Vec<Resp>) {
+        self.register(Action::Reply(replies));
+    }
+
+    /// Replies with an error such as `"ERR unknown command"`.
+    pub fn reply_error(self, msg: &str) {
+        self.register(Action::Reply(vec![Resp::Error(msg.to_string())]));
+    }
+
+    /// Closes the connection instead of replying.
+    pub fn drop_connection(self) {
+        self.register(Action::Drop);
+    }
+
+    fn register(self, action: Action) {
+        self.server.push(Scripted {
+            command: self.command,
+            any_args: self.any_args,
+            delay: self.delay,
+            action,
+        });
+    }
+}
+
+async fn accept(listener: TcpListener, state: Arc<Mutex<State>>) {
+    // dropping the set when the server is dropped aborts the connections
+    let mut connections = tokio::task::JoinSet::new();
+    while let Ok((socket, _)) = listener.accept().await {
+        connections.spawn(serve(socket, state.clone()));
+    }
+}
+
+async fn serve(socket: TcpStream, state: Arc<Mutex<State>>) {
+    let mut framed = Framed::new(socket, RespCodec::new());
+
+    while let Some(Ok(command)) = framed.next().await {
+        let scripted = {
+            let mut state = state.lock().unwrap();
+            match state.expected.pop_front() {
+                Some(scripted) if scripted.matches(&command) => {
+                    state.received.push(command);
+                    Ok(scripted)
+                }
+                Some(scripted) => {
+                    let failure = format!("expected {:?}, got {:?}", scripted.command, command);
+                    state.expected.push_front(scripted);
+                    state.failures.push(failure.clone());
+                    Err(failure)
+                }
+                None => {
+                    let failure = format!("no command expected, got {:?}", command);
+                    state.failures.push(failure.clone());
+                    Err(failure)
+                }
+            }
+        };
+
+        let scripted = match scripted {
+            Ok(scripted) => scripted,
+            Err(failure) => {
+                let reply = Resp::Error(format!("ERR mock server: {}", failure));
+                if framed.send(reply).await.is_err() {
+                    return;
+                }
+                continue;
+            }
+        };
+
+        if let Some(delay) = scripted.delay {
+            tokio::time::sleep(delay).await;
+        }
+        match scripted.action {
+            Action::Reply(replies) => {
+                for reply in replies {
+                    if framed.send(reply).await.is_err() {
+                        return;
+                    }
+                }
+            }
+            Action::Drop => return,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{client, error::Error};
+    use std::io::ErrorKind;
+
+    #[tokio::test]
+    async fn scripted_replies() {
+        let server = MockServer::start().await;
+        server
+            .expect(cmd!["GET", "k"])
+            .reply(Resp::BulkString(Bytes::from("v")));
+        server
+            .expect(cmd!["INCR", "k"])
+            .reply_error("ERR value is not an integer or out of range");
+
+        let mut client = client::connect(server.addr()).await.unwrap();
+        assert_eq!(Some(Bytes::from("v")), client.get("k").await.unwrap());
+        assert!(matches!(
+            client.incr("k").await,
+            Err(Error::Generic { code, .. }) if code == "ERR"
+        ));
+        server.assert_done();
+    }
+
+    #[tokio::test]
+    async fn delay_and_drop() {
+        let server = MockServer::start().await;
+        server
+            .expect(cmd!["PING"])
+            .delay(Duration::from_millis(200))
+            .reply(Resp::SimpleString("PONG".into()));
+        server.expect(cmd!["PING"]).drop_connection();
+
+        let mut client = client::Client::builder()
+            .command_timeout(Duration::from_millis(50))
+            .connect(server.addr())
+            .await
+            .unwrap();
+        match client.ping().await {
+            Err(Error::IO(e)) => assert_eq!(ErrorKind::TimedOut, e.kind()),
+            others => panic!("unexpected result {:?}", others),
+        }
+
+        let mut client = client::connect(server.addr()).await.unwrap();
+        assert!(client.ping().await.unwrap_err().is_connection_error());
+        server.assert_done();
+    }
+
+    #[tokio::test]
+    async fn any_arg() {
+        let server = MockServer::start().await;
+        server
+            .expect(cmd!["SET", "a", ""])
+            .any_arg(2)
+            .reply(Resp::SimpleString("OK".into()));
+
+        let mut client = client::connect(server.addr()).await.unwrap();
+        client.set("a", Bytes::from("random")).await.unwrap();
+        assert_eq!(vec![cmd!["SET", "a", "random"]], server.received());
+        server.assert_done();
+    }
+
+    #[tokio::test]
+    #[should_panic(expected = "unexpected commands")]
+    async fn unexpected_command() {
+        let server = MockServer::start().await;
+        server.expect(cmd!["GET", "a"]).reply(Resp::Null);
+
+        let mut client = client::connect(server.addr()).await.unwrap();
+        assert!(client.get("b").await.is_err());
+        server.assert_done();
+    }
+}